@@ -1,5 +1,14 @@
-use crate::{AvroResult, Error};
+use crate::{
+    util::{safe_len, zag_i32, zag_i64, zig_i32, zig_i64},
+    AvroResult, Error,
+};
 use num_bigint::{BigInt, Sign};
+use std::{
+    convert::TryFrom,
+    fmt,
+    io::{Cursor, Read},
+    str::FromStr,
+};
 
 #[derive(Debug, Clone)]
 pub struct Decimal {
@@ -55,3 +64,153 @@ impl<T: AsRef<[u8]>> From<T> for Decimal {
         }
     }
 }
+
+/// A decimal number of arbitrary scale: the value of the `big-decimal` logical type, which stores
+/// the scale of each value along with its unscaled `Decimal` value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BigDecimal {
+    unscaled: Decimal,
+    scale: i32,
+}
+
+impl BigDecimal {
+    /// Create the decimal number `unscaled * 10^-scale`.
+    pub fn new(unscaled: Decimal, scale: i32) -> Self {
+        Self { unscaled, scale }
+    }
+
+    /// The unscaled value of the number.
+    pub fn unscaled(&self) -> &Decimal {
+        &self.unscaled
+    }
+
+    /// The number of digits after the decimal point, or before it if negative.
+    pub fn scale(&self) -> i32 {
+        self.scale
+    }
+}
+
+/// The content of the `bytes` of a `big-decimal`: the unscaled value, encoded as Avro `bytes`,
+/// followed by the scale, encoded as an Avro `int`.
+impl From<&BigDecimal> for Vec<u8> {
+    fn from(decimal: &BigDecimal) -> Self {
+        let unscaled = decimal.unscaled.value.to_signed_bytes_be();
+        let mut bytes = Vec::with_capacity(unscaled.len() + 10);
+        zig_i64(unscaled.len() as i64, &mut bytes);
+        bytes.extend_from_slice(&unscaled);
+        zig_i32(decimal.scale, &mut bytes);
+        bytes
+    }
+}
+
+impl TryFrom<&[u8]> for BigDecimal {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let mut reader = Cursor::new(bytes);
+        let len = zag_i64(&mut reader)?;
+        let len = safe_len(usize::try_from(len).map_err(|e| Error::ConvertI64ToUsize(e, len))?)?;
+        let mut unscaled = vec![0u8; len];
+        reader.read_exact(&mut unscaled).map_err(Error::ReadBytes)?;
+        let scale = zag_i32(&mut reader)?;
+        let trailing = bytes.len() - reader.position() as usize;
+        if trailing > 0 {
+            return Err(Error::BigDecimalTrailingBytes(trailing));
+        }
+        Ok(Self::new(Decimal::from(unscaled), scale))
+    }
+}
+
+/// Formats the number in plain notation, like `-12.345`.
+impl fmt::Display for BigDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.unscaled.value.to_string();
+        let (sign, digits) = match value.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", value.as_str()),
+        };
+        if self.scale <= 0 {
+            let zeros = if digits == "0" {
+                0
+            } else {
+                (-(self.scale as i64)) as usize
+            };
+            return write!(f, "{}{}{}", sign, digits, "0".repeat(zeros));
+        }
+        let scale = self.scale as usize;
+        if digits.len() > scale {
+            let (int, frac) = digits.split_at(digits.len() - scale);
+            write!(f, "{}{}.{}", sign, int, frac)
+        } else {
+            write!(
+                f,
+                "{}0.{}{}",
+                sign,
+                "0".repeat(scale - digits.len()),
+                digits
+            )
+        }
+    }
+}
+
+/// Parses a number in plain notation, like `-12.345`, keeping the number of digits after the
+/// decimal point as its scale.
+impl FromStr for BigDecimal {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::ParseBigDecimal(s.to_string());
+        let (int, frac) = match s.find('.') {
+            Some(index) => (&s[..index], &s[index + 1..]),
+            None => (s, ""),
+        };
+        let unsigned = int.trim_start_matches(&['-', '+'][..]);
+        if int.len() - unsigned.len() > 1
+            || (unsigned.is_empty() && frac.is_empty())
+            || !unsigned
+                .chars()
+                .chain(frac.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+        let scale = i32::try_from(frac.len()).map_err(|_| invalid())?;
+        let value = BigInt::from_str(&format!("{}{}", int, frac)).map_err(|_| invalid())?;
+        Ok(Self::new(Decimal::from(value.to_signed_bytes_be()), scale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_big_decimal_bytes() {
+        let decimal = BigDecimal::from_str("-123.45").unwrap();
+        assert_eq!(decimal.scale(), 2);
+        let bytes = Vec::from(&decimal);
+        // The 2 bytes of -12345, then the scale 2.
+        assert_eq!(bytes, vec![4, 0xcf, 0xc7, 4]);
+        assert_eq!(BigDecimal::try_from(&bytes[..]).unwrap(), decimal);
+        assert!(BigDecimal::try_from(&bytes[..3]).is_err());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            BigDecimal::try_from(&trailing[..]),
+            Err(Error::BigDecimalTrailingBytes(1))
+        ));
+    }
+
+    #[test]
+    fn test_big_decimal_string() {
+        for s in &["0", "-1", "123.45", "-0.0012", "0.10", "+7"] {
+            let decimal = BigDecimal::from_str(s).unwrap();
+            assert_eq!(decimal.to_string(), s.trim_start_matches('+'));
+        }
+        assert_eq!(BigDecimal::new(Decimal::from([12]), -2).to_string(), "1200");
+        assert_eq!(BigDecimal::from_str(".5").unwrap().to_string(), "0.5");
+        for s in &["", "-", "1.2.3", "--1", "1e3", " 1", "-+1"] {
+            assert!(BigDecimal::from_str(s).is_err(), "{:?}", s);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
-    decimal::Decimal,
+    decimal::{BigDecimal, Decimal},
     duration::Duration,
-    schema::Schema,
+    schema::{Names, Schema},
     types::Value,
     util::{safe_len, zag_i32, zag_i64},
     AvroResult, Error,
@@ -46,7 +46,16 @@ fn decode_seq_len<R: Read>(reader: &mut R) -> AvroResult<usize> {
 
 /// Decode a `Value` from avro format given its `Schema`.
 pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> AvroResult<Value> {
-    match *schema {
+    decode_internal(schema, &mut Names::new(schema), reader)
+}
+
+/// Same as `decode`, resolving references to named types through `names`.
+pub(crate) fn decode_internal<'s, R: Read>(
+    schema: &'s Schema,
+    names: &mut Names<'s>,
+    reader: &mut R,
+) -> AvroResult<Value> {
+    match *names.resolve(schema)? {
         Schema::Null => Ok(Value::Null),
         Schema::Boolean => {
             let mut buf = [0u8; 1];
@@ -60,8 +69,8 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> AvroResult<Value> {
                 _ => Err(Error::BoolValue(buf[0])),
             }
         }
-        Schema::Decimal { ref inner, .. } => match &**inner {
-            Schema::Fixed { .. } => match decode(inner, reader)? {
+        Schema::Decimal { ref inner, .. } => match names.resolve_fixed(inner)? {
+            fixed @ Schema::Fixed { .. } => match decode_internal(fixed, names, reader)? {
                 Value::Fixed(_, bytes) => Ok(Value::Decimal(Decimal::from(bytes))),
                 value => Err(Error::FixedValue(value.into())),
             },
@@ -71,13 +80,24 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> AvroResult<Value> {
             },
             schema => Err(Error::ResolveDecimalSchema(schema.into())),
         },
-        Schema::Uuid => Ok(Value::Uuid(
-            Uuid::from_str(match decode(&Schema::String, reader)? {
+        Schema::BigDecimal => match decode(&Schema::Bytes, reader)? {
+            Value::Bytes(bytes) => BigDecimal::try_from(&bytes[..]).map(Value::BigDecimal),
+            value => Err(Error::BytesValue(value.into())),
+        },
+        Schema::Uuid { ref inner } => Ok(Value::Uuid(match names.resolve_fixed(inner)? {
+            Schema::Fixed { .. } => {
+                let mut buf = [0u8; 16];
+                reader
+                    .read_exact(&mut buf)
+                    .map_err(|e| Error::ReadFixed(e, 16))?;
+                Uuid::from_bytes(buf)
+            }
+            _ => Uuid::from_str(match decode(&Schema::String, reader)? {
                 Value::String(ref s) => s,
                 value => return Err(Error::GetUuidFromStringValue(value.into())),
             })
             .map_err(Error::ConvertStrToUuid)?,
-        )),
+        })),
         Schema::Int => decode_int(reader),
         Schema::Date => zag_i32(reader).map(Value::Date),
         Schema::TimeMillis => zag_i32(reader).map(Value::TimeMillis),
@@ -85,6 +105,10 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> AvroResult<Value> {
         Schema::TimeMicros => zag_i64(reader).map(Value::TimeMicros),
         Schema::TimestampMillis => zag_i64(reader).map(Value::TimestampMillis),
         Schema::TimestampMicros => zag_i64(reader).map(Value::TimestampMicros),
+        Schema::LocalTimestampMillis => zag_i64(reader).map(Value::LocalTimestampMillis),
+        Schema::LocalTimestampMicros => zag_i64(reader).map(Value::LocalTimestampMicros),
+        Schema::TimestampNanos => zag_i64(reader).map(Value::TimestampNanos),
+        Schema::LocalTimestampNanos => zag_i64(reader).map(Value::LocalTimestampNanos),
         Schema::Duration => {
             let mut buf = [0u8; 12];
             reader.read_exact(&mut buf).map_err(Error::ReadDuration)?;
@@ -133,7 +157,7 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> AvroResult<Value> {
 
                 items.reserve(len);
                 for _ in 0..len {
-                    items.push(decode(inner, reader)?);
+                    items.push(decode_internal(inner, names, reader)?);
                 }
             }
 
@@ -152,7 +176,7 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> AvroResult<Value> {
                 for _ in 0..len {
                     match decode(&Schema::String, reader)? {
                         Value::String(key) => {
-                            let value = decode(inner, reader)?;
+                            let value = decode_internal(inner, names, reader)?;
                             items.insert(key, value);
                         }
                         value => return Err(Error::MapKeyType(value.into())),
@@ -165,21 +189,26 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> AvroResult<Value> {
         Schema::Union(ref inner) => {
             let index = zag_i64(reader)?;
             let variants = inner.variants();
+            let position =
+                usize::try_from(index).map_err(|e| Error::ConvertI64ToUsize(e, index))?;
             let variant = variants
-                .get(usize::try_from(index).map_err(|e| Error::ConvertI64ToUsize(e, index))?)
+                .get(position)
                 .ok_or_else(|| Error::GetUnionVariant {
                     index,
                     num_variants: variants.len(),
                 })?;
-            let value = decode(variant, reader)?;
-            Ok(Value::Union(Box::new(value)))
+            let value = decode_internal(variant, names, reader)?;
+            Ok(Value::Union(inner.branch(position), Box::new(value)))
         }
         Schema::Record { ref fields, .. } => {
             // Benchmarks indicate ~10% improvement using this method.
             let mut items = Vec::with_capacity(fields.len());
             for field in fields {
                 // TODO: This clone is also expensive. See if we can do away with it...
-                items.push((field.name.clone(), decode(&field.schema, reader)?));
+                items.push((
+                    field.name.clone(),
+                    decode_internal(&field.schema, names, reader)?,
+                ));
             }
             Ok(Value::Record(items))
         }
@@ -200,6 +229,13 @@ pub fn decode<R: Read>(schema: &Schema, reader: &mut R) -> AvroResult<Value> {
                 return Err(Error::GetEnumSymbol);
             })
         }
+        Schema::Custom {
+            ref logical_type,
+            ref inner,
+        } => logical_type.to_custom(decode_internal(inner, names, reader)?),
+        Schema::Ref { .. } | Schema::Attributed { .. } => {
+            unreachable!("references and attributes are resolved above")
+        }
     }
 }
 
@@ -255,6 +291,8 @@ mod tests {
         let inner = Box::new(Schema::Fixed {
             size: 2,
             name: Name::new("decimal"),
+            doc: None,
+            attributes: Default::default(),
         });
         let schema = Schema::Decimal {
             inner,
@@ -265,7 +303,7 @@ mod tests {
         let value = Value::Decimal(Decimal::from(bigint.to_signed_bytes_be()));
 
         let mut buffer = Vec::new();
-        encode(&value, &schema, &mut buffer);
+        encode(&value, &schema, &mut buffer).unwrap();
 
         let mut bytes = &buffer[..];
         let result = decode(&schema, &mut bytes).unwrap();
@@ -279,6 +317,8 @@ mod tests {
         let inner = Box::new(Schema::Fixed {
             size: 13,
             name: Name::new("decimal"),
+            doc: None,
+            attributes: Default::default(),
         });
         let schema = Schema::Decimal {
             inner,
@@ -290,7 +330,7 @@ mod tests {
         ));
         let mut buffer = Vec::<u8>::new();
 
-        encode(&value, &schema, &mut buffer);
+        encode(&value, &schema, &mut buffer).unwrap();
         let mut bytes: &[u8] = &buffer[..];
         let result = decode(&schema, &mut bytes).unwrap();
         assert_eq!(result, value);
@@ -1,22 +1,27 @@
 use crate::{
-    schema::Schema,
+    decimal::BigDecimal,
+    schema::{Names, Schema},
     types::Value,
     util::{zig_i32, zig_i64},
+    AvroResult, Error,
 };
-use std::convert::TryInto;
+use std::{convert::TryInto, str::FromStr};
+use uuid::Uuid;
 
 /// Encode a `Value` into avro format.
 ///
 /// **NOTE** This will not perform schema validation. The value is assumed to
 /// be valid with regards to the schema. Schema are needed only to guide the
 /// encoding for complex type values.
-pub fn encode(value: &Value, schema: &Schema, buffer: &mut Vec<u8>) {
+///
+/// Fails if a reference to a named type cannot be resolved.
+pub fn encode(value: &Value, schema: &Schema, buffer: &mut Vec<u8>) -> AvroResult<()> {
     encode_ref(&value, schema, buffer)
 }
 
 fn encode_bytes<B: AsRef<[u8]> + ?Sized>(s: &B, buffer: &mut Vec<u8>) {
     let bytes = s.as_ref();
-    encode(&Value::Long(bytes.len() as i64), &Schema::Long, buffer);
+    encode_long(bytes.len() as i64, buffer);
     buffer.extend_from_slice(bytes);
 }
 
@@ -33,7 +38,28 @@ fn encode_int(i: i32, buffer: &mut Vec<u8>) {
 /// **NOTE** This will not perform schema validation. The value is assumed to
 /// be valid with regards to the schema. Schema are needed only to guide the
 /// encoding for complex type values.
-pub fn encode_ref(value: &Value, schema: &Schema, buffer: &mut Vec<u8>) {
+///
+/// Fails if a reference to a named type cannot be resolved.
+pub fn encode_ref(value: &Value, schema: &Schema, buffer: &mut Vec<u8>) -> AvroResult<()> {
+    encode_internal(value, schema, &mut Names::new(schema), buffer)
+}
+
+/// Same as `encode_ref`, resolving references to named types through `names`.
+pub(crate) fn encode_internal<'s>(
+    value: &Value,
+    schema: &'s Schema,
+    names: &mut Names<'s>,
+    buffer: &mut Vec<u8>,
+) -> AvroResult<()> {
+    let schema = names.resolve(schema)?;
+    if let Schema::Custom {
+        ref logical_type,
+        ref inner,
+    } = *schema
+    {
+        let base = logical_type.to_base(value, |value| value.validate_with_names(inner, names))?;
+        return encode_internal(&base, inner, names, buffer);
+    }
     match value {
         Value::Null => (),
         Value::Boolean(b) => buffer.push(if *b { 1u8 } else { 0u8 }),
@@ -42,11 +68,15 @@ pub fn encode_ref(value: &Value, schema: &Schema, buffer: &mut Vec<u8>) {
         Value::Long(i)
         | Value::TimestampMillis(i)
         | Value::TimestampMicros(i)
+        | Value::LocalTimestampMillis(i)
+        | Value::LocalTimestampMicros(i)
+        | Value::TimestampNanos(i)
+        | Value::LocalTimestampNanos(i)
         | Value::TimeMicros(i) => encode_long(*i, buffer),
         Value::Float(x) => buffer.extend_from_slice(&x.to_le_bytes()),
         Value::Double(x) => buffer.extend_from_slice(&x.to_le_bytes()),
         Value::Decimal(decimal) => match schema {
-            Schema::Decimal { inner, .. } => match *inner.clone() {
+            Schema::Decimal { inner, .. } => match *names.resolve_fixed(inner)? {
                 Schema::Fixed { size, .. } => {
                     let bytes = decimal.to_sign_extended_bytes_with_len(size).unwrap();
                     let num_bytes = bytes.len();
@@ -56,20 +86,28 @@ pub fn encode_ref(value: &Value, schema: &Schema, buffer: &mut Vec<u8>) {
                             num_bytes, size
                         );
                     }
-                    encode(&Value::Fixed(size, bytes), inner, buffer)
+                    encode_internal(&Value::Fixed(size, bytes), inner, names, buffer)?
                 }
-                Schema::Bytes => encode(&Value::Bytes(decimal.try_into().unwrap()), inner, buffer),
+                Schema::Bytes => encode(&Value::Bytes(decimal.try_into().unwrap()), inner, buffer)?,
                 _ => panic!("invalid inner type for decimal: {:?}", inner),
             },
             _ => panic!("invalid type for decimal: {:?}", schema),
         },
+        Value::BigDecimal(decimal) => encode_bytes(&Vec::from(decimal), buffer),
         &Value::Duration(duration) => {
             let slice: [u8; 12] = duration.into();
             buffer.extend_from_slice(&slice);
         }
-        Value::Uuid(uuid) => encode_bytes(&uuid.to_string(), buffer),
+        Value::Uuid(uuid) => match *schema {
+            Schema::Uuid { ref inner }
+                if matches!(names.resolve_fixed(inner), Ok(Schema::Fixed { .. })) =>
+            {
+                buffer.extend_from_slice(uuid.as_bytes())
+            }
+            _ => encode_bytes(&uuid.to_string(), buffer),
+        },
         Value::Bytes(bytes) => match *schema {
-            Schema::Bytes => encode_bytes(bytes, buffer),
+            Schema::Bytes | Schema::BigDecimal => encode_bytes(bytes, buffer),
             Schema::Fixed { .. } => buffer.extend(bytes),
             _ => (),
         },
@@ -82,19 +120,32 @@ pub fn encode_ref(value: &Value, schema: &Schema, buffer: &mut Vec<u8>) {
                     encode_int(index as i32, buffer);
                 }
             }
+            Schema::BigDecimal => encode_bytes(&Vec::from(&BigDecimal::from_str(s)?), buffer),
+            Schema::Uuid { ref inner } => match names.resolve_fixed(inner)? {
+                Schema::Fixed { .. } => {
+                    if let Ok(uuid) = Uuid::from_str(s) {
+                        buffer.extend_from_slice(uuid.as_bytes());
+                    }
+                }
+                _ => encode_bytes(s, buffer),
+            },
             _ => (),
         },
         Value::Fixed(_, bytes) => buffer.extend(bytes),
         Value::Enum(i, _) => encode_int(*i, buffer),
-        Value::Union(item) => {
+        Value::Union(branch, item) => {
             if let Schema::Union(ref inner) = *schema {
-                // Find the schema that is matched here. Due to validation, this should always
-                // return a value.
-                let (idx, inner_schema) = inner
-                    .find_schema(item)
-                    .expect("Invalid Union validation occurred");
+                // Use the branch of the value if it is known, or else find the schema that is
+                // matched here. Due to validation, this should always return a value.
+                let (idx, inner_schema) = match branch {
+                    Some(branch) => inner
+                        .find_branch(branch)
+                        .map(|inner_schema| (branch.index, inner_schema)),
+                    None => inner.find_schema_with_names(item, names),
+                }
+                .expect("Invalid Union validation occurred");
                 encode_long(idx as i64, buffer);
-                encode_ref(&*item, inner_schema, buffer);
+                encode_internal(&*item, inner_schema, names, buffer)?;
             }
         }
         Value::Array(items) => {
@@ -102,7 +153,7 @@ pub fn encode_ref(value: &Value, schema: &Schema, buffer: &mut Vec<u8>) {
                 if !items.is_empty() {
                     encode_long(items.len() as i64, buffer);
                     for item in items.iter() {
-                        encode_ref(item, inner, buffer);
+                        encode_internal(item, inner, names, buffer)?;
                     }
                 }
                 buffer.push(0u8);
@@ -114,7 +165,7 @@ pub fn encode_ref(value: &Value, schema: &Schema, buffer: &mut Vec<u8>) {
                     encode_long(items.len() as i64, buffer);
                     for (key, value) in items {
                         encode_bytes(key, buffer);
-                        encode_ref(value, inner, buffer);
+                        encode_internal(value, inner, names, buffer)?;
                     }
                 }
                 buffer.push(0u8);
@@ -127,22 +178,25 @@ pub fn encode_ref(value: &Value, schema: &Schema, buffer: &mut Vec<u8>) {
             } = *schema
             {
                 for (i, &(_, ref value)) in fields.iter().enumerate() {
-                    encode_ref(value, &schema_fields[i].schema, buffer);
+                    encode_internal(value, &schema_fields[i].schema, names, buffer)?;
                 }
             }
         }
+        Value::Custom(name, _) => return Err(Error::CustomValueSchema(name.clone())),
     }
+    Ok(())
 }
 
-pub fn encode_to_vec(value: &Value, schema: &Schema) -> Vec<u8> {
+pub fn encode_to_vec(value: &Value, schema: &Schema) -> AvroResult<Vec<u8>> {
     let mut buffer = Vec::new();
-    encode(&value, schema, &mut buffer);
-    buffer
+    encode(&value, schema, &mut buffer)?;
+    Ok(buffer)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{schema::Name, Error};
     use std::collections::HashMap;
 
     #[test]
@@ -153,7 +207,8 @@ mod tests {
             &Value::Array(empty),
             &Schema::Array(Box::new(Schema::Int)),
             &mut buf,
-        );
+        )
+        .unwrap();
         assert_eq!(vec![0u8], buf);
     }
 
@@ -165,7 +220,60 @@ mod tests {
             &Value::Map(empty),
             &Schema::Map(Box::new(Schema::Int)),
             &mut buf,
-        );
+        )
+        .unwrap();
         assert_eq!(vec![0u8], buf);
     }
+
+    #[test]
+    fn test_encode_unresolved_reference() {
+        let schema = Schema::Ref {
+            name: Name::new("Node"),
+        };
+        let mut buf = Vec::new();
+        match encode(&Value::Null, &schema, &mut buf) {
+            Err(Error::GetNamedSchema(name)) => assert_eq!(name, "Node"),
+            other => panic!("Expected Error::GetNamedSchema, got {:?}", other),
+        }
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_encode_unparseable_big_decimal() {
+        let mut buf = Vec::new();
+        match encode(&Value::String("1,5".into()), &Schema::BigDecimal, &mut buf) {
+            Err(Error::ParseBigDecimal(s)) => assert_eq!(s, "1,5"),
+            other => panic!("Expected Error::ParseBigDecimal, got {:?}", other),
+        }
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_encode_union_branch() {
+        let schema =
+            Schema::parse_str(r#"["long", {"type": "long", "logicalType": "timestamp-millis"}]"#)
+                .unwrap();
+        let union_schema = match schema {
+            Schema::Union(ref union_schema) => union_schema,
+            _ => unreachable!(),
+        };
+
+        let mut buf = Vec::new();
+        encode(
+            &Value::Union(None, Box::new(Value::Long(1))),
+            &schema,
+            &mut buf,
+        )
+        .unwrap();
+        assert_eq!(vec![0u8, 2u8], buf);
+
+        let mut buf = Vec::new();
+        encode(
+            &Value::Union(union_schema.branch(1), Box::new(Value::Long(1))),
+            &schema,
+            &mut buf,
+        )
+        .unwrap();
+        assert_eq!(vec![2u8, 2u8], buf);
+    }
 }
@@ -1,4 +1,7 @@
-use crate::{schema::SchemaKind, types::ValueKind};
+use crate::{
+    schema::{SchemaKind, SchemaLocation, SchemaViolation},
+    types::{Value, ValueKind},
+};
 use std::fmt;
 
 #[derive(thiserror::Error, Debug)]
@@ -62,6 +65,9 @@ pub enum Error {
     #[error("Failed to convert &str to UUID")]
     ConvertStrToUuid(#[source] uuid::Error),
 
+    #[error("Failed to convert bytes to UUID")]
+    ConvertSliceToUuid(#[source] uuid::Error),
+
     #[error("Map key is not a string; key type is {0:?}")]
     MapKeyType(ValueKind),
 
@@ -85,6 +91,9 @@ pub enum Error {
     #[error("expected UUID, got: {0:?}")]
     GetUuid(ValueKind),
 
+    #[error("Fixed of size 16 expected for a uuid, got size {0}")]
+    GetUuidFixedSize(usize),
+
     #[error("Fixed bytes of size 12 expected, got Fixed of size {0}")]
     GetDecimalFixedBytes(usize),
 
@@ -94,6 +103,15 @@ pub enum Error {
     #[error("Decimal expected, got {0:?}")]
     ResolveDecimal(ValueKind),
 
+    #[error("BigDecimal expected, got {0:?}")]
+    ResolveBigDecimal(ValueKind),
+
+    #[error("Failed to parse a big decimal from {0:?}")]
+    ParseBigDecimal(String),
+
+    #[error("{0} unexpected bytes after a big decimal")]
+    BigDecimalTrailingBytes(usize),
+
     #[error("Missing field in record: {0:?}")]
     GetField(String),
 
@@ -121,12 +139,27 @@ pub enum Error {
     #[error("TimestampMicros expected, got {0:?}")]
     GetTimestampMicros(ValueKind),
 
+    #[error("LocalTimestampMillis expected, got {0:?}")]
+    GetLocalTimestampMillis(ValueKind),
+
+    #[error("LocalTimestampMicros expected, got {0:?}")]
+    GetLocalTimestampMicros(ValueKind),
+
+    #[error("TimestampNanos expected, got {0:?}")]
+    GetTimestampNanos(ValueKind),
+
+    #[error("LocalTimestampNanos expected, got {0:?}")]
+    GetLocalTimestampNanos(ValueKind),
+
     #[error("Null expected, got {0:?}")]
     GetNull(ValueKind),
 
     #[error("Boolean expected, got {0:?}")]
     GetBoolean(ValueKind),
 
+    #[error("{from:?} cannot be resolved as {to:?}: not a promotion of the Avro specification")]
+    ResolvePromotion { from: ValueKind, to: SchemaKind },
+
     #[error("Int expected, got {0:?}")]
     GetInt(ValueKind),
 
@@ -160,6 +193,16 @@ pub enum Error {
         symbols: Vec<String>,
     },
 
+    #[error("Enum default must be a string, got {0}")]
+    GetEnumDefaultFromJson(serde_json::Value),
+
+    #[error("Invalid default value for field {field:?}: {value} is not a valid {kind:?}")]
+    GetDefaultValue {
+        field: String,
+        value: serde_json::Value,
+        kind: SchemaKind,
+    },
+
     #[error("Enum value index {index} is out of bounds {nsymbols}")]
     GetEnumValue { index: usize, nsymbols: usize },
 
@@ -199,6 +242,9 @@ pub enum Error {
     #[error("Unions cannot contain duplicate types")]
     GetUnionDuplicate,
 
+    #[error("Unions cannot contain duplicate named types: {0}")]
+    GetUnionDuplicateName(String),
+
     #[error("JSON value {0} claims to be u64 but cannot be converted")]
     GetU64FromJson(serde_json::Number),
 
@@ -223,9 +269,12 @@ pub enum Error {
     #[error("Must be a JSON string, object or array")]
     ParseSchemaFromValidJson,
 
-    #[error("Unknown primitiive type: {0}")]
+    #[error("Unknown primitive type: {0}")]
     ParsePrimitive(String),
 
+    #[error("Reference to undefined named type: {0}")]
+    GetNamedSchema(String),
+
     #[error("invalid JSON for {key:?}: {precision:?}")]
     GetDecimalPrecisionFromJson {
         key: String,
@@ -241,6 +290,33 @@ pub enum Error {
     #[error("logicalType must be a string")]
     GetLogicalTypeFieldType,
 
+    #[error("Logical type {logical_type:?} cannot annotate a schema of kind {kind:?}")]
+    LogicalTypeBaseKind {
+        logical_type: String,
+        kind: SchemaKind,
+    },
+
+    #[error("Invalid schema for logical type {logical_type:?}: {reason}")]
+    ValidateLogicalTypeSchema {
+        logical_type: String,
+        reason: String,
+    },
+
+    #[error("Failed to convert a value of logical type {logical_type:?}: {reason}")]
+    ConvertLogicalType {
+        logical_type: String,
+        reason: String,
+    },
+
+    #[error("Invalid value for logical type {0:?}")]
+    LogicalTypeValue(String),
+
+    #[error("Value of logical type {0:?} for a schema which is not annotated with it")]
+    CustomValueSchema(String),
+
+    #[error("Only named types hold custom attributes, not a schema of kind {0:?}")]
+    SetAttribute(SchemaKind),
+
     #[error("Unknown complex type: {0}")]
     GetComplexType(serde_json::Value),
 
@@ -256,9 +332,6 @@ pub enum Error {
     #[error("Unable to parse `symbols` in enum")]
     GetEnumSymbols,
 
-    #[error("Invalid enum symbol name {0}")]
-    EnumSymbolName(String),
-
     #[error("Duplicate enum symbol {0}")]
     EnumSymbolDuplicate(String),
 
@@ -271,6 +344,71 @@ pub enum Error {
     #[error("No `size` in fixed")]
     GetFixedSizeField,
 
+    #[error("Fixed size must not be negative, got {0}")]
+    GetFixedSizeNegative(i64),
+
+    #[error("Invalid schema: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    ValidateSchema(Vec<SchemaViolation>),
+
+    #[error("Failed to read schema file {1:?}")]
+    ReadSchemaFile(#[source] std::io::Error, std::path::PathBuf),
+
+    #[error("Invalid schema file {1:?}: {0}")]
+    LoadSchemaFile(#[source] Box<Error>, std::path::PathBuf),
+
+    #[error("Cyclic references between schemas: {}", .0.join(" -> "))]
+    SchemaCycle(Vec<String>),
+
+    #[error("No `protocol` field in protocol")]
+    GetProtocolNameField,
+
+    #[error("`types` of protocol must be an array")]
+    GetProtocolTypes,
+
+    #[error("`messages` of protocol must be an object of messages")]
+    GetProtocolMessages,
+
+    #[error("Invalid `{1}` field of message {0:?}")]
+    GetMessageField(String, &'static str),
+
+    #[error("Error {error:?} of message {message:?} is not a record")]
+    GetMessageError { message: String, error: String },
+
+    #[error("One-way message {0:?} must have a null response and no errors")]
+    OneWayMessage(String),
+
+    #[error("Unknown message {0:?}")]
+    UnknownMessage(String),
+
+    #[error("Invalid Avro RPC handshake: {0}")]
+    Handshake(String),
+
+    #[error("Remote error: {0:?}")]
+    RemoteError(Value),
+
+    #[error("Remote system error: {0}")]
+    RemoteSystemError(String),
+
+    #[error("Failed to transmit an Avro RPC message")]
+    RpcIo(#[source] std::io::Error),
+
+    #[error("Invalid HTTP message: {0}")]
+    Http(String),
+
+    #[error("Invalid Avro IDL at line {line}, column {column}: {message}")]
+    ParseIdl {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+
+    #[error("Invalid schema at {location}: {source}")]
+    ParseSchemaAt {
+        location: SchemaLocation,
+        #[source]
+        source: Box<Error>,
+    },
+
     #[error("Failed to compress with flate")]
     DeflateCompress(#[source] std::io::Error),
 
@@ -348,6 +486,28 @@ pub enum Error {
     ConvertF64ToJson(f64),
 }
 
+impl Error {
+    /// Location within the JSON schema of an error raised when parsing it, if any.
+    ///
+    /// The line and column are only known when the schema is parsed from a string. Violations
+    /// of `Error::ValidateSchema` each have their own location.
+    pub fn schema_location(&self) -> Option<&SchemaLocation> {
+        match self {
+            Error::ParseSchemaAt { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+
+    /// The error raised when parsing a schema, without its location: the source of an
+    /// `Error::ParseSchemaAt`, or else the error itself.
+    pub fn without_location(&self) -> &Error {
+        match self {
+            Error::ParseSchemaAt { source, .. } => source,
+            error => error,
+        }
+    }
+}
+
 impl serde::ser::Error for Error {
     fn custom<T: fmt::Display>(msg: T) -> Self {
         Error::SerializeValue(msg.to_string())
@@ -0,0 +1,1163 @@
+//! Logic for parsing Avro IDL (`.avdl`) files into schemas
+use crate::{
+    protocol::inherit_namespace,
+    schema::{parse_defaults, validate, Name, Parser, ParsingMode, Schema},
+    AvroResult, Error,
+};
+use serde_json::{Map, Number, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// The schemas declared by an Avro IDL file, either within a `protocol` or with the schema syntax
+/// of Avro 1.11.
+///
+/// Records, errors, enums and fixed are declared as named types, and the schemas of the files
+/// imported with `import idl`, `import schema` and `import protocol` are added to them. The
+/// messages of a protocol are not part of its schemas.
+///
+/// ```
+/// use avro_rs::idl::Idl;
+///
+/// let idl = Idl::parse_str(r#"
+///     namespace org.example;
+///     schema Card;
+///
+///     enum Suit { SPADES, HEARTS, DIAMONDS, CLUBS }
+///
+///     record Card {
+///         Suit suit;
+///         int number = 1;
+///         string? nickname = null;
+///     }
+/// "#).unwrap();
+/// assert_eq!(idl.schemas.len(), 2);
+/// assert!(idl.main_schema.is_some());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Idl {
+    /// The namespace of the protocol, or declared with `namespace`.
+    pub namespace: Option<String>,
+    /// The name of the protocol, if the file declares one.
+    pub protocol: Option<String>,
+    /// The schema declared with `schema`, if any.
+    pub main_schema: Option<Schema>,
+    /// The named schemas imported and declared by the file, in order.
+    pub schemas: Vec<Schema>,
+}
+
+impl Idl {
+    /// Parse an Avro IDL document. Its imports are relative to the current directory.
+    pub fn parse_str(input: &str) -> AvroResult<Idl> {
+        Self::from_document(IdlDocument::parse(
+            input,
+            Path::new(""),
+            &mut HashSet::new(),
+        )?)
+    }
+
+    /// Parse an Avro IDL file. Its imports are relative to the directory of the file.
+    pub fn parse_file<P: AsRef<Path>>(path: P) -> AvroResult<Idl> {
+        Self::from_document(IdlDocument::read(path.as_ref(), &mut HashSet::new())?)
+    }
+
+    fn from_document(document: IdlDocument) -> AvroResult<Idl> {
+        let mut inputs = HashMap::with_capacity(document.types.len());
+        let mut order = Vec::with_capacity(document.types.len());
+        for value in document.types {
+            validate(&value)?;
+            let fullname = match value {
+                Value::Object(ref complex) => Name::parse(complex)?.fullname(None),
+                _ => return Err(Error::GetNameField),
+            };
+            if inputs.insert(fullname.clone(), value).is_some() {
+                return Err(Error::NameCollision(fullname));
+            }
+            order.push(fullname);
+        }
+
+        let mut parser = Parser::with_inputs(inputs, HashMap::new());
+        parser.parse_list()?;
+        let main_schema = match document.main_schema {
+            Some(value) => {
+                validate(&value)?;
+                let schema = parser.parse_in_namespace(&value, document.namespace.clone())?;
+                Some(parse_defaults(
+                    schema,
+                    ParsingMode::Strict,
+                    parser.record_paths(),
+                )?)
+            }
+            None => None,
+        };
+        let (mut parsed, record_paths) = parser.into_parsed();
+        let schemas = order
+            .iter()
+            .map(|fullname| {
+                let schema = parsed
+                    .remove(fullname)
+                    .expect("One of the declared schemas was unexpectedly not parsed");
+                parse_defaults(schema, ParsingMode::Strict, &record_paths)
+            })
+            .collect::<AvroResult<_>>()?;
+
+        Ok(Idl {
+            namespace: document.namespace,
+            protocol: document
+                .protocol
+                .and_then(|protocol| protocol["protocol"].as_str().map(ToString::to_string)),
+            main_schema,
+            schemas,
+        })
+    }
+}
+
+/// An Avro IDL file translated to JSON, along with the files it imports.
+pub(crate) struct IdlDocument {
+    pub(crate) namespace: Option<String>,
+    /// The JSON protocol declared by the file, with its `types` and `messages`.
+    pub(crate) protocol: Option<Map<String, Value>>,
+    /// The JSON named types imported and declared by the file, in order.
+    pub(crate) types: Vec<Value>,
+    /// The JSON schema declared with `schema`.
+    pub(crate) main_schema: Option<Value>,
+}
+
+impl IdlDocument {
+    /// Read and parse an Avro IDL file, unless it is one of the files already imported.
+    fn read(path: &Path, imported: &mut HashSet<PathBuf>) -> AvroResult<IdlDocument> {
+        let input = read_import(path, imported)?.unwrap_or_default();
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        Self::parse(&input, dir, imported)
+            .map_err(|e| Error::LoadSchemaFile(Box::new(e), path.to_path_buf()))
+    }
+
+    /// Parse an Avro IDL document whose imports are relative to `dir`.
+    pub(crate) fn parse(
+        input: &str,
+        dir: &Path,
+        imported: &mut HashSet<PathBuf>,
+    ) -> AvroResult<IdlDocument> {
+        let mut parser = IdlParser {
+            tokens: tokenize(input)?,
+            position: 0,
+            dir,
+            imported,
+            namespace: None,
+            types: Vec::new(),
+            messages: Map::new(),
+        };
+        parser.parse_document()
+    }
+}
+
+/// Read an imported file, or return `None` if it has already been imported.
+fn read_import(path: &Path, imported: &mut HashSet<PathBuf>) -> AvroResult<Option<String>> {
+    let canonical =
+        fs::canonicalize(path).map_err(|e| Error::ReadSchemaFile(e, path.to_path_buf()))?;
+    if !imported.insert(canonical) {
+        return Ok(None);
+    }
+    fs::read_to_string(path)
+        .map(Some)
+        .map_err(|e| Error::ReadSchemaFile(e, path.to_path_buf()))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Number(Number),
+    Symbol(char),
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(f, "'{}'", ident),
+            Token::Str(string) => write!(f, "{}", Value::String(string.clone())),
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Symbol(symbol) => write!(f, "'{}'", symbol),
+            Token::End => write!(f, "the end of the input"),
+        }
+    }
+}
+
+/// A token, with its position and the doc comment preceding it.
+struct Spanned {
+    token: Token,
+    line: usize,
+    column: usize,
+    doc: Option<String>,
+}
+
+struct Lexer<'i> {
+    input: &'i str,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'i> Lexer<'i> {
+    fn rest(&self) -> &'i str {
+        &self.input[self.offset..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    /// Consume the characters matching `f`, and return them.
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'i str {
+        let start = self.offset;
+        while matches!(self.peek(), Some(c) if f(c)) {
+            self.bump();
+        }
+        &self.input[start..self.offset]
+    }
+
+    /// Consume the input up to and including `end`, and return what precedes it.
+    fn take_until(&mut self, end: &str) -> Option<&'i str> {
+        let start = self.offset;
+        while !self.rest().starts_with(end) {
+            self.bump()?;
+        }
+        let taken = &self.input[start..self.offset];
+        for _ in end.chars() {
+            self.bump();
+        }
+        Some(taken)
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::ParseIdl {
+            message: message.into(),
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+fn tokenize(input: &str) -> AvroResult<Vec<Spanned>> {
+    let mut lexer = Lexer {
+        input,
+        offset: 0,
+        line: 1,
+        column: 1,
+    };
+    let mut tokens = Vec::new();
+    let mut doc = None;
+    loop {
+        lexer.take_while(char::is_whitespace);
+        let (line, column) = (lexer.line, lexer.column);
+        let rest = lexer.rest();
+        let token = if rest.starts_with("//") {
+            lexer.take_until("\n");
+            continue;
+        } else if rest.starts_with("/**") && !rest.starts_with("/**/") {
+            lexer.take_until("/**");
+            let comment = lexer
+                .take_until("*/")
+                .ok_or_else(|| lexer.error("Unterminated comment"))?;
+            doc = Some(parse_doc(comment));
+            continue;
+        } else if rest.starts_with("/*") {
+            lexer.take_until("/*");
+            lexer
+                .take_until("*/")
+                .ok_or_else(|| lexer.error("Unterminated comment"))?;
+            continue;
+        } else {
+            match lexer.peek() {
+                None => Token::End,
+                Some('"') => {
+                    let start = lexer.offset;
+                    lexer.bump();
+                    loop {
+                        match lexer.bump() {
+                            Some('"') => break,
+                            Some('\\') => {
+                                lexer.bump();
+                            }
+                            Some('\n') | None => return Err(lexer.error("Unterminated string")),
+                            Some(_) => {}
+                        }
+                    }
+                    let string = serde_json::from_str(&input[start..lexer.offset])
+                        .map_err(|e| lexer.error(format!("Invalid string: {}", e)))?;
+                    Token::Str(string)
+                }
+                Some('`') => {
+                    lexer.bump();
+                    let ident = lexer
+                        .take_until("`")
+                        .ok_or_else(|| lexer.error("Unterminated identifier"))?;
+                    Token::Ident(ident.to_string())
+                }
+                Some(c) if c.is_ascii_digit() || c == '-' => {
+                    let number = lexer.take_while(|c| {
+                        c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '+'
+                    });
+                    let number = serde_json::from_str(number).map_err(|_| Error::ParseIdl {
+                        message: format!("Invalid number {}", number),
+                        line,
+                        column,
+                    })?;
+                    Token::Number(number)
+                }
+                Some(c) if c.is_alphabetic() || c == '_' => {
+                    let ident = lexer
+                        .take_while(|c| c.is_alphanumeric() || c == '_' || c == '.' || c == '-');
+                    Token::Ident(ident.to_string())
+                }
+                Some(c) if "{}()[]<>;,=@:?".contains(c) => {
+                    lexer.bump();
+                    Token::Symbol(c)
+                }
+                Some(c) => return Err(lexer.error(format!("Unexpected character '{}'", c))),
+            }
+        };
+        let end = token == Token::End;
+        tokens.push(Spanned {
+            token,
+            line,
+            column,
+            doc: doc.take(),
+        });
+        if end {
+            return Ok(tokens);
+        }
+    }
+}
+
+/// Strip the leading `*` of the lines of a doc comment.
+fn parse_doc(comment: &str) -> String {
+    comment
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            line.strip_prefix('*').unwrap_or(line).trim()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Recursive descent parser of the tokens of an Avro IDL document, which translates its
+/// declarations to JSON.
+struct IdlParser<'a> {
+    tokens: Vec<Spanned>,
+    position: usize,
+    dir: &'a Path,
+    imported: &'a mut HashSet<PathBuf>,
+    namespace: Option<String>,
+    types: Vec<Value>,
+    messages: Map<String, Value>,
+}
+
+impl<'a> IdlParser<'a> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].token
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].token.clone();
+        if token != Token::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn doc(&self) -> Option<String> {
+        self.tokens[self.position].doc.clone()
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        let token = &self.tokens[self.position];
+        Error::ParseIdl {
+            message: message.into(),
+            line: token.line,
+            column: token.column,
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> Error {
+        self.error(format!("Expected {}, found {}", expected, self.peek()))
+    }
+
+    fn eat_symbol(&mut self, symbol: char) -> bool {
+        if *self.peek() == Token::Symbol(symbol) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: char) -> AvroResult<()> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", symbol)))
+        }
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Ident(ident) if ident == keyword)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek_keyword(keyword) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_ident(&mut self, expected: &str) -> AvroResult<String> {
+        match self.peek() {
+            Token::Ident(_) => match self.next() {
+                Token::Ident(ident) => Ok(ident),
+                _ => unreachable!(),
+            },
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+
+    fn expect_size(&mut self, expected: &str) -> AvroResult<u64> {
+        match self.peek() {
+            Token::Number(number) => match number.as_u64() {
+                Some(size) => {
+                    self.next();
+                    Ok(size)
+                }
+                None => Err(self.unexpected(expected)),
+            },
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+
+    fn annotation_string(&self, key: &str, value: Value) -> AvroResult<String> {
+        match value {
+            Value::String(string) => Ok(string),
+            _ => Err(self.error(format!("The @{} annotation must be a string", key))),
+        }
+    }
+
+    fn parse_document(&mut self) -> AvroResult<IdlDocument> {
+        let start = self.position;
+        let doc = self.doc();
+        let annotations = self.parse_annotations()?;
+        if self.eat_keyword("protocol") {
+            return self.parse_protocol(doc, annotations);
+        }
+        // Not a protocol, the annotations belong to the first declaration.
+        self.position = start;
+
+        if self.eat_keyword("namespace") {
+            self.namespace = Some(self.expect_ident("a namespace")?);
+            self.expect_symbol(';')?;
+        }
+        let main_schema = if self.eat_keyword("schema") {
+            let schema = self.parse_type()?;
+            self.expect_symbol(';')?;
+            Some(schema)
+        } else {
+            None
+        };
+        while *self.peek() != Token::End {
+            let doc = self.doc();
+            let annotations = self.parse_annotations()?;
+            if !self.parse_declaration(doc, annotations)? {
+                return Err(self.unexpected("an import or a declaration"));
+            }
+        }
+        Ok(IdlDocument {
+            namespace: self.namespace.take(),
+            protocol: None,
+            types: std::mem::take(&mut self.types),
+            main_schema,
+        })
+    }
+
+    fn parse_protocol(
+        &mut self,
+        doc: Option<String>,
+        annotations: Vec<(String, Value)>,
+    ) -> AvroResult<IdlDocument> {
+        let name = self.expect_ident("the name of the protocol")?;
+        let mut protocol = Map::new();
+        protocol.insert("protocol".to_string(), Value::String(name));
+        for (key, value) in annotations {
+            if key == "namespace" {
+                self.namespace = Some(self.annotation_string(&key, value)?);
+            } else {
+                protocol.insert(key, value);
+            }
+        }
+        if let Some(ref namespace) = self.namespace {
+            protocol.insert("namespace".to_string(), Value::String(namespace.clone()));
+        }
+        if let Some(doc) = doc {
+            protocol.insert("doc".to_string(), Value::String(doc));
+        }
+
+        self.expect_symbol('{')?;
+        while !self.eat_symbol('}') {
+            let doc = self.doc();
+            let annotations = self.parse_annotations()?;
+            if !self.parse_declaration(doc.clone(), annotations.clone())? {
+                self.parse_message(doc, annotations)?;
+            }
+        }
+        if *self.peek() != Token::End {
+            return Err(self.unexpected("the end of the input"));
+        }
+
+        let types = std::mem::take(&mut self.types);
+        protocol.insert("types".to_string(), Value::Array(types.clone()));
+        protocol.insert(
+            "messages".to_string(),
+            Value::Object(std::mem::take(&mut self.messages)),
+        );
+        Ok(IdlDocument {
+            namespace: self.namespace.take(),
+            protocol: Some(protocol),
+            types,
+            main_schema: None,
+        })
+    }
+
+    fn parse_annotations(&mut self) -> AvroResult<Vec<(String, Value)>> {
+        let mut annotations = Vec::new();
+        while self.eat_symbol('@') {
+            let name = self.expect_ident("the name of an annotation")?;
+            self.expect_symbol('(')?;
+            let value = self.parse_json()?;
+            self.expect_symbol(')')?;
+            annotations.push((name, value));
+        }
+        Ok(annotations)
+    }
+
+    fn parse_json(&mut self) -> AvroResult<Value> {
+        match self.peek().clone() {
+            Token::Str(string) => {
+                self.next();
+                Ok(Value::String(string))
+            }
+            Token::Number(number) => {
+                self.next();
+                Ok(Value::Number(number))
+            }
+            Token::Ident(ref ident) if ident == "null" => {
+                self.next();
+                Ok(Value::Null)
+            }
+            Token::Ident(ref ident) if ident == "true" || ident == "false" => {
+                self.next();
+                Ok(Value::Bool(ident == "true"))
+            }
+            Token::Symbol('[') => {
+                self.next();
+                let mut items = Vec::new();
+                while !self.eat_symbol(']') {
+                    if !items.is_empty() {
+                        self.expect_symbol(',')?;
+                    }
+                    items.push(self.parse_json()?);
+                }
+                Ok(Value::Array(items))
+            }
+            Token::Symbol('{') => {
+                self.next();
+                let mut object = Map::new();
+                while !self.eat_symbol('}') {
+                    if !object.is_empty() {
+                        self.expect_symbol(',')?;
+                    }
+                    let key = match self.peek() {
+                        Token::Str(key) => key.clone(),
+                        _ => return Err(self.unexpected("a string")),
+                    };
+                    self.next();
+                    self.expect_symbol(':')?;
+                    object.insert(key, self.parse_json()?);
+                }
+                Ok(Value::Object(object))
+            }
+            _ => Err(self.unexpected("a JSON value")),
+        }
+    }
+
+    /// Parse an import or the declaration of a named type, if the next token starts one.
+    fn parse_declaration(
+        &mut self,
+        doc: Option<String>,
+        annotations: Vec<(String, Value)>,
+    ) -> AvroResult<bool> {
+        let kind = match self.peek() {
+            Token::Ident(ident) => ident.clone(),
+            _ => return Ok(false),
+        };
+        let declared = match kind.as_str() {
+            "import" => {
+                self.next();
+                self.parse_import()?;
+                return Ok(true);
+            }
+            "record" | "error" => {
+                self.next();
+                let mut record = self.parse_named(&kind, doc, annotations)?;
+                self.expect_symbol('{')?;
+                let mut fields = Vec::new();
+                while !self.eat_symbol('}') {
+                    fields.extend(self.parse_fields()?);
+                }
+                record.insert("fields".to_string(), Value::Array(fields));
+                record
+            }
+            "enum" => {
+                self.next();
+                let mut enumeration = self.parse_named(&kind, doc, annotations)?;
+                self.expect_symbol('{')?;
+                let mut symbols = Vec::new();
+                while !self.eat_symbol('}') {
+                    if !symbols.is_empty() {
+                        self.expect_symbol(',')?;
+                    }
+                    symbols.push(Value::String(self.expect_ident("a symbol")?));
+                }
+                enumeration.insert("symbols".to_string(), Value::Array(symbols));
+                if self.eat_symbol('=') {
+                    let default = self.expect_ident("the default symbol")?;
+                    enumeration.insert("default".to_string(), Value::String(default));
+                    self.expect_symbol(';')?;
+                } else {
+                    self.eat_symbol(';');
+                }
+                enumeration
+            }
+            "fixed" => {
+                self.next();
+                let mut fixed = self.parse_named(&kind, doc, annotations)?;
+                self.expect_symbol('(')?;
+                let size = self.expect_size("the size of the fixed")?;
+                self.expect_symbol(')')?;
+                self.expect_symbol(';')?;
+                fixed.insert("size".to_string(), size.into());
+                fixed
+            }
+            _ => return Ok(false),
+        };
+        self.types.push(Value::Object(declared));
+        Ok(true)
+    }
+
+    /// Parse the name of a named type, and build its JSON schema from its annotations.
+    fn parse_named(
+        &mut self,
+        kind: &str,
+        doc: Option<String>,
+        annotations: Vec<(String, Value)>,
+    ) -> AvroResult<Map<String, Value>> {
+        let name = self.expect_ident(&format!("the name of the {}", kind))?;
+        let mut named = Map::new();
+        named.insert("type".to_string(), Value::String(kind.to_string()));
+        named.insert("name".to_string(), Value::String(name));
+        let mut namespace = self.namespace.clone();
+        for (key, value) in annotations {
+            if key == "namespace" {
+                namespace = Some(self.annotation_string(&key, value)?);
+            } else {
+                named.insert(key, value);
+            }
+        }
+        if let Some(namespace) = namespace {
+            named.insert("namespace".to_string(), Value::String(namespace));
+        }
+        if let Some(doc) = doc {
+            named.insert("doc".to_string(), Value::String(doc));
+        }
+        Ok(named)
+    }
+
+    fn parse_import(&mut self) -> AvroResult<()> {
+        let kind = match self.peek() {
+            Token::Ident(kind) if kind == "idl" || kind == "protocol" || kind == "schema" => {
+                kind.clone()
+            }
+            _ => return Err(self.unexpected("'idl', 'protocol' or 'schema'")),
+        };
+        self.next();
+        let path = match self.peek() {
+            Token::Str(path) => self.dir.join(path),
+            _ => return Err(self.unexpected("the path of the imported file")),
+        };
+        self.next();
+        self.expect_symbol(';')?;
+
+        match kind.as_str() {
+            "idl" => {
+                let document = IdlDocument::read(&path, self.imported)?;
+                self.types.extend(document.types);
+                if let Some(Value::Object(messages)) = document
+                    .protocol
+                    .and_then(|mut protocol| protocol.remove("messages"))
+                {
+                    self.messages.extend(messages);
+                }
+            }
+            _ => {
+                let input = match read_import(&path, self.imported)? {
+                    Some(input) => input,
+                    None => return Ok(()),
+                };
+                let value: Value = serde_json::from_str(&input).map_err(|e| {
+                    Error::LoadSchemaFile(Box::new(Error::ParseSchemaJson(e)), path.clone())
+                })?;
+                if kind == "schema" {
+                    match value {
+                        Value::Array(types) => self.types.extend(types),
+                        value => self.types.push(value),
+                    }
+                } else {
+                    self.import_protocol(value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the types and messages of a JSON protocol, its types inheriting its namespace.
+    fn import_protocol(&mut self, mut protocol: Value) {
+        let namespace = protocol
+            .get("namespace")
+            .and_then(Value::as_str)
+            .map(ToString::to_string);
+        if let Some(Value::Array(types)) = protocol.get_mut("types").map(Value::take) {
+            for mut schema in types {
+                if let Some(ref namespace) = namespace {
+                    inherit_namespace(&mut schema, namespace);
+                }
+                self.types.push(schema);
+            }
+        }
+        if let Some(Value::Object(messages)) = protocol.get_mut("messages").map(Value::take) {
+            self.messages.extend(messages);
+        }
+    }
+
+    /// Parse the declaration of one or several fields of the same type.
+    fn parse_fields(&mut self) -> AvroResult<Vec<Value>> {
+        let doc = self.doc();
+        let (field_annotations, type_annotations): (Vec<_>, Vec<_>) = self
+            .parse_annotations()?
+            .into_iter()
+            .partition(|(key, _)| key == "order" || key == "aliases");
+        let schema = self.parse_type_with(type_annotations)?;
+        let optional = self.position > 0
+            && self.tokens[self.position - 1].token == Token::Symbol('?')
+            && schema.is_array();
+
+        let mut fields = Vec::new();
+        loop {
+            let mut field = self.parse_variable(&schema, doc.clone(), &field_annotations)?;
+            // The default value of a union is of its first branch.
+            if optional && !matches!(field.get("default"), None | Some(Value::Null)) {
+                if let Some(Value::Array(branches)) = field.get_mut("type") {
+                    branches.reverse();
+                }
+            }
+            fields.push(Value::Object(field));
+            if !self.eat_symbol(',') {
+                break;
+            }
+        }
+        self.expect_symbol(';')?;
+        Ok(fields)
+    }
+
+    /// Parse the name, annotations and default of a field or parameter.
+    fn parse_variable(
+        &mut self,
+        schema: &Value,
+        doc: Option<String>,
+        annotations: &[(String, Value)],
+    ) -> AvroResult<Map<String, Value>> {
+        let doc = self.doc().or(doc);
+        let annotations = [annotations, &self.parse_annotations()?].concat();
+        let name = self.expect_ident("the name of a field")?;
+        let mut field = Map::new();
+        field.insert("name".to_string(), Value::String(name));
+        field.insert("type".to_string(), schema.clone());
+        if let Some(doc) = doc {
+            field.insert("doc".to_string(), Value::String(doc));
+        }
+        for (key, value) in annotations {
+            field.insert(key, value);
+        }
+        if self.eat_symbol('=') {
+            field.insert("default".to_string(), self.parse_json()?);
+        }
+        Ok(field)
+    }
+
+    fn parse_message(
+        &mut self,
+        doc: Option<String>,
+        annotations: Vec<(String, Value)>,
+    ) -> AvroResult<()> {
+        let response = self.parse_type()?;
+        let name = self.expect_ident("the name of a message")?;
+        let mut message = Map::new();
+        if let Some(doc) = doc {
+            message.insert("doc".to_string(), Value::String(doc));
+        }
+        for (key, value) in annotations {
+            message.insert(key, value);
+        }
+
+        self.expect_symbol('(')?;
+        let mut request = Vec::new();
+        while !self.eat_symbol(')') {
+            if !request.is_empty() {
+                self.expect_symbol(',')?;
+            }
+            let doc = self.doc();
+            let schema = self.parse_type()?;
+            request.push(Value::Object(self.parse_variable(&schema, doc, &[])?));
+        }
+        message.insert("request".to_string(), Value::Array(request));
+        message.insert("response".to_string(), response);
+
+        if self.eat_keyword("oneway") {
+            message.insert("one-way".to_string(), Value::Bool(true));
+        } else if self.eat_keyword("throws") {
+            let mut errors = vec![Value::String(self.expect_ident("an error")?)];
+            while self.eat_symbol(',') {
+                errors.push(Value::String(self.expect_ident("an error")?));
+            }
+            message.insert("errors".to_string(), Value::Array(errors));
+        }
+        self.expect_symbol(';')?;
+        self.messages.insert(name, Value::Object(message));
+        Ok(())
+    }
+
+    fn parse_type(&mut self) -> AvroResult<Value> {
+        let annotations = self.parse_annotations()?;
+        self.parse_type_with(annotations)
+    }
+
+    /// Parse a type, to which the given annotations apply, and which can be made optional with
+    /// a trailing `?`.
+    fn parse_type_with(&mut self, annotations: Vec<(String, Value)>) -> AvroResult<Value> {
+        let name = self.expect_ident("a type")?;
+        let schema = match name.as_str() {
+            "boolean" | "int" | "long" | "float" | "double" | "bytes" | "string" | "null" => {
+                Value::String(name)
+            }
+            "void" => Value::String("null".to_string()),
+            "date" => logical("int", "date"),
+            "time_ms" => logical("int", "time-millis"),
+            "timestamp_ms" => logical("long", "timestamp-millis"),
+            "local_timestamp_ms" => logical("long", "local-timestamp-millis"),
+            "uuid" => logical("string", "uuid"),
+            "decimal" => {
+                self.expect_symbol('(')?;
+                let precision = self.expect_size("the precision of the decimal")?;
+                self.expect_symbol(',')?;
+                let scale = self.expect_size("the scale of the decimal")?;
+                self.expect_symbol(')')?;
+                let mut decimal = logical("bytes", "decimal");
+                decimal["precision"] = precision.into();
+                decimal["scale"] = scale.into();
+                decimal
+            }
+            "array" | "map" => {
+                self.expect_symbol('<')?;
+                let inner = self.parse_type()?;
+                self.expect_symbol('>')?;
+                let key = if name == "array" { "items" } else { "values" };
+                let mut collection = Map::new();
+                collection.insert("type".to_string(), Value::String(name));
+                collection.insert(key.to_string(), inner);
+                Value::Object(collection)
+            }
+            "union" => {
+                self.expect_symbol('{')?;
+                let mut branches = vec![self.parse_type()?];
+                while self.eat_symbol(',') {
+                    branches.push(self.parse_type()?);
+                }
+                self.expect_symbol('}')?;
+                Value::Array(branches)
+            }
+            _ => Value::String(name),
+        };
+
+        let schema = match schema {
+            _ if annotations.is_empty() => schema,
+            Value::String(name) => {
+                let mut complex = Map::new();
+                complex.insert("type".to_string(), Value::String(name));
+                complex.extend(annotations);
+                Value::Object(complex)
+            }
+            Value::Object(mut complex) => {
+                complex.extend(annotations);
+                Value::Object(complex)
+            }
+            _ => return Err(self.error("Annotations cannot apply to a union")),
+        };
+        if self.eat_symbol('?') {
+            Ok(Value::Array(vec![
+                Value::String("null".to_string()),
+                schema,
+            ]))
+        } else {
+            Ok(schema)
+        }
+    }
+}
+
+fn logical(schema: &str, logical_type: &str) -> Value {
+    let mut complex = Map::new();
+    complex.insert("type".to_string(), Value::String(schema.to_string()));
+    complex.insert(
+        "logicalType".to_string(),
+        Value::String(logical_type.to_string()),
+    );
+    Value::Object(complex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_parse_protocol() {
+        let idl = Idl::parse_str(
+            r#"
+            /** Cards and their suits. */
+            @namespace("org.example")
+            protocol Cards {
+                /** The suit of a card. */
+                @aliases(["org.example.Colour"])
+                enum Suit { SPADES, HEARTS, DIAMONDS, CLUBS } = SPADES;
+
+                @namespace("org.example.hash") fixed MD5(16);
+
+                record Card {
+                    Suit suit;
+                    int number = 1;
+                    /** Its nickname. */
+                    string? nickname = null;
+                    string @order("ignore") @aliases(["label"]) name = "card", title;
+                    union { null, org.example.hash.MD5 } hash = null;
+                    array<long> history = [];
+                    map<decimal(9, 2)> prices = {};
+                    @logicalType("timestamp-micros") long updated;
+                    date? issued;
+                    @java-class("java.util.UUID") uuid `id`;
+                }
+
+                error Invalid {
+                    string message;
+                }
+
+                Card draw(Suit suit = "HEARTS") throws Invalid;
+                void shuffle() oneway;
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(idl.namespace.as_deref(), Some("org.example"));
+        assert_eq!(idl.protocol.as_deref(), Some("Cards"));
+        assert_eq!(idl.main_schema, None);
+        let expected = Schema::parse_list(&[
+            r#"{"type": "enum", "name": "Suit", "namespace": "org.example", "doc": "The suit of a card.",
+                "aliases": ["org.example.Colour"], "symbols": ["SPADES", "HEARTS", "DIAMONDS", "CLUBS"],
+                "default": "SPADES"}"#,
+            r#"{"type": "fixed", "name": "MD5", "namespace": "org.example.hash", "size": 16}"#,
+            r#"{"type": "record", "name": "Card", "namespace": "org.example", "fields": [
+                {"name": "suit", "type": "Suit"},
+                {"name": "number", "type": "int", "default": 1},
+                {"name": "nickname", "type": ["null", "string"], "doc": "Its nickname.", "default": null},
+                {"name": "name", "type": "string", "order": "ignore", "aliases": ["label"], "default": "card"},
+                {"name": "title", "type": "string"},
+                {"name": "hash", "type": ["null", "org.example.hash.MD5"], "default": null},
+                {"name": "history", "type": {"type": "array", "items": "long"}, "default": []},
+                {"name": "prices", "type": {"type": "map", "values": {"type": "bytes",
+                    "logicalType": "decimal", "precision": 9, "scale": 2}}, "default": {}},
+                {"name": "updated", "type": {"type": "long", "logicalType": "timestamp-micros"}},
+                {"name": "issued", "type": ["null", {"type": "int", "logicalType": "date"}]},
+                {"name": "id", "type": {"type": "string", "logicalType": "uuid",
+                    "java-class": "java.util.UUID"}}
+            ]}"#,
+            r#"{"type": "record", "name": "Invalid", "namespace": "org.example", "fields": [
+                {"name": "message", "type": "string"}
+            ]}"#,
+        ])
+        .unwrap();
+        assert_eq!(idl.schemas, expected);
+    }
+
+    #[test]
+    fn test_parse_schema_syntax() {
+        let idl = Idl::parse_str(
+            r#"
+            namespace org.example;
+            schema array<Card>;
+
+            // A playing card.
+            record Card {
+                string? nickname = "joker";
+                int number;
+            }
+            "#,
+        )
+        .unwrap();
+
+        let card = Schema::parse_str(
+            r#"{"type": "record", "name": "Card", "namespace": "org.example", "fields": [
+                {"name": "nickname", "type": ["string", "null"], "default": "joker"},
+                {"name": "number", "type": "int"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(idl.protocol, None);
+        assert_eq!(idl.schemas, vec![card.clone()]);
+        assert_eq!(
+            idl.main_schema,
+            Some(Schema::Array(Box::new(card))),
+            "The main schema refers to the declared types in the namespace of the file"
+        );
+    }
+
+    #[test]
+    fn test_parse_imports() {
+        let dir = env::temp_dir().join(format!("avro-rs-idl-{}", std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(
+            dir.join("nested/suit.avsc"),
+            r#"{"type": "enum", "name": "org.example.Suit", "symbols": ["SPADES"]}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("nested/ids.avpr"),
+            r#"{"protocol": "Ids", "namespace": "org.example", "types": [
+                {"type": "fixed", "name": "Id", "size": 4}
+            ]}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("nested/common.avdl"),
+            r#"
+            @namespace("org.example")
+            protocol Common {
+                import schema "suit.avsc";
+                import protocol "ids.avpr";
+                record Owner { Id id; }
+            }
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("main.avdl"),
+            r#"
+            namespace org.example;
+            import idl "nested/common.avdl";
+            import schema "nested/suit.avsc";
+            record Card { Suit suit; Owner owner; }
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("invalid.avdl"),
+            "import idl \"nested/missing.avdl\";",
+        )
+        .unwrap();
+
+        let idl = Idl::parse_file(dir.join("main.avdl"));
+        let invalid = Idl::parse_file(dir.join("invalid.avdl"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names = idl
+            .unwrap()
+            .schemas
+            .iter()
+            .map(|schema| match schema {
+                Schema::Record { name, .. }
+                | Schema::Enum { name, .. }
+                | Schema::Fixed { name, .. } => name.fullname(None),
+                _ => panic!("Expected a named schema, got {:?}", schema),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "org.example.Suit",
+                "org.example.Id",
+                "org.example.Owner",
+                "org.example.Card"
+            ]
+        );
+        match invalid {
+            Err(Error::LoadSchemaFile(e, path)) => {
+                assert_eq!(path, dir.join("invalid.avdl"));
+                assert!(matches!(*e, Error::ReadSchemaFile(_, _)));
+            }
+            other => panic!("Expected a missing import, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input: &str| match Idl::parse_str(input) {
+            Err(Error::ParseIdl {
+                message,
+                line,
+                column,
+            }) => (message, line, column),
+            other => panic!("Expected a syntax error, got {:?}", other),
+        };
+
+        assert_eq!(
+            error("protocol P {\n  record R {\n    int a\n  }\n}"),
+            ("Expected ';', found '}'".to_string(), 4, 3)
+        );
+        assert_eq!(
+            error("record R { decimal(9) d; }"),
+            ("Expected ',', found ')'".to_string(), 1, 21)
+        );
+        assert_eq!(
+            error("enum E { A } # nope"),
+            ("Unexpected character '#'".to_string(), 1, 14)
+        );
+        assert!(matches!(
+            Idl::parse_str("record R { Unknown u; }"),
+            Err(Error::ParseSchemaAt { .. })
+        ));
+    }
+}
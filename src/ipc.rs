@@ -0,0 +1,1147 @@
+//! Logic for Avro RPC: the calls of the messages of a protocol, preceded by a handshake, over
+//! socket and HTTP transports
+use crate::{
+    decode::decode,
+    protocol::{Message, Protocol},
+    schema::{Schema, UnionSchema},
+    types::Value,
+    util::safe_len,
+    writer::to_avro_datum,
+    AvroResult, Error,
+};
+use lazy_static::lazy_static;
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::Arc,
+};
+
+/// The size of the buffers which messages are split in.
+const BUFFER_SIZE: usize = 8192;
+
+/// The number of client protocols a `Responder` keeps at most.
+const MAX_CLIENT_PROTOCOLS: usize = 128;
+
+const HANDSHAKE_REQUEST_SCHEMA: &str = r#"{
+    "type": "record",
+    "name": "HandshakeRequest",
+    "namespace": "org.apache.avro.ipc",
+    "fields": [
+        {"name": "clientHash", "type": {"type": "fixed", "name": "MD5", "size": 16}},
+        {"name": "clientProtocol", "type": ["null", "string"]},
+        {"name": "serverHash", "type": "MD5"},
+        {"name": "meta", "type": ["null", {"type": "map", "values": "bytes"}]}
+    ]
+}"#;
+
+const HANDSHAKE_RESPONSE_SCHEMA: &str = r#"{
+    "type": "record",
+    "name": "HandshakeResponse",
+    "namespace": "org.apache.avro.ipc",
+    "fields": [
+        {"name": "match", "type": {
+            "type": "enum",
+            "name": "HandshakeMatch",
+            "symbols": ["BOTH", "CLIENT", "NONE"]
+        }},
+        {"name": "serverProtocol", "type": ["null", "string"]},
+        {"name": "serverHash", "type": ["null", {"type": "fixed", "name": "MD5", "size": 16}]},
+        {"name": "meta", "type": ["null", {"type": "map", "values": "bytes"}]}
+    ]
+}"#;
+
+lazy_static! {
+    static ref HANDSHAKE_REQUEST: Schema = Schema::parse_str(HANDSHAKE_REQUEST_SCHEMA).unwrap();
+    static ref HANDSHAKE_RESPONSE: Schema = Schema::parse_str(HANDSHAKE_RESPONSE_SCHEMA).unwrap();
+    static ref META: Schema = Schema::Map(Box::new(Schema::Bytes));
+    static ref SYSTEM_ERROR: Schema =
+        Schema::Union(UnionSchema::new(vec![Schema::String]).unwrap());
+}
+
+/// The metadata of a call, or of its response.
+pub type Meta = HashMap<String, Vec<u8>>;
+
+/// How well the client and server know each other's protocol, as told by the handshake.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum HandshakeMatch {
+    /// The server knows the protocol of the client, and the client the protocol of the server.
+    Both,
+    /// The server knows the protocol of the client, but the client not the one of the server.
+    Client,
+    /// The server does not know the protocol of the client.
+    None,
+}
+
+impl HandshakeMatch {
+    fn symbol(self) -> &'static str {
+        match self {
+            HandshakeMatch::Both => "BOTH",
+            HandshakeMatch::Client => "CLIENT",
+            HandshakeMatch::None => "NONE",
+        }
+    }
+}
+
+/// A transport of the requests of a `Requestor` to a server.
+pub trait Transceiver {
+    /// Send a request, and return the response of the server.
+    fn transceive(&mut self, request: &[u8]) -> AvroResult<Vec<u8>>;
+
+    /// Send a request of a one-way message, whose response is not waited for.
+    fn send(&mut self, request: &[u8]) -> AvroResult<()> {
+        self.transceive(request).map(|_| ())
+    }
+
+    /// Whether the transport keeps a connection to the server, which only needs a handshake with
+    /// its first request. Stateless transports send a handshake with every request.
+    fn is_stateful(&self) -> bool {
+        false
+    }
+}
+
+/// A `Transceiver` over a TCP connection, over which the messages are framed in length-prefixed
+/// buffers.
+pub struct SocketTransceiver {
+    stream: TcpStream,
+}
+
+impl SocketTransceiver {
+    /// Connect to a `SocketServer`.
+    pub fn connect<A: ToSocketAddrs>(address: A) -> AvroResult<Self> {
+        let stream = TcpStream::connect(address).map_err(Error::RpcIo)?;
+        Ok(SocketTransceiver { stream })
+    }
+}
+
+impl Transceiver for SocketTransceiver {
+    fn transceive(&mut self, request: &[u8]) -> AvroResult<Vec<u8>> {
+        self.send(request)?;
+        read_framed(&mut self.stream)?.ok_or_else(|| {
+            Error::RpcIo(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "The server closed the connection",
+            ))
+        })
+    }
+
+    fn send(&mut self, request: &[u8]) -> AvroResult<()> {
+        write_framed(&mut self.stream, request)
+    }
+
+    fn is_stateful(&self) -> bool {
+        true
+    }
+}
+
+/// A `Transceiver` which posts every request to an HTTP server, with the content type
+/// `avro/binary`.
+pub struct HttpTransceiver {
+    address: String,
+    path: String,
+}
+
+impl HttpTransceiver {
+    /// Create a transceiver posting the requests to `path` on the server at `address`, e.g.
+    /// `"localhost:8080"`.
+    pub fn new(address: &str, path: &str) -> Self {
+        HttpTransceiver {
+            address: address.to_string(),
+            path: path.to_string(),
+        }
+    }
+}
+
+impl Transceiver for HttpTransceiver {
+    fn transceive(&mut self, request: &[u8]) -> AvroResult<Vec<u8>> {
+        let mut stream = TcpStream::connect(&self.address).map_err(Error::RpcIo)?;
+        let head = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: avro/binary\r\n",
+            self.path, self.address
+        );
+        write_http(&mut stream, &head, request)?;
+
+        let (status, body) = read_http(&mut BufReader::new(stream))?;
+        match status.split_whitespace().nth(1) {
+            Some("200") => read_framed(&mut &body[..])?
+                .ok_or_else(|| Error::Http("The response is empty".to_string())),
+            _ => Err(Error::Http(format!("Unexpected status {:?}", status))),
+        }
+    }
+}
+
+/// The client of a protocol, which calls its messages through a `Transceiver`.
+///
+/// The protocol of the server is learnt from the handshake, and cached along with its hash for
+/// the next calls. The requests are written with the protocol of the client, and the responses
+/// read with the protocol of the server and resolved to the one of the client.
+pub struct Requestor<T> {
+    local: Protocol,
+    local_hash: [u8; 16],
+    transceiver: T,
+    remote: Option<([u8; 16], Protocol)>,
+    send_protocol: bool,
+    connected: bool,
+}
+
+impl<T: Transceiver> Requestor<T> {
+    /// Create a client of `protocol`, sending its requests through `transceiver`.
+    pub fn new(protocol: Protocol, transceiver: T) -> Self {
+        Requestor {
+            local_hash: protocol.md5(),
+            local: protocol,
+            transceiver,
+            remote: None,
+            send_protocol: false,
+            connected: false,
+        }
+    }
+
+    /// Returns the protocol of the server, once known from a handshake.
+    pub fn remote_protocol(&self) -> Option<&Protocol> {
+        self.remote.as_ref().map(|(_, protocol)| protocol)
+    }
+
+    /// Call a message with a request, a `Value::Record` of its parameters, and return its
+    /// response. The errors declared by the message are returned as `Error::RemoteError`, and the
+    /// other errors of the server as `Error::RemoteSystemError`.
+    pub fn request(&mut self, message: &str, request: Value) -> AvroResult<Value> {
+        self.request_with_meta(message, request, Meta::new())
+            .map(|(response, _)| response)
+    }
+
+    /// Same as `request`, sending the metadata of the call and returning the one of its response.
+    pub fn request_with_meta(
+        &mut self,
+        message: &str,
+        request: Value,
+        meta: Meta,
+    ) -> AvroResult<(Value, Meta)> {
+        let local = self
+            .local
+            .message(message)
+            .ok_or_else(|| Error::UnknownMessage(message.to_string()))?
+            .clone();
+        let request = request.resolve(&local.request_schema())?;
+        let mut call = to_avro_datum(&META, meta_value(meta))?;
+        call.extend(to_avro_datum(&Schema::String, message)?);
+        call.extend(to_avro_datum(&local.request_schema(), request)?);
+
+        loop {
+            let handshake = !(self.connected && self.transceiver.is_stateful());
+            let mut buffer = Vec::new();
+            if handshake {
+                buffer.extend(self.handshake_request()?);
+            }
+            buffer.extend(&call);
+            if !handshake && local.one_way {
+                self.transceiver.send(&buffer)?;
+                return Ok((Value::Null, Meta::new()));
+            }
+
+            let response = self.transceiver.transceive(&buffer)?;
+            let mut reader = &response[..];
+            if handshake && !self.read_handshake_response(&mut reader)? {
+                continue;
+            }
+            if local.one_way {
+                return Ok((Value::Null, Meta::new()));
+            }
+            return self.read_response(&local, &mut reader);
+        }
+    }
+
+    fn handshake_request(&self) -> AvroResult<Vec<u8>> {
+        let client_protocol = if self.send_protocol {
+            Some(serde_json::to_string(&self.local).map_err(Error::ConvertJsonToString)?)
+        } else {
+            None
+        };
+        let server_hash = match self.remote {
+            Some((hash, _)) => hash,
+            None => self.local_hash,
+        };
+        let handshake = Value::Record(vec![
+            (
+                "clientHash".to_string(),
+                Value::Fixed(16, self.local_hash.to_vec()),
+            ),
+            ("clientProtocol".to_string(), client_protocol.into()),
+            (
+                "serverHash".to_string(),
+                Value::Fixed(16, server_hash.to_vec()),
+            ),
+            (
+                "meta".to_string(),
+                Value::Union(None, Box::new(Value::Null)),
+            ),
+        ]);
+        to_avro_datum(&HANDSHAKE_REQUEST, handshake)
+    }
+
+    /// Read the handshake response of the server, and return whether it accepted the request.
+    fn read_handshake_response(&mut self, reader: &mut &[u8]) -> AvroResult<bool> {
+        let handshake = decode(&HANDSHAKE_RESPONSE, reader)?;
+        let handshake_match = match field(&handshake, "match") {
+            Some(Value::Enum(_, symbol)) if symbol == "BOTH" => HandshakeMatch::Both,
+            Some(Value::Enum(_, symbol)) if symbol == "CLIENT" => HandshakeMatch::Client,
+            Some(Value::Enum(_, symbol)) if symbol == "NONE" => HandshakeMatch::None,
+            _ => return Err(Error::Handshake("invalid match".to_string())),
+        };
+
+        match handshake_match {
+            HandshakeMatch::Both => {
+                if self.remote.is_none() {
+                    // The server hash sent was the local one.
+                    self.remote = Some((self.local_hash, self.local.clone()));
+                }
+            }
+            HandshakeMatch::Client | HandshakeMatch::None => {
+                let protocol = match field(&handshake, "serverProtocol") {
+                    Some(Value::String(protocol)) => Protocol::parse_str(protocol)?,
+                    _ => return Err(Error::Handshake("missing server protocol".to_string())),
+                };
+                let hash = field(&handshake, "serverHash")
+                    .and_then(md5)
+                    .ok_or_else(|| Error::Handshake("missing server hash".to_string()))?;
+                self.remote = Some((hash, protocol));
+            }
+        }
+        if handshake_match == HandshakeMatch::None {
+            if self.send_protocol {
+                // The server tells why it does not accept the protocol, if it can.
+                return Err(match read_system_error(reader) {
+                    Some(error) => Error::RemoteSystemError(error),
+                    None => Error::Handshake(
+                        "the server does not accept the protocol of the client".to_string(),
+                    ),
+                });
+            }
+            self.send_protocol = true;
+            return Ok(false);
+        }
+        self.send_protocol = false;
+        self.connected = true;
+        Ok(true)
+    }
+
+    fn read_response(&self, local: &Message, reader: &mut &[u8]) -> AvroResult<(Value, Meta)> {
+        let remote = self
+            .remote_protocol()
+            .and_then(|protocol| protocol.message(&local.name))
+            .unwrap_or(local);
+        let meta = meta_from_value(decode(&META, reader)?);
+        match decode(&Schema::Boolean, reader)? {
+            Value::Boolean(false) => {
+                let response = decode(&remote.response, reader)?.resolve(&local.response)?;
+                Ok((response, meta))
+            }
+            _ => match decode(&Schema::Union(error_union(remote)?), reader)? {
+                Value::Union(_, error) => match *error {
+                    Value::String(error) => Err(Error::RemoteSystemError(error)),
+                    // Read as the matching error of the client, if it declares one.
+                    error => match error.clone().resolve(&Schema::Union(error_union(local)?)) {
+                        Ok(Value::Union(_, error)) => Err(Error::RemoteError(*error)),
+                        _ => Err(Error::RemoteError(error)),
+                    },
+                },
+                error => Err(Error::RemoteError(error)),
+            },
+        }
+    }
+}
+
+/// A call of a message received by a server.
+pub struct Call<'a> {
+    /// The message called, of the protocol of the server.
+    pub message: &'a Message,
+    /// The request, a `Value::Record` of the parameters of the message.
+    pub request: Value,
+    pub meta: Meta,
+}
+
+/// Handles the calls received by a server.
+///
+/// It returns either the response to the call, or its error: a value of one of the errors
+/// declared by the message, or a `Value::String` for any other error. The closures taking a
+/// `Call` are handlers.
+pub trait Handler {
+    fn handle(&mut self, call: Call<'_>) -> Result<Value, Value>;
+}
+
+impl<F> Handler for F
+where
+    F: FnMut(Call<'_>) -> Result<Value, Value>,
+{
+    fn handle(&mut self, call: Call<'_>) -> Result<Value, Value> {
+        self(call)
+    }
+}
+
+/// The server side of a protocol, which answers to the handshakes of the clients and hands their
+/// calls to a `Handler`.
+///
+/// The protocols of the clients are cached by hash, so that a client only sends its protocol
+/// the first time it calls the server, unless it was evicted by too many others. The requests are
+/// read with the protocol of the client, and resolved to the one of the server.
+pub struct Responder<H> {
+    local: Protocol,
+    local_hash: [u8; 16],
+    local_json: String,
+    handler: H,
+    clients: HashMap<[u8; 16], Arc<Protocol>>,
+}
+
+/// The state of a connection to a server.
+#[derive(Default)]
+struct Session {
+    /// Whether the connection outlives a call, and only needs a handshake with the first one.
+    stateful: bool,
+    /// The protocol of the client, once the handshake succeeded.
+    client: Option<Arc<Protocol>>,
+}
+
+/// What the server knows of the protocol of a client, after their handshake.
+enum ClientProtocol {
+    /// The protocol of the client, cached by the server.
+    Known(Arc<Protocol>),
+    /// The client did not send its protocol, which the server does not know.
+    Unknown,
+    /// The protocol sent by the client failed to parse.
+    Invalid(Error),
+}
+
+impl<H: Handler> Responder<H> {
+    /// Create a server of `protocol`, whose calls are handled by `handler`.
+    pub fn new(protocol: Protocol, handler: H) -> AvroResult<Self> {
+        let local_json = serde_json::to_string(&protocol).map_err(Error::ConvertJsonToString)?;
+        let local_hash = protocol.md5();
+        let mut clients = HashMap::new();
+        clients.insert(local_hash, Arc::new(protocol.clone()));
+        Ok(Responder {
+            local: protocol,
+            local_hash,
+            local_json,
+            handler,
+            clients,
+        })
+    }
+
+    /// Respond to a request of a stateless transport, which starts with a handshake.
+    pub fn respond(&mut self, request: &[u8]) -> AvroResult<Vec<u8>> {
+        self.respond_in(request, &mut Session::default())
+    }
+
+    fn respond_in(&mut self, request: &[u8], session: &mut Session) -> AvroResult<Vec<u8>> {
+        let mut reader = request;
+        let mut response = Vec::new();
+        let client = match session.client {
+            Some(ref client) if session.stateful => client.clone(),
+            _ => {
+                let (handshake, client) = self.handshake(&mut reader)?;
+                response.extend(handshake);
+                match client {
+                    ClientProtocol::Known(client) => client,
+                    // The call cannot be read without the protocol of the client.
+                    ClientProtocol::Unknown => return Ok(response),
+                    ClientProtocol::Invalid(error) => {
+                        response.extend(self.system_error(error.to_string())?);
+                        return Ok(response);
+                    }
+                }
+            }
+        };
+        session.client = Some(client.clone());
+
+        match self.call(&mut reader, &client) {
+            Ok(call_response) => response.extend(call_response),
+            Err(e) => response.extend(self.system_error(e.to_string())?),
+        }
+        Ok(response)
+    }
+
+    /// Read a call of a client of the given protocol, hand it to the handler, and return its
+    /// response, which is empty for one-way messages. Fails if the call cannot be read.
+    fn call(&mut self, reader: &mut &[u8], client: &Protocol) -> AvroResult<Vec<u8>> {
+        let meta = meta_from_value(decode(&META, reader)?);
+        let name = match decode(&Schema::String, reader)? {
+            Value::String(name) => name,
+            _ => unreachable!(),
+        };
+        let local = match self.local.message(&name) {
+            Some(local) => local,
+            None => return self.system_error(format!("Unknown message {:?}", name)),
+        };
+        let remote = client.message(&name).unwrap_or(local);
+        let request = match decode(&remote.request_schema(), reader)
+            .and_then(|request| request.resolve(&local.request_schema()))
+        {
+            Ok(request) => request,
+            // The client of a one-way message does not read any response.
+            Err(_) if local.one_way => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let result = self.handler.handle(Call {
+            message: local,
+            request,
+            meta,
+        });
+        if local.one_way {
+            return Ok(Vec::new());
+        }
+        let payload = match result {
+            Ok(value) => value
+                .resolve(&local.response)
+                .and_then(|value| to_avro_datum(&local.response, value))
+                .map(|payload| (false, payload)),
+            Err(error) => error_union(local)
+                .map(Schema::Union)
+                .and_then(|schema| to_avro_datum(&schema, Value::Union(None, Box::new(error))))
+                .map(|payload| (true, payload)),
+        };
+        match payload {
+            Ok((is_error, payload)) => {
+                let mut response = to_avro_datum(&META, meta_value(Meta::new()))?;
+                response.extend(to_avro_datum(&Schema::Boolean, is_error)?);
+                response.extend(payload);
+                Ok(response)
+            }
+            Err(e) => self.system_error(e.to_string()),
+        }
+    }
+
+    /// Read the handshake request of a client, and return the handshake response along with what
+    /// is known of the protocol of the client.
+    fn handshake(&mut self, reader: &mut &[u8]) -> AvroResult<(Vec<u8>, ClientProtocol)> {
+        let handshake = decode(&HANDSHAKE_REQUEST, reader)?;
+        let client_hash = field(&handshake, "clientHash")
+            .and_then(md5)
+            .ok_or_else(|| Error::Handshake("missing client hash".to_string()))?;
+        let server_hash = field(&handshake, "serverHash").and_then(md5);
+        let mut protocol_error = None;
+        if let Some(Value::String(protocol)) = field(&handshake, "clientProtocol") {
+            match Protocol::parse_str(protocol) {
+                Ok(protocol) => self.add_client(client_hash, protocol),
+                Err(e) => protocol_error = Some(e),
+            }
+        }
+
+        let handshake_match = if !self.clients.contains_key(&client_hash) {
+            HandshakeMatch::None
+        } else if server_hash == Some(self.local_hash) {
+            HandshakeMatch::Both
+        } else {
+            HandshakeMatch::Client
+        };
+        let (server_protocol, server_hash) = if handshake_match == HandshakeMatch::Both {
+            (None, None)
+        } else {
+            (
+                Some(self.local_json.clone()),
+                Some(Value::Fixed(16, self.local_hash.to_vec())),
+            )
+        };
+        let handshake = Value::Record(vec![
+            (
+                "match".to_string(),
+                Value::Enum(handshake_match as i32, handshake_match.symbol().to_string()),
+            ),
+            ("serverProtocol".to_string(), server_protocol.into()),
+            ("serverHash".to_string(), server_hash.into()),
+            (
+                "meta".to_string(),
+                Value::Union(None, Box::new(Value::Null)),
+            ),
+        ]);
+        let response = to_avro_datum(&HANDSHAKE_RESPONSE, handshake)?;
+        let client = match (self.clients.get(&client_hash), protocol_error) {
+            (Some(client), _) => ClientProtocol::Known(client.clone()),
+            (None, Some(error)) => ClientProtocol::Invalid(error),
+            (None, None) => ClientProtocol::Unknown,
+        };
+        Ok((response, client))
+    }
+
+    /// Cache the protocol of a client, evicting another one than the protocol of the server if
+    /// there are too many already. The clients of the evicted protocol send it again.
+    fn add_client(&mut self, client_hash: [u8; 16], protocol: Protocol) {
+        if !self.clients.contains_key(&client_hash) && self.clients.len() >= MAX_CLIENT_PROTOCOLS {
+            let local_hash = self.local_hash;
+            let evicted = self
+                .clients
+                .keys()
+                .find(|&&hash| hash != local_hash)
+                .copied();
+            if let Some(evicted) = evicted {
+                self.clients.remove(&evicted);
+            }
+        }
+        self.clients.insert(client_hash, Arc::new(protocol));
+    }
+
+    /// Encode the response of a call which failed with a system error.
+    fn system_error(&self, error: String) -> AvroResult<Vec<u8>> {
+        let mut response = to_avro_datum(&META, meta_value(Meta::new()))?;
+        response.extend(to_avro_datum(&Schema::Boolean, true)?);
+        response.extend(to_avro_datum(
+            &SYSTEM_ERROR,
+            Value::Union(None, Box::new(Value::String(error))),
+        )?);
+        Ok(response)
+    }
+}
+
+/// A server of a `Responder` over TCP, whose connections are stateful and carry messages framed
+/// in length-prefixed buffers.
+pub struct SocketServer<H> {
+    listener: TcpListener,
+    responder: Responder<H>,
+}
+
+impl<H: Handler> SocketServer<H> {
+    /// Create a server listening at `address`.
+    pub fn bind<A: ToSocketAddrs>(address: A, responder: Responder<H>) -> AvroResult<Self> {
+        let listener = TcpListener::bind(address).map_err(Error::RpcIo)?;
+        Ok(SocketServer {
+            listener,
+            responder,
+        })
+    }
+
+    /// Returns the address the server listens at.
+    pub fn local_addr(&self) -> AvroResult<SocketAddr> {
+        self.listener.local_addr().map_err(Error::RpcIo)
+    }
+
+    /// Accept a connection, and respond to its calls until the client closes it.
+    pub fn serve_connection(&mut self) -> AvroResult<()> {
+        let (stream, _) = self.listener.accept().map_err(Error::RpcIo)?;
+        self.handle(stream)
+    }
+
+    fn handle(&mut self, mut stream: TcpStream) -> AvroResult<()> {
+        let mut session = Session {
+            stateful: true,
+            client: None,
+        };
+        while let Some(request) = read_framed(&mut stream)? {
+            let response = self.responder.respond_in(&request, &mut session)?;
+            // The one-way calls have no response, once the handshake is done.
+            if !response.is_empty() {
+                write_framed(&mut stream, &response)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Serve the connections one after the other, until accepting one fails. The errors of the
+    /// connections themselves only close them.
+    pub fn serve(&mut self) -> AvroResult<()> {
+        loop {
+            let (stream, _) = self.listener.accept().map_err(Error::RpcIo)?;
+            let _ = self.handle(stream);
+        }
+    }
+}
+
+/// A server of a `Responder` over HTTP, which answers to the requests posted by
+/// `HttpTransceiver`s.
+pub struct HttpServer<H> {
+    listener: TcpListener,
+    responder: Responder<H>,
+}
+
+impl<H: Handler> HttpServer<H> {
+    /// Create a server listening at `address`.
+    pub fn bind<A: ToSocketAddrs>(address: A, responder: Responder<H>) -> AvroResult<Self> {
+        let listener = TcpListener::bind(address).map_err(Error::RpcIo)?;
+        Ok(HttpServer {
+            listener,
+            responder,
+        })
+    }
+
+    /// Returns the address the server listens at.
+    pub fn local_addr(&self) -> AvroResult<SocketAddr> {
+        self.listener.local_addr().map_err(Error::RpcIo)
+    }
+
+    /// Accept a connection, and respond to the request posted to it.
+    pub fn serve_connection(&mut self) -> AvroResult<()> {
+        let (stream, _) = self.listener.accept().map_err(Error::RpcIo)?;
+        self.handle(stream)
+    }
+
+    fn handle(&mut self, mut stream: TcpStream) -> AvroResult<()> {
+        let (request_line, body) = read_http(&mut BufReader::new(
+            stream.try_clone().map_err(Error::RpcIo)?,
+        ))?;
+        if !request_line.starts_with("POST ") {
+            return write_http(
+                &mut stream,
+                "HTTP/1.1 405 Method Not Allowed\r\nAllow: POST\r\n",
+                &[],
+            );
+        }
+        let response = read_framed(&mut &body[..])
+            .and_then(|request| {
+                request.ok_or_else(|| Error::Http("The request is empty".to_string()))
+            })
+            .and_then(|request| self.responder.respond(&request));
+        match response {
+            Ok(response) => write_http(
+                &mut stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: avro/binary\r\n",
+                &response,
+            ),
+            Err(e) => {
+                write_http(&mut stream, "HTTP/1.1 500 Internal Server Error\r\n", &[])?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Serve the connections one after the other, until accepting one fails. The errors of the
+    /// connections themselves only close them.
+    pub fn serve(&mut self) -> AvroResult<()> {
+        loop {
+            let (stream, _) = self.listener.accept().map_err(Error::RpcIo)?;
+            let _ = self.handle(stream);
+        }
+    }
+}
+
+/// Write a message as a series of length-prefixed buffers, terminated by an empty buffer.
+fn write_framed<W: Write>(writer: &mut W, message: &[u8]) -> AvroResult<()> {
+    let mut framed = Vec::with_capacity(message.len() + 8);
+    for buffer in message.chunks(BUFFER_SIZE) {
+        framed.extend(&(buffer.len() as u32).to_be_bytes());
+        framed.extend(buffer);
+    }
+    framed.extend(&0u32.to_be_bytes());
+    writer.write_all(&framed).map_err(Error::RpcIo)?;
+    writer.flush().map_err(Error::RpcIo)
+}
+
+/// Read a message written by `write_framed`, or `None` if the input ends before it.
+fn read_framed<R: Read>(reader: &mut R) -> AvroResult<Option<Vec<u8>>> {
+    let mut message = Vec::new();
+    let mut length = [0; 4];
+    let mut first = true;
+    loop {
+        match reader.read_exact(&mut length) {
+            Err(e) if first && e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            result => result.map_err(Error::RpcIo)?,
+        }
+        first = false;
+        let length = safe_len(u32::from_be_bytes(length) as usize)?;
+        if length == 0 {
+            return Ok(Some(message));
+        }
+        let start = message.len();
+        message.resize(start + length, 0);
+        reader
+            .read_exact(&mut message[start..])
+            .map_err(Error::RpcIo)?;
+    }
+}
+
+/// Write an HTTP message, made of its start line and headers, and of a body framed by
+/// `write_framed`.
+fn write_http<W: Write>(writer: &mut W, head: &str, body: &[u8]) -> AvroResult<()> {
+    let mut framed = Vec::new();
+    if !body.is_empty() {
+        write_framed(&mut framed, body)?;
+    }
+    let head = format!(
+        "{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+        head,
+        framed.len()
+    );
+    writer.write_all(head.as_bytes()).map_err(Error::RpcIo)?;
+    writer.write_all(&framed).map_err(Error::RpcIo)?;
+    writer.flush().map_err(Error::RpcIo)
+}
+
+/// Read an HTTP message, and return its start line and body.
+fn read_http<R: BufRead>(reader: &mut R) -> AvroResult<(String, Vec<u8>)> {
+    let mut read_line = || {
+        let mut line = String::new();
+        reader.read_line(&mut line).map_err(Error::RpcIo)?;
+        Ok(line.trim_end().to_string())
+    };
+    let start_line = read_line()?;
+    let mut length = 0;
+    loop {
+        let header = read_line()?;
+        if header.is_empty() {
+            break;
+        }
+        let mut parts = header.splitn(2, ':');
+        if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| Error::Http(format!("Invalid header {:?}", header)))?;
+            }
+        }
+    }
+    let mut body = vec![0; safe_len(length)?];
+    reader.read_exact(&mut body).map_err(Error::RpcIo)?;
+    Ok((start_line, body))
+}
+
+/// Read the system error which follows a handshake response, if any.
+fn read_system_error(reader: &mut &[u8]) -> Option<String> {
+    decode(&META, reader).ok()?;
+    match (
+        decode(&Schema::Boolean, reader).ok()?,
+        decode(&SYSTEM_ERROR, reader).ok()?,
+    ) {
+        (Value::Boolean(true), Value::Union(_, error)) => match *error {
+            Value::String(error) => Some(error),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the union of the errors of a message, whose first branch is the `string` of system
+/// errors.
+fn error_union(message: &Message) -> AvroResult<UnionSchema> {
+    let mut errors = vec![Schema::String];
+    errors.extend(message.errors.iter().cloned());
+    UnionSchema::new(errors)
+}
+
+/// Returns the value of a field of a record, out of its union if it is in one.
+fn field<'v>(record: &'v Value, name: &str) -> Option<&'v Value> {
+    match record {
+        Value::Record(fields) => {
+            fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| match value {
+                    Value::Union(_, value) => &**value,
+                    value => value,
+                })
+        }
+        _ => None,
+    }
+}
+
+fn md5(value: &Value) -> Option<[u8; 16]> {
+    match value {
+        Value::Fixed(16, bytes) => {
+            let mut hash = [0; 16];
+            hash.copy_from_slice(bytes);
+            Some(hash)
+        }
+        _ => None,
+    }
+}
+
+fn meta_value(meta: Meta) -> Value {
+    Value::Map(
+        meta.into_iter()
+            .map(|(key, value)| (key, Value::Bytes(value)))
+            .collect(),
+    )
+}
+
+fn meta_from_value(value: Value) -> Meta {
+    match value {
+        Value::Map(meta) => meta
+            .into_iter()
+            .filter_map(|(key, value)| match value {
+                Value::Bytes(value) => Some((key, value)),
+                _ => None,
+            })
+            .collect(),
+        _ => Meta::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    const PROTOCOL: &str = r#"{
+        "protocol": "Calculator",
+        "namespace": "org.example",
+        "types": [
+            {"type": "error", "name": "DivisionByZero", "fields": [
+                {"name": "dividend", "type": "int"}
+            ]}
+        ],
+        "messages": {
+            "divide": {
+                "request": [{"name": "a", "type": "int"}, {"name": "b", "type": "int"}],
+                "response": "int",
+                "errors": ["DivisionByZero"]
+            },
+            "fail": {"request": [], "response": "null"},
+            "log": {"request": [{"name": "line", "type": "string"}], "response": "null",
+                "one-way": true}
+        }
+    }"#;
+
+    fn handle(call: Call<'_>) -> Result<Value, Value> {
+        let params = match call.request {
+            Value::Record(params) => params,
+            request => panic!("Expected a record, got {:?}", request),
+        };
+        match (call.message.name.as_str(), &params[..]) {
+            ("divide", [(_, Value::Int(a)), (_, Value::Int(0))]) => Err(Value::Record(vec![(
+                "dividend".to_string(),
+                Value::Int(*a),
+            )])),
+            ("divide", [(_, Value::Int(a)), (_, Value::Int(b))]) => Ok(Value::Int(a / b)),
+            ("log", _) => Ok(Value::Null),
+            _ => Err(Value::String("Not implemented".to_string())),
+        }
+    }
+
+    /// A transport to a `Responder` of the same process, which records the match of the
+    /// handshake responses.
+    struct Loopback<'r, H>(&'r mut Responder<H>, Vec<String>);
+
+    impl<'r, H> Loopback<'r, H> {
+        fn new(responder: &'r mut Responder<H>) -> Self {
+            Loopback(responder, Vec::new())
+        }
+    }
+
+    impl<H: Handler> Transceiver for Loopback<'_, H> {
+        fn transceive(&mut self, request: &[u8]) -> AvroResult<Vec<u8>> {
+            let response = self.0.respond(request)?;
+            let handshake = decode(&HANDSHAKE_RESPONSE, &mut &response[..])?;
+            if let Some(Value::Enum(_, symbol)) = field(&handshake, "match") {
+                self.1.push(symbol.clone());
+            }
+            Ok(response)
+        }
+    }
+
+    fn divide(a: i32, b: i32) -> Value {
+        Value::Record(vec![
+            ("a".to_string(), Value::Int(a)),
+            ("b".to_string(), Value::Int(b)),
+        ])
+    }
+
+    fn check_calls<T: Transceiver>(requestor: &mut Requestor<T>) {
+        assert_eq!(
+            requestor.request("divide", divide(7, 2)).unwrap(),
+            Value::Int(3)
+        );
+        match requestor.request("divide", divide(7, 0)) {
+            Err(Error::RemoteError(error)) => assert_eq!(
+                error,
+                Value::Record(vec![("dividend".to_string(), Value::Int(7))])
+            ),
+            other => panic!("Expected a declared error, got {:?}", other),
+        }
+        assert!(matches!(
+            requestor.request("fail", Value::Record(vec![])),
+            Err(Error::RemoteSystemError(ref error)) if error == "Not implemented"
+        ));
+        assert_eq!(
+            requestor
+                .request(
+                    "log",
+                    Value::Record(vec![("line".to_string(), "hello".into())])
+                )
+                .unwrap(),
+            Value::Null
+        );
+        assert!(matches!(
+            requestor.request("unknown", Value::Record(vec![])),
+            Err(Error::UnknownMessage(_))
+        ));
+    }
+
+    #[test]
+    fn test_framing() {
+        let message = (0..20_000).map(|i| i as u8).collect::<Vec<_>>();
+        let mut framed = Vec::new();
+        write_framed(&mut framed, &message).unwrap();
+        assert_eq!(framed.len(), message.len() + 4 * 4);
+        assert_eq!(framed[..4], 8192u32.to_be_bytes());
+
+        let mut reader = &framed[..];
+        assert_eq!(read_framed(&mut reader).unwrap(), Some(message));
+        assert_eq!(read_framed(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn test_socket_rpc() {
+        let protocol = Protocol::parse_str(PROTOCOL).unwrap();
+        let responder = Responder::new(protocol.clone(), handle).unwrap();
+        let mut server = SocketServer::bind("127.0.0.1:0", responder).unwrap();
+        let address = server.local_addr().unwrap();
+        let server = thread::spawn(move || server.serve_connection());
+
+        let transceiver = SocketTransceiver::connect(address).unwrap();
+        let mut requestor = Requestor::new(protocol.clone(), transceiver);
+        check_calls(&mut requestor);
+        assert_eq!(requestor.remote_protocol(), Some(&protocol));
+        drop(requestor);
+        server.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_http_rpc() {
+        let protocol = Protocol::parse_str(PROTOCOL).unwrap();
+        let responder = Responder::new(protocol.clone(), handle).unwrap();
+        let mut server = HttpServer::bind("127.0.0.1:0", responder).unwrap();
+        let address = server.local_addr().unwrap();
+        // Every call but the one of the unknown message opens a connection.
+        let server = thread::spawn(move || (0..4).try_for_each(|_| server.serve_connection()));
+
+        let transceiver = HttpTransceiver::new(&address.to_string(), "/rpc");
+        let mut requestor = Requestor::new(protocol, transceiver);
+        check_calls(&mut requestor);
+        server.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_protocol_resolution() {
+        let server_protocol = Protocol::parse_str(PROTOCOL).unwrap();
+        // The client reads the responses as longs, and has no parameter `b` but its default.
+        let client_protocol = Protocol::parse_str(
+            &PROTOCOL
+                .replace(r#""response": "int""#, r#""response": "long""#)
+                .replace(
+                    r#"{"name": "b", "type": "int"}"#,
+                    r#"{"name": "c", "type": "string", "default": ""}"#,
+                ),
+        )
+        .unwrap();
+        let mut responder = Responder::new(server_protocol.clone(), |call: Call<'_>| {
+            match call.request {
+                Value::Record(params) => Ok(Value::Int(params.len() as i32)),
+                _ => Err(Value::Null),
+            }
+        })
+        .unwrap();
+
+        let mut requestor = Requestor::new(client_protocol, Loopback::new(&mut responder));
+        match requestor.request(
+            "divide",
+            Value::Record(vec![("a".to_string(), Value::Int(1))]),
+        ) {
+            Err(Error::RemoteSystemError(error)) => assert!(error.contains("b")),
+            other => panic!("Expected a missing parameter, got {:?}", other),
+        }
+        assert_eq!(requestor.remote_protocol(), Some(&server_protocol));
+
+        let client_protocol =
+            Protocol::parse_str(&PROTOCOL.replace(r#""response": "int""#, r#""response": "long""#))
+                .unwrap();
+        let mut requestor = Requestor::new(client_protocol, Loopback::new(&mut responder));
+        assert_eq!(
+            requestor.request("divide", divide(1, 1)).unwrap(),
+            Value::Long(2),
+            "The int response of the server is read as a long"
+        );
+    }
+
+    #[test]
+    fn test_handshake_match() {
+        let server_protocol = Protocol::parse_str(PROTOCOL).unwrap();
+        let client_protocol =
+            Protocol::parse_str(&PROTOCOL.replace(r#""response": "int""#, r#""response": "long""#))
+                .unwrap();
+        let mut responder = Responder::new(server_protocol, handle).unwrap();
+
+        // The server does not know the protocol of the client, which sends it again.
+        let mut requestor = Requestor::new(client_protocol.clone(), Loopback::new(&mut responder));
+        for _ in 0..2 {
+            assert_eq!(
+                requestor.request("divide", divide(4, 2)).unwrap(),
+                Value::Long(2)
+            );
+        }
+        assert_eq!(requestor.transceiver.1, ["NONE", "BOTH", "BOTH"]);
+
+        // The server knows the protocol of the client, which does not know the one of the server.
+        let mut requestor = Requestor::new(client_protocol, Loopback::new(&mut responder));
+        assert_eq!(
+            requestor.request("divide", divide(4, 2)).unwrap(),
+            Value::Long(2)
+        );
+        assert_eq!(requestor.transceiver.1, ["CLIENT"]);
+
+        // A protocol which cannot be parsed is answered with a system error.
+        let handshake = Value::Record(vec![
+            ("clientHash".to_string(), Value::Fixed(16, vec![0; 16])),
+            ("clientProtocol".to_string(), Some("{").into()),
+            ("serverHash".to_string(), Value::Fixed(16, vec![0; 16])),
+            (
+                "meta".to_string(),
+                Value::Union(None, Box::new(Value::Null)),
+            ),
+        ]);
+        let response = responder
+            .respond(&to_avro_datum(&HANDSHAKE_REQUEST, handshake).unwrap())
+            .unwrap();
+        let mut reader = &response[..];
+        let handshake = decode(&HANDSHAKE_RESPONSE, &mut reader).unwrap();
+        assert!(matches!(
+            field(&handshake, "match"),
+            Some(Value::Enum(_, ref symbol)) if symbol == "NONE"
+        ));
+        assert!(read_system_error(&mut reader).is_some());
+    }
+
+    #[test]
+    fn test_malformed_requests() {
+        let protocol = Protocol::parse_str(PROTOCOL).unwrap();
+        let mut responder = Responder::new(protocol.clone(), handle).unwrap();
+        assert!(responder.respond(&[0xff]).is_err());
+
+        // A call which cannot be read is answered with a system error, after the handshake.
+        let requestor = Requestor::new(protocol.clone(), HttpTransceiver::new("", ""));
+        let mut request = requestor.handshake_request().unwrap();
+        request.push(0x02);
+        let response = responder.respond(&request).unwrap();
+        let mut reader = &response[..];
+        decode(&HANDSHAKE_RESPONSE, &mut reader).unwrap();
+        assert!(read_system_error(&mut reader).is_some());
+
+        // A truncated frame.
+        assert!(read_framed(&mut &[0, 0, 0, 2, 1][..]).is_err());
+
+        let mut server = HttpServer::bind("127.0.0.1:0", responder).unwrap();
+        let address = server.local_addr().unwrap();
+        let server = thread::spawn(move || server.serve_connection());
+        let mut transceiver = HttpTransceiver::new(&address.to_string(), "/rpc");
+        match transceiver.transceive(&[0xff]) {
+            Err(Error::Http(status)) => assert!(status.contains("500")),
+            other => panic!("Expected an internal server error, got {:?}", other),
+        }
+        assert!(server.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_client_protocols_eviction() {
+        let protocol = Protocol::parse_str(PROTOCOL).unwrap();
+        let mut responder = Responder::new(protocol, handle).unwrap();
+        for i in 0..MAX_CLIENT_PROTOCOLS + 2 {
+            let client_protocol =
+                Protocol::parse_str(&PROTOCOL.replace("Calculator", &format!("Calculator{}", i)))
+                    .unwrap();
+            let mut requestor = Requestor::new(client_protocol, Loopback::new(&mut responder));
+            assert_eq!(
+                requestor.request("divide", divide(4, 2)).unwrap(),
+                Value::Int(2)
+            );
+        }
+        assert_eq!(responder.clients.len(), MAX_CLIENT_PROTOCOLS);
+        assert!(responder.clients.contains_key(&responder.local_hash));
+    }
+}
@@ -111,6 +111,11 @@
 //! *N.B.* It is important to note that the composition of schema definitions requires schemas with names.
 //! For this reason, only schemas of type Record, Enum, and Fixed should be input into this function.
 //!
+//! Schemas are parsed in `ParsingMode::Strict` by default, which rejects schemas that are not valid
+//! per the specification, reporting every violation at once. Schemas of legacy data can be parsed
+//! with `Schema::parse_str_with_mode` in `ParsingMode::Lenient` instead, which is also how the schema
+//! of a data file is read.
+//!
 //! The library provides also a programmatic interface to define schemas without encoding them in
 //! JSON (for advanced use), but we highly recommend the JSON interface. Please read the API
 //! reference in case you are interested.
@@ -495,13 +500,19 @@
 //! `avro-rs` also supports the logical types listed in the [Avro specification](https://avro.apache.org/docs/current/spec.html#Logical+Types):
 //!
 //! 1. `Decimal` using the [`num_bigint`](https://docs.rs/num-bigint/0.2.6/num_bigint) crate
+//! 1. `BigDecimal`, a `Decimal` along with its own scale, for decimals of arbitrary scale
 //! 1. UUID using the [`uuid`](https://docs.rs/uuid/0.8.1/uuid) crate
 //! 1. Date, Time (milli) as `i32` and Time (micro) as `i64`
-//! 1. Timestamp (milli and micro) as `i64`
+//! 1. Timestamp and local timestamp (milli, micro and nano) as `i64`
 //! 1. Duration as a custom type with `months`, `days` and `millis` accessor methods each of which returns an `i32`
 //!
 //! Note that the on-disk representation is identical to the underlying primitive/complex type.
 //!
+//! Other logical types can be registered in a `logical_type::LogicalTypes`, along with the kinds
+//! of schemas they annotate, how their values are validated and how they are converted to a richer
+//! representation, held by a `Value::Custom`. The schemas parsed with
+//! `Schema::parse_str_with_logical_types` hold them as a `Schema::Custom`.
+//!
 //! ### Read and write logical types
 //!
 //! ```rust
@@ -705,6 +716,22 @@
 //! let readers_schema = Schema::parse_str(r#"{"type": "array", "items":"int"}"#).unwrap();
 //! assert_eq!(false, SchemaCompatibility::can_read(&writers_schema, &readers_schema));
 //! ```
+//!
+//! 3. Incompatibilities between schemas
+//!
+//! Explanation: `read_incompatibilities` returns every reason why the schemas are not
+//! compatible, along with where they are found in the schemas
+//!
+//! ```rust
+//! use avro_rs::{Schema, schema_compatibility::SchemaCompatibility};
+//!
+//! let writers_schema = Schema::parse_str(r#"{"type": "record", "name": "r", "fields": [{"name": "a", "type": "long"}]}"#).unwrap();
+//! let readers_schema = Schema::parse_str(r#"{"type": "record", "name": "r", "fields": [{"name": "a", "type": "int"}, {"name": "b", "type": "string"}]}"#).unwrap();
+//! for incompatibility in SchemaCompatibility::read_incompatibilities(&writers_schema, &readers_schema) {
+//!     // e.g. "/fields/a: type mismatch (writer: Long, reader: Int)"
+//!     println!("{}", incompatibility);
+//! }
+//! ```
 
 mod codec;
 mod de;
@@ -718,14 +745,21 @@ mod ser;
 mod util;
 mod writer;
 
+pub mod idl;
+pub mod ipc;
+pub mod logical_type;
+pub mod protocol;
 pub mod rabin;
 pub mod schema;
 pub mod schema_compatibility;
+pub mod schema_diff;
+pub mod schema_lint;
+pub mod schema_loader;
 pub mod types;
 
 pub use codec::Codec;
 pub use de::from_value;
-pub use decimal::Decimal;
+pub use decimal::{BigDecimal, Decimal};
 pub use duration::{Days, Duration, Millis, Months};
 pub use error::{Error, Error as DeError, Error as SerError};
 pub use reader::{from_avro_datum, Reader};
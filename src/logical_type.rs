@@ -0,0 +1,511 @@
+//! Logic for custom logical types, which annotate schemas with a `logicalType` other than the
+//! ones of the specification
+use crate::{
+    schema::{Schema, SchemaKind},
+    types::Value,
+    AvroResult, Error,
+};
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// A custom logical type, which annotates schemas of some base kinds and may represent their
+/// values in a richer form.
+///
+/// Once registered in the `LogicalTypes` a schema is parsed with, the logical type is consulted
+/// for every schema whose `logicalType` attribute is its name, which is parsed into a
+/// `Schema::Custom`:
+///
+/// * parsing the schema fails unless its kind is one of the `base_kinds` and `validate_schema`
+///   accepts it;
+/// * validating a value checks it with `validate`, once converted to the base schema;
+/// * decoding or resolving a value converts it with `to_custom` into a `Value::Custom`;
+/// * encoding a `Value::Custom` converts it back with `to_base`, as well as any value which is not
+///   a valid value of the base schema, like the values serialized by serde from the richer
+///   representation.
+///
+/// `from_value` deserializes a `Value::Custom` from its richer representation.
+///
+/// ```
+/// use avro_rs::{
+///     logical_type::{LogicalType, LogicalTypes},
+///     schema::{ParsingMode, SchemaKind},
+///     types::Value,
+///     Schema,
+/// };
+///
+/// struct IsoCountry;
+///
+/// impl LogicalType for IsoCountry {
+///     fn base_kinds(&self) -> &[SchemaKind] {
+///         &[SchemaKind::String]
+///     }
+///
+///     fn validate(&self, value: &Value) -> bool {
+///         matches!(value, Value::String(s) if s.len() == 2 && s.chars().all(|c| c.is_ascii_uppercase()))
+///     }
+/// }
+///
+/// let mut logical_types = LogicalTypes::new();
+/// logical_types.register("iso-country", IsoCountry);
+/// let schema = Schema::parse_str_with_logical_types(
+///     r#"{"type": "string", "logicalType": "iso-country"}"#,
+///     ParsingMode::Strict,
+///     &logical_types,
+/// )
+/// .unwrap();
+/// assert!(Value::String("FR".to_string()).validate(&schema));
+/// assert!(!Value::String("France".to_string()).validate(&schema));
+/// ```
+pub trait LogicalType: Send + Sync {
+    /// The kinds of schemas the logical type can annotate.
+    fn base_kinds(&self) -> &[SchemaKind];
+
+    /// Check a schema annotated by the logical type, e.g. its custom attributes, returning the
+    /// reason why it is invalid if it is. Every schema of the `base_kinds` is valid by default.
+    fn validate_schema(&self, _schema: &Schema) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Whether a value of the base schema is a valid value of the logical type. Every value is
+    /// valid by default.
+    fn validate(&self, _value: &Value) -> bool {
+        true
+    }
+
+    /// Convert a valid value of the base schema into the richer representation of the logical
+    /// type, which is held by a `Value::Custom`. The value is kept as is by default.
+    fn to_custom(&self, value: Value) -> Result<Value, String> {
+        Ok(value)
+    }
+
+    /// Convert a value in the richer representation of the logical type back into a value of the
+    /// base schema. The value is kept as is by default.
+    fn to_base(&self, value: Value) -> Result<Value, String> {
+        Ok(value)
+    }
+}
+
+/// The custom logical types to parse schemas with, keyed by name.
+///
+/// The logical types of the specification, like `decimal` or `uuid`, are always parsed as such
+/// and cannot be replaced.
+#[derive(Clone, Default)]
+pub struct LogicalTypes {
+    logical_types: HashMap<String, Arc<dyn LogicalType>>,
+}
+
+impl LogicalTypes {
+    /// Create an empty set of logical types.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a custom logical type under `name`, returning the logical type previously
+    /// registered under that name, if any.
+    pub fn register<L: LogicalType + 'static>(
+        &mut self,
+        name: &str,
+        logical_type: L,
+    ) -> Option<Arc<dyn LogicalType>> {
+        self.logical_types
+            .insert(name.to_string(), Arc::new(logical_type))
+    }
+
+    /// Unregister the custom logical type registered under `name`, returning it if any.
+    pub fn unregister(&mut self, name: &str) -> Option<Arc<dyn LogicalType>> {
+        self.logical_types.remove(name)
+    }
+
+    /// Returns the logical type registered under `name`, if any.
+    pub(crate) fn get(&self, name: &str) -> Option<CustomLogicalType> {
+        self.logical_types
+            .get(name)
+            .map(|logical_type| CustomLogicalType {
+                name: name.to_string(),
+                logical_type: logical_type.clone(),
+            })
+    }
+}
+
+impl fmt::Debug for LogicalTypes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.logical_types.keys()).finish()
+    }
+}
+
+/// The custom logical type of a `Schema::Custom`, along with its name.
+#[derive(Clone)]
+pub struct CustomLogicalType {
+    name: String,
+    logical_type: Arc<dyn LogicalType>,
+}
+
+impl fmt::Debug for CustomLogicalType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CustomLogicalType")
+            .field(&self.name)
+            .finish()
+    }
+}
+
+impl CustomLogicalType {
+    /// The name the logical type is registered under, i.e. the `logicalType` of its schemas.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Check `schema`, the base schema annotated by the logical type, once it is parsed.
+    pub(crate) fn check_schema(&self, schema: &Schema) -> AvroResult<()> {
+        let kind = SchemaKind::from(schema);
+        // The kind of a named type referred to is only known once the schema is complete.
+        if kind != SchemaKind::Ref && !self.logical_type.base_kinds().contains(&kind) {
+            return Err(Error::LogicalTypeBaseKind {
+                logical_type: self.name.clone(),
+                kind,
+            });
+        }
+        self.logical_type.validate_schema(schema).map_err(|reason| {
+            Error::ValidateLogicalTypeSchema {
+                logical_type: self.name.clone(),
+                reason,
+            }
+        })
+    }
+
+    /// Whether `value`, a value of the base schema, is valid for the logical type.
+    pub(crate) fn validate(&self, value: &Value) -> bool {
+        self.logical_type.validate(value)
+    }
+
+    /// Convert `value` into a value of the base schema: values of this logical type are converted
+    /// with `to_base`, as well as the values for which `is_base` is false.
+    pub(crate) fn to_base<F>(&self, value: &Value, is_base: F) -> AvroResult<Value>
+    where
+        F: FnOnce(&Value) -> bool,
+    {
+        let value = match value {
+            Value::Custom(name, inner) if *name == self.name => (**inner).clone(),
+            Value::Custom(name, _) => return Err(Error::CustomValueSchema(name.clone())),
+            value if is_base(value) => return Ok(value.clone()),
+            value => value.clone(),
+        };
+        self.logical_type
+            .to_base(value)
+            .map_err(|reason| self.convert_error(reason))
+    }
+
+    /// Convert `value`, a value of the base schema, into a `Value::Custom` of the logical type.
+    pub(crate) fn to_custom(&self, value: Value) -> AvroResult<Value> {
+        if !self.logical_type.validate(&value) {
+            return Err(Error::LogicalTypeValue(self.name.clone()));
+        }
+        self.logical_type
+            .to_custom(value)
+            .map(|value| Value::Custom(self.name.clone(), Box::new(value)))
+            .map_err(|reason| self.convert_error(reason))
+    }
+
+    fn convert_error(&self, reason: String) -> Error {
+        Error::ConvertLogicalType {
+            logical_type: self.name.clone(),
+            reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode::encode, from_value, schema::ParsingMode, Reader, Writer};
+    use serde::{Deserialize, Serialize};
+    use std::convert::TryInto;
+
+    struct IsoCountry;
+
+    impl LogicalType for IsoCountry {
+        fn base_kinds(&self) -> &[SchemaKind] {
+            &[SchemaKind::String]
+        }
+
+        fn validate(&self, value: &Value) -> bool {
+            matches!(value, Value::String(s) if s.len() == 2 && s.chars().all(|c| c.is_ascii_uppercase()))
+        }
+    }
+
+    /// A point, represented by an array of its latitude and longitude.
+    struct GeoPoint;
+
+    impl LogicalType for GeoPoint {
+        fn base_kinds(&self) -> &[SchemaKind] {
+            &[SchemaKind::Record]
+        }
+
+        fn to_custom(&self, value: Value) -> Result<Value, String> {
+            match value {
+                Value::Record(fields) => Ok(Value::Array(
+                    fields.into_iter().map(|(_, value)| value).collect(),
+                )),
+                value => Err(format!("not a record: {:?}", value)),
+            }
+        }
+
+        fn to_base(&self, value: Value) -> Result<Value, String> {
+            match value {
+                Value::Array(items) if items.len() == 2 => Ok(Value::Record(
+                    vec!["lat".to_string(), "lon".to_string()]
+                        .into_iter()
+                        .zip(items)
+                        .collect(),
+                )),
+                value => Err(format!("not a point: {:?}", value)),
+            }
+        }
+    }
+
+    /// An amount of money in cents, represented by a long.
+    struct Money;
+
+    impl LogicalType for Money {
+        fn base_kinds(&self) -> &[SchemaKind] {
+            &[SchemaKind::Fixed]
+        }
+
+        fn validate_schema(&self, schema: &Schema) -> Result<(), String> {
+            match schema {
+                Schema::Fixed { size: 8, .. } if schema.attribute("currency").is_some() => Ok(()),
+                _ => Err("a fixed of size 8 with a currency is expected".to_string()),
+            }
+        }
+
+        fn to_custom(&self, value: Value) -> Result<Value, String> {
+            match value {
+                Value::Fixed(8, bytes) => Ok(Value::Long(i64::from_be_bytes(
+                    bytes[..].try_into().unwrap(),
+                ))),
+                value => Err(format!("not a fixed of size 8: {:?}", value)),
+            }
+        }
+
+        fn to_base(&self, value: Value) -> Result<Value, String> {
+            match value {
+                Value::Long(cents) => Ok(Value::Fixed(8, cents.to_be_bytes().to_vec())),
+                value => Err(format!("not a long: {:?}", value)),
+            }
+        }
+    }
+
+    const SCHEMA: &str = r#"
+    {
+        "type": "record",
+        "name": "Place",
+        "fields": [
+            {"name": "country", "type": {"type": "string", "logicalType": "iso-country"}},
+            {
+                "name": "location",
+                "type": {
+                    "type": "record",
+                    "name": "GeoPoint",
+                    "logicalType": "geo-point",
+                    "fields": [
+                        {"name": "lat", "type": "double"},
+                        {"name": "lon", "type": "double"}
+                    ]
+                }
+            },
+            {
+                "name": "price",
+                "type": {
+                    "type": "fixed",
+                    "name": "Money",
+                    "size": 8,
+                    "logicalType": "money",
+                    "currency": "EUR"
+                }
+            }
+        ]
+    }
+    "#;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Place {
+        country: String,
+        location: (f64, f64),
+        price: i64,
+    }
+
+    fn logical_types() -> LogicalTypes {
+        let mut logical_types = LogicalTypes::new();
+        logical_types.register("iso-country", IsoCountry);
+        logical_types.register("geo-point", GeoPoint);
+        logical_types.register("money", Money);
+        logical_types
+    }
+
+    fn parse(input: &str) -> AvroResult<Schema> {
+        Schema::parse_str_with_logical_types(input, ParsingMode::Strict, &logical_types())
+    }
+
+    #[test]
+    fn test_parse_logical_types() {
+        let schema = parse(SCHEMA).unwrap();
+        let serialized = serde_json::to_string(&schema).unwrap();
+        assert_eq!(parse(&serialized).unwrap(), schema);
+        assert_eq!(
+            serde_json::to_string(&parse(&serialized).unwrap()).unwrap(),
+            serialized
+        );
+
+        let country = r#"{"type": "string", "logicalType": "iso-country"}"#;
+        match parse(country).unwrap() {
+            Schema::Custom {
+                logical_type,
+                inner,
+            } => {
+                assert_eq!(logical_type.name(), "iso-country");
+                assert_eq!(*inner, Schema::String);
+            }
+            other => panic!("Expected Schema::Custom, got {:?}", other),
+        }
+        // Unregistered logical types are ignored, and only kept as an attribute.
+        let schema = Schema::parse_str(country).unwrap();
+        assert!(matches!(
+            schema,
+            Schema::Attributed { ref inner, .. } if **inner == Schema::String
+        ));
+        assert_eq!(
+            schema.attribute("logicalType"),
+            Some(&serde_json::Value::from("iso-country"))
+        );
+        let mut logical_types = logical_types();
+        logical_types.unregister("iso-country");
+        assert!(matches!(
+            Schema::parse_str_with_logical_types(country, ParsingMode::Strict, &logical_types),
+            Ok(Schema::Attributed { ref inner, .. }) if **inner == Schema::String
+        ));
+
+        assert!(matches!(
+            parse(r#"{"type": "int", "logicalType": "iso-country"}"#),
+            Err(Error::ParseSchemaAt { ref source, .. })
+                if matches!(**source, Error::LogicalTypeBaseKind { kind: SchemaKind::Int, .. })
+        ));
+        assert!(matches!(
+            parse(r#"{"type": "fixed", "name": "Money", "size": 8, "logicalType": "money"}"#),
+            Err(Error::ParseSchemaAt { ref source, .. })
+                if matches!(**source, Error::ValidateLogicalTypeSchema { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_logical_types() {
+        let schema = parse(r#"{"type": "string", "logicalType": "iso-country"}"#).unwrap();
+        assert!(Value::String("FR".to_string()).validate(&schema));
+        assert!(!Value::String("France".to_string()).validate(&schema));
+        assert_eq!(
+            SchemaKind::from(&Value::Custom(
+                "iso-country".to_string(),
+                Box::new("FR".into())
+            )),
+            SchemaKind::Custom
+        );
+
+        let schema = parse(SCHEMA).unwrap();
+        let mut writer = Writer::new(&schema, Vec::new());
+        let place = Place {
+            country: "France".to_string(),
+            location: (48.85, 2.35),
+            price: 1999,
+        };
+        assert!(matches!(writer.append_ser(place), Err(Error::Validation)));
+    }
+
+    #[test]
+    fn test_write_and_read_logical_types() -> AvroResult<()> {
+        let schema = parse(SCHEMA)?;
+        let place = Place {
+            country: "FR".to_string(),
+            location: (48.85, 2.35),
+            price: 1999,
+        };
+        let mut writer = Writer::new(&schema, Vec::new());
+        writer.append_ser(&place)?;
+        let input = writer.into_inner()?;
+
+        // The values are converted to the logical types of the reader's schema.
+        let values = Reader::with_schema(&schema, &input[..])?.collect::<AvroResult<Vec<_>>>()?;
+        assert_eq!(
+            values,
+            vec![Value::Record(vec![
+                (
+                    "country".to_string(),
+                    Value::Custom("iso-country".to_string(), Box::new("FR".into()))
+                ),
+                (
+                    "location".to_string(),
+                    Value::Custom(
+                        "geo-point".to_string(),
+                        Box::new(Value::Array(vec![
+                            Value::Double(48.85),
+                            Value::Double(2.35)
+                        ]))
+                    )
+                ),
+                (
+                    "price".to_string(),
+                    Value::Custom("money".to_string(), Box::new(Value::Long(1999)))
+                ),
+            ])]
+        );
+        assert_eq!(from_value::<Place>(&values[0])?, place);
+
+        // Values of logical types are converted back to their base schema.
+        let mut writer = Writer::new(&schema, Vec::new());
+        writer.append(values[0].clone())?;
+        assert_eq!(
+            Reader::with_schema(&schema, &writer.into_inner()?[..])?
+                .collect::<AvroResult<Vec<_>>>()?,
+            values
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_logical_types() {
+        let schema = parse(SCHEMA).unwrap();
+        let location = match schema {
+            Schema::Record { ref fields, .. } => &fields[1].schema,
+            _ => unreachable!(),
+        };
+        let mut buffer = Vec::new();
+        match encode(
+            &Value::Custom("geo-point".to_string(), Box::new(Value::Array(vec![]))),
+            location,
+            &mut buffer,
+        ) {
+            Err(Error::ConvertLogicalType { logical_type, .. }) => {
+                assert_eq!(logical_type, "geo-point")
+            }
+            other => panic!("Expected Error::ConvertLogicalType, got {:?}", other),
+        }
+        match encode(
+            &Value::Custom("iso-country".to_string(), Box::new("FR".into())),
+            location,
+            &mut buffer,
+        ) {
+            Err(Error::CustomValueSchema(name)) => assert_eq!(name, "iso-country"),
+            other => panic!("Expected Error::CustomValueSchema, got {:?}", other),
+        }
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_logical_types() -> AvroResult<()> {
+        let schema = parse(
+            r#"{"type": "fixed", "name": "Money", "size": 8, "logicalType": "money", "currency": "EUR"}"#,
+        )?;
+        assert_eq!(
+            Value::Fixed(8, 1999i64.to_be_bytes().to_vec()).resolve(&schema)?,
+            Value::Custom("money".to_string(), Box::new(Value::Long(1999)))
+        );
+        assert!(Value::Fixed(4, vec![0; 4]).resolve(&schema).is_err());
+        Ok(())
+    }
+}
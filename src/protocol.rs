@@ -0,0 +1,544 @@
+//! Logic for parsing and serializing Avro protocols (`.avpr`)
+use crate::{
+    schema::{
+        namespace_of, parse_defaults, validate, Name, Parser, ParsingMode, RecordField, Schema,
+    },
+    AvroResult, Error,
+};
+use md5::{Digest, Md5};
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Attributes which are part of the definition of a protocol.
+const PROTOCOL_ATTRIBUTES: &[&str] = &["protocol", "namespace", "doc", "types", "messages"];
+
+/// Attributes which are part of the definition of a message.
+const MESSAGE_ATTRIBUTES: &[&str] = &["doc", "request", "response", "errors", "one-way"];
+
+/// Represents an Avro protocol: a set of named types and of the messages exchanged by a client
+/// and a server.
+///
+/// Its types can refer to each other by name, and its messages to its types. Like in schemas, the
+/// references to the named types are resolved when the protocol is parsed.
+///
+/// More information about protocols can be found in the
+/// [Avro specification](https://avro.apache.org/docs/current/spec.html#Protocol+Declaration)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Protocol {
+    /// The name and namespace of the protocol.
+    pub name: Name,
+    pub doc: Option<String>,
+    /// The named types of the protocol, in order.
+    pub types: Vec<Schema>,
+    /// The messages of the protocol, keyed by name.
+    pub messages: BTreeMap<String, Message>,
+    /// The custom attributes of the protocol.
+    pub attributes: BTreeMap<String, Value>,
+}
+
+/// Represents a message of an Avro protocol.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    pub name: String,
+    pub doc: Option<String>,
+    /// The parameters of the request.
+    pub request: Vec<RecordField>,
+    pub response: Schema,
+    /// The error records which the message declares, besides the `string` of system errors.
+    pub errors: Vec<Schema>,
+    /// Whether the message is sent without waiting for a response.
+    pub one_way: bool,
+    /// The custom attributes of the message.
+    pub attributes: BTreeMap<String, Value>,
+}
+
+impl Protocol {
+    /// Create a `Protocol` from a string representing a JSON Avro protocol.
+    pub fn parse_str(input: &str) -> AvroResult<Protocol> {
+        let value = serde_json::from_str(input).map_err(Error::ParseSchemaJson)?;
+        Self::parse(&value)
+    }
+
+    /// Create a `Protocol` from a `serde_json::Value` representing a JSON Avro protocol.
+    ///
+    /// Its types and the parameters of its messages are validated in `ParsingMode::Strict`.
+    pub fn parse(value: &Value) -> AvroResult<Protocol> {
+        let protocol = value.as_object().ok_or(Error::GetProtocolNameField)?;
+        let name = protocol
+            .get("protocol")
+            .and_then(Value::as_str)
+            .ok_or(Error::GetProtocolNameField)?;
+        let namespace = match protocol.get("namespace").and_then(Value::as_str) {
+            Some(namespace) => Some(namespace.to_string()),
+            None => namespace_of(name),
+        };
+
+        let types = match protocol.get("types") {
+            Some(Value::Array(types)) => types.clone(),
+            Some(_) => return Err(Error::GetProtocolTypes),
+            None => Vec::new(),
+        };
+        let mut inputs = HashMap::with_capacity(types.len());
+        let mut order = Vec::with_capacity(types.len());
+        for mut value in types {
+            if let Some(ref namespace) = namespace {
+                inherit_namespace(&mut value, namespace);
+            }
+            validate(&value)?;
+            let fullname = match value {
+                Value::Object(ref complex) => Name::parse(complex)?.fullname(None),
+                _ => return Err(Error::GetNameField),
+            };
+            if inputs.insert(fullname.clone(), value).is_some() {
+                return Err(Error::NameCollision(fullname));
+            }
+            order.push(fullname);
+        }
+        let mut parser = Parser::with_inputs(inputs, HashMap::new());
+        parser.parse_list()?;
+
+        let messages = match protocol.get("messages") {
+            Some(Value::Object(messages)) => messages
+                .iter()
+                .map(|(name, message)| {
+                    Message::parse(name, message, namespace.clone(), &mut parser)
+                        .map(|message| (name.clone(), message))
+                })
+                .collect::<AvroResult<_>>()?,
+            Some(_) => return Err(Error::GetProtocolMessages),
+            None => BTreeMap::new(),
+        };
+
+        let (mut parsed, record_paths) = parser.into_parsed();
+        let types = order
+            .iter()
+            .map(|fullname| {
+                let schema = parsed
+                    .remove(fullname)
+                    .expect("One of the protocol types was unexpectedly not parsed");
+                parse_defaults(schema, ParsingMode::Strict, &record_paths)
+            })
+            .collect::<AvroResult<_>>()?;
+
+        Ok(Protocol {
+            name: Name {
+                name: name.to_string(),
+                namespace: protocol
+                    .get("namespace")
+                    .and_then(Value::as_str)
+                    .map(ToString::to_string),
+                aliases: None,
+            },
+            doc: protocol
+                .get("doc")
+                .and_then(Value::as_str)
+                .map(ToString::to_string),
+            types,
+            messages,
+            attributes: custom_attributes(protocol, PROTOCOL_ATTRIBUTES),
+        })
+    }
+
+    /// Returns the message with the given name, if any.
+    pub fn message(&self, name: &str) -> Option<&Message> {
+        self.messages.get(name)
+    }
+
+    /// Returns the named type of the protocol with the given fullname, if any.
+    pub fn find_type(&self, fullname: &str) -> Option<&Schema> {
+        self.types.iter().find(|schema| match schema {
+            Schema::Record { name, .. }
+            | Schema::Enum { name, .. }
+            | Schema::Fixed { name, .. } => name.fullname(None) == fullname,
+            _ => false,
+        })
+    }
+
+    /// Returns the MD5 hash of the JSON representation of the protocol, which identifies it in
+    /// the handshakes of Avro RPC.
+    pub fn md5(&self) -> [u8; 16] {
+        let mut hash = [0; 16];
+        let json = serde_json::to_string(self).expect("A protocol is always serializable to JSON");
+        hash.copy_from_slice(&Md5::digest(json.as_bytes()));
+        hash
+    }
+
+    /// Build the JSON representation of the protocol, where the named types are defined once, and
+    /// referred to by fullname afterwards.
+    fn to_json(&self) -> Value {
+        let error_names: HashSet<String> = self
+            .messages
+            .values()
+            .flat_map(|message| message.errors.iter().filter_map(schema_fullname))
+            .collect();
+        let mut defined = HashSet::new();
+
+        let mut protocol = Map::new();
+        protocol.insert("protocol".to_string(), self.name.name.clone().into());
+        if let Some(ref namespace) = self.name.namespace {
+            protocol.insert("namespace".to_string(), namespace.clone().into());
+        }
+        if let Some(ref doc) = self.doc {
+            protocol.insert("doc".to_string(), doc.clone().into());
+        }
+        for (key, value) in &self.attributes {
+            protocol.insert(key.clone(), value.clone());
+        }
+
+        let types = self
+            .types
+            .iter()
+            .map(|schema| {
+                let mut value = to_value(schema);
+                refer_to_defined(&mut value, self.name.namespace.as_deref(), &mut defined);
+                if let Some(fullname) = schema_fullname(schema) {
+                    if let Value::Object(ref mut complex) = value {
+                        if error_names.contains(&fullname) {
+                            complex.insert("type".to_string(), "error".into());
+                        }
+                    }
+                }
+                value
+            })
+            .collect();
+        protocol.insert("types".to_string(), Value::Array(types));
+
+        let messages = self
+            .messages
+            .iter()
+            .map(|(name, message)| {
+                let mut value = message.to_json();
+                for key in &["request", "response"] {
+                    if let Some(value) = value.get_mut(*key) {
+                        refer_to_defined(value, self.name.namespace.as_deref(), &mut defined);
+                    }
+                }
+                (name.clone(), value)
+            })
+            .collect();
+        protocol.insert("messages".to_string(), Value::Object(messages));
+        Value::Object(protocol)
+    }
+}
+
+impl Message {
+    fn parse(
+        name: &str,
+        value: &Value,
+        namespace: Option<String>,
+        parser: &mut Parser,
+    ) -> AvroResult<Message> {
+        let message = value.as_object().ok_or(Error::GetProtocolMessages)?;
+        let field_error = |field| Error::GetMessageField(name.to_string(), field);
+
+        let request = message
+            .get("request")
+            .and_then(Value::as_array)
+            .ok_or_else(|| field_error("request"))?;
+        // The parameters are validated and their defaults parsed as the fields of a record.
+        let mut record = Map::new();
+        record.insert("type".to_string(), "record".into());
+        record.insert("name".to_string(), name.into());
+        record.insert("fields".to_string(), Value::Array(request.clone()));
+        validate(&Value::Object(record))?;
+        let request = parser.parse_fields_in_namespace(request, namespace.clone())?;
+        let request = match parse_defaults(
+            request_schema(name, request),
+            ParsingMode::Strict,
+            parser.record_paths(),
+        )? {
+            Schema::Record { fields, .. } => fields,
+            _ => unreachable!(),
+        };
+
+        let response = message
+            .get("response")
+            .ok_or_else(|| field_error("response"))?;
+        let response = parser.parse_in_namespace(response, namespace.clone())?;
+
+        let errors = match message.get("errors") {
+            Some(Value::Array(errors)) => errors
+                .iter()
+                .map(|error| {
+                    let error_name = error.as_str().ok_or_else(|| field_error("errors"))?;
+                    match parser.parse_in_namespace(error, namespace.clone())? {
+                        schema @ Schema::Record { .. } => Ok(schema),
+                        _ => Err(Error::GetMessageError {
+                            message: name.to_string(),
+                            error: error_name.to_string(),
+                        }),
+                    }
+                })
+                .collect::<AvroResult<_>>()?,
+            Some(_) => return Err(field_error("errors")),
+            None => Vec::new(),
+        };
+
+        let one_way = match message.get("one-way") {
+            Some(Value::Bool(one_way)) => *one_way,
+            Some(_) => return Err(field_error("one-way")),
+            None => false,
+        };
+        if one_way && (response != Schema::Null || !errors.is_empty()) {
+            return Err(Error::OneWayMessage(name.to_string()));
+        }
+
+        Ok(Message {
+            name: name.to_string(),
+            doc: message
+                .get("doc")
+                .and_then(Value::as_str)
+                .map(ToString::to_string),
+            request,
+            response,
+            errors,
+            one_way,
+            attributes: custom_attributes(message, MESSAGE_ATTRIBUTES),
+        })
+    }
+
+    /// Returns the schema of the request, a record of its parameters named after the message.
+    pub fn request_schema(&self) -> Schema {
+        request_schema(&self.name, self.request.clone())
+    }
+
+    fn to_json(&self) -> Value {
+        let mut message = Map::new();
+        if let Some(ref doc) = self.doc {
+            message.insert("doc".to_string(), doc.clone().into());
+        }
+        for (key, value) in &self.attributes {
+            message.insert(key.clone(), value.clone());
+        }
+        message.insert(
+            "request".to_string(),
+            Value::Array(self.request.iter().map(to_value).collect()),
+        );
+        message.insert("response".to_string(), to_value(&self.response));
+        if !self.errors.is_empty() {
+            let errors = self.errors.iter().filter_map(schema_fullname);
+            message.insert(
+                "errors".to_string(),
+                Value::Array(errors.map(Value::String).collect()),
+            );
+        }
+        if self.one_way {
+            message.insert("one-way".to_string(), Value::Bool(true));
+        }
+        Value::Object(message)
+    }
+}
+
+impl Serialize for Protocol {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.to_json().serialize(serializer)
+    }
+}
+
+fn request_schema(name: &str, fields: Vec<RecordField>) -> Schema {
+    let lookup = fields
+        .iter()
+        .map(|field| (field.name.clone(), field.position))
+        .collect();
+    Schema::Record {
+        name: Name::new(name),
+        doc: None,
+        fields,
+        lookup,
+        attributes: BTreeMap::new(),
+    }
+}
+
+fn schema_fullname(schema: &Schema) -> Option<String> {
+    match schema {
+        Schema::Record { name, .. } | Schema::Enum { name, .. } | Schema::Fixed { name, .. } => {
+            Some(name.fullname(None))
+        }
+        _ => None,
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).expect("A schema is always serializable to JSON")
+}
+
+fn custom_attributes(complex: &Map<String, Value>, known: &[&str]) -> BTreeMap<String, Value> {
+    complex
+        .iter()
+        .filter(|(key, _)| !known.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// Set the namespace of a JSON named type of a protocol to the namespace of the protocol, unless
+/// it has its own.
+pub(crate) fn inherit_namespace(schema: &mut Value, namespace: &str) {
+    if let Value::Object(ref mut complex) = schema {
+        let qualified =
+            matches!(complex.get("name"), Some(Value::String(name)) if name.contains('.'));
+        if !qualified && !complex.contains_key("namespace") {
+            complex.insert("namespace".to_string(), namespace.into());
+        }
+    }
+}
+
+/// Replace the definitions of the named types which are already defined by their fullname, for
+/// the JSON representation of the protocol to be parsed back.
+fn refer_to_defined(value: &mut Value, namespace: Option<&str>, defined: &mut HashSet<String>) {
+    match value {
+        Value::Array(branches) => {
+            for branch in branches {
+                refer_to_defined(branch, namespace, defined);
+            }
+        }
+        Value::Object(complex) => {
+            let named = matches!(
+                complex.get("type").and_then(Value::as_str),
+                Some("record") | Some("error") | Some("enum") | Some("fixed")
+            );
+            if !named {
+                for key in &["type", "items", "values"] {
+                    if let Some(inner) = complex.get_mut(*key) {
+                        refer_to_defined(inner, namespace, defined);
+                    }
+                }
+                return;
+            }
+            let fullname = match Name::parse(complex) {
+                Ok(name) => name.fullname(namespace),
+                Err(_) => return,
+            };
+            if defined.contains(&fullname) {
+                *value = Value::String(fullname);
+                return;
+            }
+            let inner_namespace = namespace_of(&fullname);
+            defined.insert(fullname);
+            if let Some(Value::Array(fields)) = complex.get_mut("fields") {
+                for field in fields {
+                    if let Some(schema) = field.get_mut("type") {
+                        refer_to_defined(schema, inner_namespace.as_deref(), defined);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROTOCOL: &str = r#"{
+        "protocol": "Cards",
+        "namespace": "org.example",
+        "doc": "Cards and their suits.",
+        "version": 2,
+        "types": [
+            {"type": "enum", "name": "Suit", "symbols": ["SPADES", "HEARTS"]},
+            {"type": "record", "name": "Card", "fields": [
+                {"name": "suit", "type": "Suit"},
+                {"name": "number", "type": "int"}
+            ]},
+            {"type": "error", "name": "Invalid", "fields": [{"name": "message", "type": "string"}]}
+        ],
+        "messages": {
+            "draw": {
+                "doc": "Draw a card.",
+                "request": [{"name": "suit", "type": "Suit", "default": "HEARTS"}],
+                "response": "Card",
+                "errors": ["Invalid"],
+                "idempotent": false
+            },
+            "shuffle": {"request": [], "response": "null", "one-way": true}
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_protocol() {
+        let protocol = Protocol::parse_str(PROTOCOL).unwrap();
+        let types = Schema::parse_list(&[
+            r#"{"type": "enum", "name": "org.example.Suit", "symbols": ["SPADES", "HEARTS"]}"#,
+            r#"{"type": "record", "name": "org.example.Card", "fields": [
+                {"name": "suit", "type": "org.example.Suit"},
+                {"name": "number", "type": "int"}
+            ]}"#,
+            r#"{"type": "record", "name": "org.example.Invalid", "fields": [
+                {"name": "message", "type": "string"}
+            ]}"#,
+        ])
+        .unwrap();
+
+        assert_eq!(protocol.name.fullname(None), "org.example.Cards");
+        assert_eq!(protocol.doc.as_deref(), Some("Cards and their suits."));
+        assert_eq!(protocol.attributes["version"], 2);
+        assert_eq!(protocol.types, types);
+        assert_eq!(protocol.find_type("org.example.Card"), Some(&types[1]));
+
+        let draw = protocol.message("draw").unwrap();
+        assert_eq!(draw.doc.as_deref(), Some("Draw a card."));
+        assert_eq!(draw.request.len(), 1);
+        assert_eq!(draw.request[0].schema, types[0]);
+        assert_eq!(
+            draw.request[0].default_value,
+            Some(crate::types::Value::Enum(1, "HEARTS".to_string()))
+        );
+        assert_eq!(draw.response, types[1]);
+        assert_eq!(draw.errors, vec![types[2].clone()]);
+        assert!(!draw.one_way);
+        assert_eq!(draw.attributes["idempotent"], false);
+        let shuffle = protocol.message("shuffle").unwrap();
+        assert!(shuffle.one_way);
+        assert_eq!(shuffle.response, Schema::Null);
+    }
+
+    #[test]
+    fn test_serialize_protocol() {
+        let protocol = Protocol::parse_str(PROTOCOL).unwrap();
+        let json = serde_json::to_string(&protocol).unwrap();
+        assert_eq!(Protocol::parse_str(&json).unwrap(), protocol);
+
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["types"][2]["type"], "error");
+        assert_eq!(
+            value["types"][1]["fields"][0]["type"], "org.example.Suit",
+            "The types already defined are referred to by fullname"
+        );
+        assert_eq!(value["messages"]["draw"]["response"], "org.example.Card");
+        assert_eq!(
+            value["messages"]["draw"]["errors"][0],
+            "org.example.Invalid"
+        );
+        assert_eq!(protocol.md5()[..], Md5::digest(json.as_bytes())[..]);
+    }
+
+    #[test]
+    fn test_invalid_protocols() {
+        assert!(matches!(
+            Protocol::parse_str(r#"{"namespace": "org.example"}"#),
+            Err(Error::GetProtocolNameField)
+        ));
+        assert!(matches!(
+            Protocol::parse_str(
+                r#"{"protocol": "P", "messages": {"m": {"request": [], "response": "int", "one-way": true}}}"#
+            ),
+            Err(Error::OneWayMessage(ref name)) if name == "m"
+        ));
+        assert!(matches!(
+            Protocol::parse_str(
+                r#"{"protocol": "P", "types": [{"type": "fixed", "name": "F", "size": 1}],
+                    "messages": {"m": {"request": [], "response": "null", "errors": ["F"]}}}"#
+            ),
+            Err(Error::GetMessageError { ref error, .. }) if error == "F"
+        ));
+        assert!(matches!(
+            Protocol::parse_str(r#"{"protocol": "P", "messages": {"m": {"response": "null"}}}"#),
+            Err(Error::GetMessageField(_, "request"))
+        ));
+    }
+}
@@ -1,7 +1,13 @@
 //! Logic handling reading from Avro format at user level.
-use crate::{decode::decode, schema::Schema, types::Value, util, AvroResult, Codec, Error};
+use crate::{
+    decode::{decode, decode_internal},
+    schema::{has_custom_logical_types, named_definitions, Names, ParsingMode, Schema},
+    types::{ResolutionMode, Value},
+    util, AvroResult, Codec, Error,
+};
 use serde_json::from_slice;
 use std::{
+    collections::HashMap,
     io::{ErrorKind, Read},
     str::FromStr,
 };
@@ -18,6 +24,8 @@ struct Block<R> {
     marker: [u8; 16],
     codec: Codec,
     writer_schema: Schema,
+    // The named types of the writer schema, looked up once for all the values read.
+    writer_definitions: HashMap<String, Schema>,
 }
 
 impl<R: Read> Block<R> {
@@ -26,6 +34,7 @@ impl<R: Read> Block<R> {
             reader,
             codec: Codec::Null,
             writer_schema: Schema::Null,
+            writer_definitions: HashMap::new(),
             buf: vec![],
             buf_idx: 0,
             message_count: 0,
@@ -62,7 +71,9 @@ impl<R: Read> Block<R> {
                     }
                 })
                 .ok_or(Error::GetAvroSchemaFromMap)?;
-            self.writer_schema = Schema::parse(&json)?;
+            // The schema of legacy data may not be valid per the specification.
+            self.writer_schema = Schema::parse_with_mode(&json, ParsingMode::Lenient)?;
+            self.writer_definitions = named_definitions(&self.writer_schema);
 
             if let Some(codec) = meta
                 .get("avro.codec")
@@ -152,7 +163,12 @@ impl<R: Read> Block<R> {
         self.len() == 0
     }
 
-    fn read_next(&mut self, read_schema: Option<&Schema>) -> AvroResult<Option<Value>> {
+    fn read_next<'s>(
+        &mut self,
+        read_schema: Option<&'s Schema>,
+        reader_names: &mut Names<'s>,
+        mode: ResolutionMode,
+    ) -> AvroResult<Option<Value>> {
         if self.is_empty() {
             self.read_block_next()?;
             if self.is_empty() {
@@ -162,7 +178,15 @@ impl<R: Read> Block<R> {
 
         let mut block_bytes = &self.buf[self.buf_idx..];
         let b_original = block_bytes.len();
-        let item = from_avro_datum(&self.writer_schema, &mut block_bytes, read_schema)?;
+        let item = decode_internal(
+            &self.writer_schema,
+            &mut Names::with_definitions(&self.writer_definitions),
+            &mut block_bytes,
+        )?;
+        let item = match read_schema {
+            Some(schema) => item.resolve_with_names(schema, reader_names, mode)?,
+            None => item,
+        };
         self.buf_idx += b_original - block_bytes.len();
         self.message_count -= 1;
         Ok(Some(item))
@@ -187,8 +211,11 @@ impl<R: Read> Block<R> {
 pub struct Reader<'a, R> {
     block: Block<R>,
     reader_schema: Option<&'a Schema>,
+    // The named types of the reader schema, looked up once for all the values read.
+    reader_names: Names<'a>,
     errored: bool,
     should_resolve_schema: bool,
+    resolution_mode: ResolutionMode,
 }
 
 impl<'a, R: Read> Reader<'a, R> {
@@ -201,8 +228,10 @@ impl<'a, R: Read> Reader<'a, R> {
         let reader = Reader {
             block,
             reader_schema: None,
+            reader_names: Names::empty(),
             errored: false,
             should_resolve_schema: false,
+            resolution_mode: ResolutionMode::default(),
         };
         Ok(reader)
     }
@@ -216,11 +245,15 @@ impl<'a, R: Read> Reader<'a, R> {
         let mut reader = Reader {
             block,
             reader_schema: Some(schema),
+            reader_names: Names::new(schema),
             errored: false,
             should_resolve_schema: false,
+            resolution_mode: ResolutionMode::default(),
         };
-        // Check if the reader and writer schemas disagree.
-        reader.should_resolve_schema = reader.writer_schema() != schema;
+        // Check if the reader and writer schemas disagree, or if the values are to be converted
+        // to the custom logical types of the reader's schema.
+        reader.should_resolve_schema =
+            reader.writer_schema() != schema || has_custom_logical_types(schema);
         Ok(reader)
     }
 
@@ -234,6 +267,12 @@ impl<'a, R: Read> Reader<'a, R> {
         self.reader_schema
     }
 
+    /// Set how lenient schema resolution is when reading values with a reader `Schema`.
+    /// Defaults to `ResolutionMode::Strict`.
+    pub fn set_resolution_mode(&mut self, mode: ResolutionMode) {
+        self.resolution_mode = mode;
+    }
+
     #[inline]
     fn read_next(&mut self) -> AvroResult<Option<Value>> {
         let read_schema = if self.should_resolve_schema {
@@ -242,7 +281,8 @@ impl<'a, R: Read> Reader<'a, R> {
             None
         };
 
-        self.block.read_next(read_schema)
+        self.block
+            .read_next(read_schema, &mut self.reader_names, self.resolution_mode)
     }
 }
 
@@ -267,7 +307,8 @@ impl<'a, R: Read> Iterator for Reader<'a, R> {
 /// Decode a `Value` encoded in Avro format given its `Schema` and anything implementing `io::Read`
 /// to read from.
 ///
-/// In case a reader `Schema` is provided, schema resolution will also be performed.
+/// In case a reader `Schema` is provided, schema resolution will also be performed, allowing
+/// only the promotions of the specification (see `Value::resolve_with_mode` otherwise).
 ///
 /// **NOTE** This function has a quite small niche of usage and does NOT take care of reading the
 /// header and consecutive data blocks; use [`Reader`](struct.Reader.html) if you don't know what
@@ -287,7 +328,10 @@ pub fn from_avro_datum<R: Read>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{types::Record, Reader};
+    use crate::{
+        types::{Record, UnionBranch},
+        Reader,
+    };
     use std::io::Cursor;
 
     const SCHEMA: &str = r#"
@@ -348,7 +392,13 @@ mod tests {
 
         assert_eq!(
             from_avro_datum(&schema, &mut encoded, None).unwrap(),
-            Value::Union(Box::new(Value::Long(0)))
+            Value::Union(
+                Some(UnionBranch {
+                    index: 1,
+                    name: None
+                }),
+                Box::new(Value::Long(0))
+            )
         );
     }
 
@@ -372,6 +422,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_reader_with_aliases() {
+        let reader_schema = Schema::parse_str(
+            r#"
+            {
+              "type": "record",
+              "name": "renamed",
+              "aliases": ["test"],
+              "fields": [
+                {"name": "x", "type": "long", "aliases": ["a"]},
+                {"name": "y", "type": "string", "aliases": ["b"]}
+              ]
+            }
+            "#,
+        )
+        .unwrap();
+        let reader = Reader::with_schema(&reader_schema, ENCODED).unwrap();
+        let values = reader.collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(
+            values,
+            vec![
+                Value::Record(vec![
+                    ("x".to_string(), Value::Long(27)),
+                    ("y".to_string(), Value::String("foo".to_string())),
+                ]),
+                Value::Record(vec![
+                    ("x".to_string(), Value::Long(42)),
+                    ("y".to_string(), Value::String("bar".to_string())),
+                ]),
+            ]
+        );
+    }
+
     #[test]
     fn test_reader_invalid_header() {
         let schema = Schema::parse_str(SCHEMA).unwrap();
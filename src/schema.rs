@@ -1,24 +1,30 @@
 //! Logic for parsing and interacting with schemas in Avro format.
-use crate::{error::Error, types, util::MapHelper, AvroResult};
+use crate::{
+    error::Error,
+    logical_type::{CustomLogicalType, LogicalTypes},
+    types,
+    util::{format_json_path, locate_json_path, JsonPathSegment, MapHelper},
+    AvroResult,
+};
 use digest::Digest;
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::{
-    ser::{SerializeMap, SerializeSeq},
+    ser::{Error as _, SerializeMap, SerializeSeq},
     Deserialize, Serialize, Serializer,
 };
 use serde_json::{Map, Value};
 use std::{
     borrow::Cow,
-    collections::{HashMap, HashSet},
-    convert::TryInto,
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::{TryFrom, TryInto},
     fmt,
     str::FromStr,
 };
-use strum_macros::{EnumDiscriminants, EnumString};
+use strum_macros::{EnumDiscriminants, EnumString, IntoStaticStr};
 
 lazy_static! {
-    static ref ENUM_SYMBOL_NAME: Regex = Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap();
+    static ref NAME: Regex = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
 }
 
 /// Represents an Avro schema fingerprint
@@ -84,15 +90,25 @@ pub enum Schema {
         doc: Documentation,
         fields: Vec<RecordField>,
         lookup: HashMap<String, usize>,
+        attributes: BTreeMap<String, Value>,
     },
     /// An `enum` Avro schema.
+    ///
+    /// The `default` symbol, if any, is used when reading a symbol unknown to this schema.
     Enum {
         name: Name,
         doc: Documentation,
         symbols: Vec<String>,
+        default: Option<String>,
+        attributes: BTreeMap<String, Value>,
     },
     /// A `fixed` Avro schema.
-    Fixed { name: Name, size: usize },
+    Fixed {
+        name: Name,
+        doc: Documentation,
+        size: usize,
+        attributes: BTreeMap<String, Value>,
+    },
     /// Logical type which represents `Decimal` values. The underlying type is serialized and
     /// deserialized as `Schema::Bytes` or `Schema::Fixed`.
     ///
@@ -103,8 +119,12 @@ pub enum Schema {
         scale: DecimalMetadata,
         inner: Box<Schema>,
     },
-    /// A universally unique identifier, annotating a string.
-    Uuid,
+    /// Logical type which represents `BigDecimal` values, whose scale is serialized along with
+    /// each of them as Avro `bytes`.
+    BigDecimal,
+    /// A universally unique identifier, annotating a `Schema::String` or a `Schema::Fixed` of 16
+    /// bytes.
+    Uuid { inner: Box<Schema> },
     /// Logical type which represents the number of days since the unix epoch.
     /// Serialization format is `Schema::Int`.
     Date,
@@ -118,8 +138,39 @@ pub enum Schema {
     TimestampMillis,
     /// An instant in time represented as the number of microseconds after the UNIX epoch.
     TimestampMicros,
+    /// A timestamp in the local time zone, whichever it is, represented as the number of
+    /// milliseconds after 1970-01-01T00:00:00 local time.
+    LocalTimestampMillis,
+    /// A timestamp in the local time zone, whichever it is, represented as the number of
+    /// microseconds after 1970-01-01T00:00:00 local time.
+    LocalTimestampMicros,
+    /// An instant in time represented as the number of nanoseconds after the UNIX epoch.
+    TimestampNanos,
+    /// A timestamp in the local time zone, whichever it is, represented as the number of
+    /// nanoseconds after 1970-01-01T00:00:00 local time.
+    LocalTimestampNanos,
     /// An amount of time defined by a number of months, days and milliseconds.
     Duration,
+    /// A reference to a named type (`record`, `enum` or `fixed`) defined elsewhere in the same
+    /// schema, by its fullname. This is what allows recursive types such as linked lists.
+    Ref { name: Name },
+    /// A schema annotated with a custom logical type of the `LogicalTypes` it was parsed with,
+    /// whose values are encoded as the values of the `inner` schema.
+    Custom {
+        logical_type: CustomLogicalType,
+        inner: Box<Schema>,
+    },
+    /// A schema which is not a named type, parsed on its own rather than as the type of a record
+    /// field, along with the custom attributes of it and of the schemas it contains which are
+    /// not named types either, keyed by the path to their JSON object within it, e.g. an empty
+    /// path or `[1].items` (see `RecordField::type_attributes`).
+    ///
+    /// Only the root of a schema is attributed, if it has such attributes, and is otherwise
+    /// handled as its `inner` schema.
+    Attributed {
+        inner: Box<Schema>,
+        type_attributes: TypeAttributes,
+    },
 }
 
 impl PartialEq for Schema {
@@ -146,6 +197,24 @@ impl SchemaKind {
                 | SchemaKind::String,
         )
     }
+
+    /// Returns whether a writer's schema of this kind can be promoted to a reader's schema of
+    /// the `reader` kind.
+    ///
+    /// See [Schema Resolution](https://avro.apache.org/docs/current/spec.html#Schema+Resolution)
+    pub fn is_promotable_to(self, reader: SchemaKind) -> bool {
+        matches!(
+            (self, reader),
+            (SchemaKind::Int, SchemaKind::Long)
+                | (SchemaKind::Int, SchemaKind::Float)
+                | (SchemaKind::Int, SchemaKind::Double)
+                | (SchemaKind::Long, SchemaKind::Float)
+                | (SchemaKind::Long, SchemaKind::Double)
+                | (SchemaKind::Float, SchemaKind::Double)
+                | (SchemaKind::String, SchemaKind::Bytes)
+                | (SchemaKind::Bytes, SchemaKind::String)
+        )
+    }
 }
 
 impl<'a> From<&'a types::Value> for SchemaKind {
@@ -162,17 +231,23 @@ impl<'a> From<&'a types::Value> for SchemaKind {
             Value::String(_) => Self::String,
             Value::Array(_) => Self::Array,
             Value::Map(_) => Self::Map,
-            Value::Union(_) => Self::Union,
+            Value::Union(_, _) => Self::Union,
             Value::Record(_) => Self::Record,
             Value::Enum(_, _) => Self::Enum,
             Value::Fixed(_, _) => Self::Fixed,
             Value::Decimal { .. } => Self::Decimal,
+            Value::BigDecimal(_) => Self::BigDecimal,
             Value::Uuid(_) => Self::Uuid,
+            Value::Custom(..) => Self::Custom,
             Value::Date(_) => Self::Date,
             Value::TimeMillis(_) => Self::TimeMillis,
             Value::TimeMicros(_) => Self::TimeMicros,
             Value::TimestampMillis(_) => Self::TimestampMillis,
             Value::TimestampMicros(_) => Self::TimestampMicros,
+            Value::LocalTimestampMillis(_) => Self::LocalTimestampMillis,
+            Value::LocalTimestampMicros(_) => Self::LocalTimestampMicros,
+            Value::TimestampNanos(_) => Self::TimestampNanos,
+            Value::LocalTimestampNanos(_) => Self::LocalTimestampNanos,
             Value::Duration { .. } => Self::Duration,
         }
     }
@@ -198,6 +273,10 @@ pub struct Name {
 /// Represents documentation for complex Avro schemas.
 pub type Documentation = Option<String>;
 
+/// Custom attributes of the schemas of a record field's type which are not named types, keyed by
+/// the path to their JSON object within the field.
+pub type TypeAttributes = BTreeMap<String, BTreeMap<String, Value>>;
+
 impl Name {
     /// Create a new `Name`.
     /// No `namespace` nor `aliases` will be defined.
@@ -210,26 +289,15 @@ impl Name {
     }
 
     /// Parse a `serde_json::Value` into a `Name`.
-    fn parse(complex: &Map<String, Value>) -> AvroResult<Self> {
+    pub(crate) fn parse(complex: &Map<String, Value>) -> AvroResult<Self> {
         let name = complex.name().ok_or(Error::GetNameField)?;
 
         let namespace = complex.string("namespace");
 
-        let aliases: Option<Vec<String>> = complex
-            .get("aliases")
-            .and_then(|aliases| aliases.as_array())
-            .and_then(|aliases| {
-                aliases
-                    .iter()
-                    .map(|alias| alias.as_str())
-                    .map(|alias| alias.map(|a| a.to_string()))
-                    .collect::<Option<_>>()
-            });
-
         Ok(Name {
             name,
             namespace,
-            aliases,
+            aliases: parse_aliases(complex),
         })
     }
 
@@ -253,6 +321,35 @@ impl Name {
             }
         }
     }
+
+    /// Return the fullnames of the `aliases` of this `Name`.
+    ///
+    /// Aliases containing no dots are relative to the namespace of this `Name`.
+    pub fn fullname_aliases(&self) -> Vec<String> {
+        let namespace = namespace_of(&self.fullname(None));
+        self.aliases
+            .iter()
+            .flatten()
+            .map(|alias| match namespace {
+                Some(ref namespace) if !alias.contains('.') => format!("{}.{}", namespace, alias),
+                _ => alias.clone(),
+            })
+            .collect()
+    }
+
+    /// Return whether a named type called `writer_name` can be read as this `Name`, which is the
+    /// case when their fullnames are the same or `writer_name` is one of its aliases.
+    pub(crate) fn matches(&self, writer_name: &Name) -> bool {
+        let writer_fullname = writer_name.fullname(None);
+        self.fullname(None) == writer_fullname || self.fullname_aliases().contains(&writer_fullname)
+    }
+}
+
+/// Return the namespace part of a fullname, if any.
+pub(crate) fn namespace_of(fullname: &str) -> Option<String> {
+    fullname
+        .rfind('.')
+        .map(|position| fullname[..position].to_string())
 }
 
 /// Represents a `field` in a `record` Avro schema.
@@ -266,6 +363,9 @@ pub struct RecordField {
     /// This value will be used when reading Avro datum if schema resolution
     /// is enabled.
     pub default: Option<Value>,
+    /// The `default` parsed into a value of the field's schema, which is done when the schema is
+    /// parsed.
+    pub default_value: Option<types::Value>,
     /// Schema of the field.
     pub schema: Schema,
     /// Order of the field.
@@ -274,10 +374,17 @@ pub struct RecordField {
     pub order: RecordFieldOrder,
     /// Position of the field in the list of `field` of its parent `Schema`
     pub position: usize,
+    /// Aliases of the field.
+    pub aliases: Option<Vec<String>>,
+    /// Custom attributes of the field.
+    pub attributes: BTreeMap<String, Value>,
+    /// Custom attributes of the schemas of the field's type which are not named types, keyed by
+    /// the path to their JSON object within the field, e.g. `type` or `type[1].items`.
+    pub type_attributes: TypeAttributes,
 }
 
 /// Represents any valid order for a `field` in a `record` Avro schema.
-#[derive(Clone, Debug, PartialEq, EnumString)]
+#[derive(Clone, Debug, PartialEq, EnumString, IntoStaticStr)]
 #[strum(serialize_all = "kebab_case")]
 pub enum RecordFieldOrder {
     Ascending,
@@ -290,8 +397,23 @@ impl RecordField {
     fn parse(field: &Map<String, Value>, position: usize, parser: &mut Parser) -> AvroResult<Self> {
         let name = field.name().ok_or(Error::GetNameFieldFromRecord)?;
 
-        // TODO: "type" = "<record name>"
-        let schema = parser.parse_complex(field)?;
+        let enclosing_attributes = parser
+            .type_attributes
+            .replace((parser.path.len(), BTreeMap::new()));
+        let schema = parser.parse_complex_type(field);
+        let type_attributes = std::mem::replace(&mut parser.type_attributes, enclosing_attributes)
+            .map(|(_, type_attributes)| type_attributes)
+            .unwrap_or_default();
+        let schema = schema?;
+
+        // When the type is given by name or annotated with a logical type, the field also holds
+        // the attributes of its type.
+        let inline_type = matches!(field.get("type"), Some(Value::String(_)))
+            || field.contains_key("logicalType");
+        let attributes = custom_attributes(field, |key| {
+            FIELD_ATTRIBUTES.contains(&key)
+                || (inline_type && schema_attributes(&schema).contains(&key))
+        });
 
         let default = field.get("default").cloned();
 
@@ -305,11 +427,98 @@ impl RecordField {
             name,
             doc: field.doc(),
             default,
+            default_value: None,
             schema,
             order,
             position,
+            aliases: parse_aliases(field),
+            attributes,
+            type_attributes,
         })
     }
+
+    /// Returns the custom attribute of this field with the given key, if any.
+    pub fn attribute(&self, key: &str) -> Option<&Value> {
+        self.attributes.get(key)
+    }
+
+    /// Sets a custom attribute of this field, returning its previous value if any.
+    pub fn set_attribute(&mut self, key: &str, value: Value) -> Option<Value> {
+        self.attributes.insert(key.to_owned(), value)
+    }
+
+    /// Removes a custom attribute of this field, returning its value if any.
+    pub fn remove_attribute(&mut self, key: &str) -> Option<Value> {
+        self.attributes.remove(key)
+    }
+}
+
+/// Attributes which are part of the definition of a record field.
+const FIELD_ATTRIBUTES: &[&str] = &["name", "type", "doc", "default", "order", "aliases"];
+
+/// Attributes which are part of the definition of the named types.
+const RECORD_ATTRIBUTES: &[&str] = &["type", "name", "namespace", "doc", "aliases", "fields"];
+const ENUM_ATTRIBUTES: &[&str] = &[
+    "type",
+    "name",
+    "namespace",
+    "doc",
+    "aliases",
+    "symbols",
+    "default",
+];
+const FIXED_ATTRIBUTES: &[&str] = &["type", "name", "namespace", "doc", "aliases", "size"];
+
+/// Return the attributes which are part of the definition of `schema`, when it is given as a JSON
+/// object.
+fn schema_attributes(schema: &Schema) -> &'static [&'static str] {
+    match schema {
+        Schema::Record { .. } => RECORD_ATTRIBUTES,
+        Schema::Enum { .. } => ENUM_ATTRIBUTES,
+        Schema::Fixed { .. } => FIXED_ATTRIBUTES,
+        Schema::Array(_) => &["type", "items"],
+        Schema::Map(_) => &["type", "values"],
+        Schema::Decimal { .. } => &["type", "logicalType", "precision", "scale"],
+        Schema::Uuid { .. }
+        | Schema::Date
+        | Schema::TimeMillis
+        | Schema::TimeMicros
+        | Schema::TimestampMillis
+        | Schema::TimestampMicros
+        | Schema::LocalTimestampMillis
+        | Schema::LocalTimestampMicros
+        | Schema::TimestampNanos
+        | Schema::LocalTimestampNanos
+        | Schema::BigDecimal
+        | Schema::Duration => &["type", "logicalType"],
+        _ => &["type"],
+    }
+}
+
+/// Collect the attributes of a JSON object which are not part of the definition it represents.
+fn custom_attributes<F>(complex: &Map<String, Value>, is_defined: F) -> BTreeMap<String, Value>
+where
+    F: Fn(&str) -> bool,
+{
+    complex
+        .iter()
+        .filter(|(key, _)| !is_defined(key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// Parse the `aliases` of a named type or a record field.
+fn parse_aliases(complex: &Map<String, Value>) -> Option<Vec<String>> {
+    complex
+        .get("aliases")
+        .and_then(|aliases| aliases.as_array())
+        .and_then(|aliases| {
+            aliases
+                .iter()
+                .map(|alias| alias.as_str())
+                .map(|alias| alias.map(|a| a.to_string()))
+                .collect::<Option<_>>()
+        })
 }
 
 #[derive(Debug, Clone)]
@@ -317,26 +526,66 @@ pub struct UnionSchema {
     pub(crate) schemas: Vec<Schema>,
     // Used to ensure uniqueness of schema inputs, and provide constant time finding of the
     // schema index given a value.
-    // **NOTE** that this approach does not work for named types, and will have to be modified
-    // to support that. A simple solution is to also keep a mapping of the names used.
+    // Named types are only indexed by kind when they are the single variant of that kind,
+    // since a value does not carry the name of its type.
     variant_index: HashMap<SchemaKind, usize>,
+    // Used to ensure uniqueness of named variants (and references to them), which may share
+    // the same kind, and to find a variant by its fullname.
+    named_index: HashMap<String, usize>,
 }
 
 impl UnionSchema {
     pub(crate) fn new(schemas: Vec<Schema>) -> AvroResult<Self> {
         let mut vindex = HashMap::new();
+        let mut nindex = HashMap::new();
+        let mut named_kinds = HashMap::new();
+        let mut has_references = false;
         for (i, schema) in schemas.iter().enumerate() {
-            if let Schema::Union(_) = schema {
-                return Err(Error::GetNestedUnion);
+            match schema {
+                Schema::Union(_) => return Err(Error::GetNestedUnion),
+                Schema::Record { name, .. }
+                | Schema::Enum { name, .. }
+                | Schema::Fixed { name, .. } => {
+                    if nindex.insert(name.fullname(None), i).is_some() {
+                        return Err(Error::GetUnionDuplicateName(name.fullname(None)));
+                    }
+                    *named_kinds
+                        .entry(SchemaKind::from(schema))
+                        .or_insert(0usize) += 1;
+                    continue;
+                }
+                // The kind of a referenced schema is only known once it is resolved, so such
+                // variants are always matched through the slow path.
+                Schema::Ref { name } => {
+                    if nindex.insert(name.fullname(None), i).is_some() {
+                        return Err(Error::GetUnionDuplicateName(name.fullname(None)));
+                    }
+                    has_references = true;
+                    continue;
+                }
+                // The values of a custom logical type may be given in their base form, so they
+                // are always matched through the slow path as well.
+                Schema::Custom { .. } => continue,
+                _ => (),
             }
             let kind = SchemaKind::from(schema);
             if vindex.insert(kind, i).is_some() {
                 return Err(Error::GetUnionDuplicate);
             }
         }
+        // A named variant can only be found by kind if no other variant may be of the same kind.
+        if !has_references {
+            for (i, schema) in schemas.iter().enumerate() {
+                let kind = SchemaKind::from(schema);
+                if named_kinds.get(&kind) == Some(&1) {
+                    vindex.insert(kind, i);
+                }
+            }
+        }
         Ok(UnionSchema {
             schemas,
             variant_index: vindex,
+            named_index: nindex,
         })
     }
 
@@ -352,28 +601,481 @@ impl UnionSchema {
 
     /// Optionally returns a reference to the schema matched by this value, as well as its position
     /// within this union.
+    ///
+    /// **NOTE** Variants which are references to named types (`Schema::Ref`) are never matched,
+    /// since the schema they are defined in is not known here; use `find_schema_in` to match them.
     pub fn find_schema(&self, value: &types::Value) -> Option<(usize, &Schema)> {
+        self.find_schema_with_names(value, &mut Names::empty())
+    }
+
+    /// Same as `find_schema`, also matching the variants which are references to the named types
+    /// defined in `root`, the schema this union is part of.
+    pub fn find_schema_in<'s>(
+        &'s self,
+        value: &types::Value,
+        root: &'s Schema,
+    ) -> Option<(usize, &'s Schema)> {
+        self.find_schema_with_names(value, &mut Names::new(root))
+    }
+
+    /// Optionally returns a reference to the named variant (or reference to a named type) with
+    /// the given fullname, as well as its position within this union.
+    pub fn find_schema_by_name(&self, fullname: &str) -> Option<(usize, &Schema)> {
+        self.named_index
+            .get(fullname)
+            .map(|&i| (i, &self.schemas[i]))
+    }
+
+    /// Returns the branch at position `index` of this union, which can be used to build a
+    /// `Value::Union` of that exact variant.
+    pub fn branch(&self, index: usize) -> Option<types::UnionBranch> {
+        self.schemas.get(index).map(|schema| types::UnionBranch {
+            index,
+            name: variant_name(schema),
+        })
+    }
+
+    /// Returns the variant a value of the given `branch` belongs to, if the branch is part of
+    /// this union.
+    pub(crate) fn find_branch(&self, branch: &types::UnionBranch) -> Option<&Schema> {
+        let schema = self.schemas.get(branch.index)?;
+        match branch.name {
+            Some(ref name) if variant_name(schema).as_ref() != Some(name) => None,
+            _ => Some(schema),
+        }
+    }
+
+    /// Returns the variants of this union, as the reader's schema, in the order in which they
+    /// are the best match for a writer's schema of the given `kind` (and `fullname`, for named
+    /// types), as the specification requires:
+    ///
+    /// 1. the variants of the same kind, and the same name or alias for named types,
+    /// 2. the named variants of the same kind, whatever their name,
+    /// 3. the variants the writer's schema can be promoted to,
+    /// 4. any other variant.
+    ///
+    /// Within each group, variants keep their order in the union. The caller picks the first
+    /// variant the writer's schema or value can actually be read as.
+    pub(crate) fn best_matches<'s>(
+        &'s self,
+        kind: SchemaKind,
+        fullname: Option<&str>,
+        names: &mut Names<'s>,
+    ) -> Vec<(usize, &'s Schema)> {
+        self.ranked_matches(kind, fullname, names)
+            .into_iter()
+            .map(|(_, i, variant)| (i, variant))
+            .collect()
+    }
+
+    /// Same as `best_matches`, along with the rank of each variant: 0 to 3 for the groups
+    /// above.
+    pub(crate) fn ranked_matches<'s>(
+        &'s self,
+        kind: SchemaKind,
+        fullname: Option<&str>,
+        names: &mut Names<'s>,
+    ) -> Vec<(u8, usize, &'s Schema)> {
+        let mut ranked = self
+            .schemas
+            .iter()
+            .enumerate()
+            .map(|(i, variant)| {
+                let resolved = match names.resolve(variant).unwrap_or(variant) {
+                    // A custom logical type is read from the values of its base schema.
+                    Schema::Custom { inner, .. } => names.resolve(inner).unwrap_or(inner),
+                    resolved => resolved,
+                };
+                let r_kind = SchemaKind::from(resolved);
+                let rank = if r_kind == kind {
+                    match (resolved, fullname) {
+                        (Schema::Record { name, .. }, Some(fullname))
+                        | (Schema::Enum { name, .. }, Some(fullname))
+                        | (Schema::Fixed { name, .. }, Some(fullname)) => {
+                            if name.matches(&Name::new(fullname)) {
+                                0
+                            } else {
+                                1
+                            }
+                        }
+                        _ => 0,
+                    }
+                } else if kind.is_promotable_to(r_kind) {
+                    2
+                } else {
+                    3
+                };
+                (rank, i, variant)
+            })
+            .collect::<Vec<_>>();
+        // The sort is stable, which keeps the order of the union within each group.
+        ranked.sort_by_key(|&(rank, _, _)| rank);
+        ranked
+    }
+
+    /// Same as `find_schema`, resolving references to named types through `names`.
+    pub(crate) fn find_schema_with_names<'s>(
+        &'s self,
+        value: &types::Value,
+        names: &mut Names<'s>,
+    ) -> Option<(usize, &'s Schema)> {
         let type_index = &SchemaKind::from(value);
         if let Some(&i) = self.variant_index.get(type_index) {
             // fast path
             Some((i, &self.schemas[i]))
         } else {
-            // slow path (required for matching logical types)
+            // slow path (required for matching logical types, named references and named types
+            // sharing the same kind)
             self.schemas
                 .iter()
                 .enumerate()
-                .find(|(_, schema)| value.validate(schema))
+                .find(|(_, schema)| value.validate_with_names(schema, names))
         }
     }
 }
 
-// No need to compare variant_index, it is derivative of schemas.
+/// Return the fullname of a union variant which is a named type, or a reference to one.
+fn variant_name(schema: &Schema) -> Option<String> {
+    match schema {
+        Schema::Record { name, .. }
+        | Schema::Enum { name, .. }
+        | Schema::Fixed { name, .. }
+        | Schema::Ref { name } => Some(name.fullname(None)),
+        Schema::Custom { inner, .. } => variant_name(inner),
+        _ => None,
+    }
+}
+
+// No need to compare variant_index and named_index, they are derivative of schemas.
 impl PartialEq for UnionSchema {
     fn eq(&self, other: &UnionSchema) -> bool {
         self.schemas.eq(&other.schemas)
     }
 }
 
+/// Lookup table of the named types (`record`, `enum` and `fixed`) defined within a schema, used
+/// to resolve `Schema::Ref`s while walking that schema.
+///
+/// The table is only built the first time a reference is looked up, so that schemas without any
+/// reference do not pay for it. To walk many values of the same schema, keep the table around, or
+/// build it from the `named_definitions` of the schema.
+pub(crate) struct Names<'s> {
+    root: Option<&'s Schema>,
+    index: Option<HashMap<String, &'s Schema>>,
+    definitions: Option<&'s HashMap<String, Schema>>,
+}
+
+impl<'s> Names<'s> {
+    /// Create a lookup table for the named types defined in `root`.
+    pub(crate) fn new(root: &'s Schema) -> Self {
+        Names {
+            root: Some(root),
+            index: None,
+            definitions: None,
+        }
+    }
+
+    /// Create a lookup table which does not resolve any name.
+    pub(crate) fn empty() -> Self {
+        Names {
+            root: None,
+            index: None,
+            definitions: None,
+        }
+    }
+
+    /// Create a lookup table of the `named_definitions` of a schema.
+    pub(crate) fn with_definitions(definitions: &'s HashMap<String, Schema>) -> Self {
+        Names {
+            root: None,
+            index: None,
+            definitions: Some(definitions),
+        }
+    }
+
+    /// Return the definition of the named type with the given fullname, if any.
+    pub(crate) fn get(&mut self, fullname: &str) -> Option<&'s Schema> {
+        if let Some(definitions) = self.definitions {
+            return definitions.get(fullname);
+        }
+        let root = self.root;
+        self.index
+            .get_or_insert_with(|| {
+                let mut index = HashMap::new();
+                if let Some(root) = root {
+                    collect_named_schemas(root, &mut index);
+                }
+                index
+            })
+            .get(fullname)
+            .copied()
+    }
+
+    /// Follow `schema` to its definition if it is a `Schema::Ref`, otherwise return it as is.
+    pub(crate) fn resolve(&mut self, schema: &'s Schema) -> AvroResult<&'s Schema> {
+        match schema {
+            Schema::Ref { name } => {
+                let fullname = name.fullname(None);
+                self.get(&fullname).ok_or(Error::GetNamedSchema(fullname))
+            }
+            // The attributes of a schema do not change how its values are handled.
+            Schema::Attributed { inner, .. } => Ok(inner),
+            schema => Ok(schema),
+        }
+    }
+
+    /// Same as `resolve`, also unwrapping the logical type the definition of a fixed may be
+    /// annotated with, for the schemas which annotate a fixed by name.
+    pub(crate) fn resolve_fixed(&mut self, schema: &'s Schema) -> AvroResult<&'s Schema> {
+        match self.resolve(schema)? {
+            Schema::Uuid { inner } | Schema::Custom { inner, .. }
+                if matches!(**inner, Schema::Fixed { .. }) =>
+            {
+                Ok(inner)
+            }
+            schema => Ok(schema),
+        }
+    }
+}
+
+/// Parse the `Name` of a named type defined within the given namespace. A name without an
+/// explicit namespace inherits it, while an empty namespace stands for the null namespace.
+fn parse_name_in(complex: &Map<String, Value>, namespace: Option<&str>) -> AvroResult<Name> {
+    let mut name = Name::parse(complex)?;
+    if !name.name.contains('.') {
+        match name.namespace {
+            Some(ref namespace) if namespace.is_empty() => name.namespace = None,
+            None => name.namespace = namespace.map(String::from),
+            _ => {}
+        }
+    }
+    Ok(name)
+}
+
+/// Push the fullname of every named type defined in the JSON schema `value`, within the given
+/// namespace, into `names`, without parsing it.
+fn collect_json_names(value: &Value, namespace: Option<&str>, names: &mut Vec<String>) {
+    match value {
+        Value::Array(variants) => {
+            for variant in variants {
+                collect_json_names(variant, namespace, names);
+            }
+        }
+        Value::Object(complex) => match complex.get("type") {
+            Some(Value::String(t)) => match t.as_str() {
+                "record" | "error" | "enum" | "fixed" => {
+                    let fullname = match parse_name_in(complex, namespace) {
+                        Ok(name) => name.fullname(None),
+                        Err(_) => return,
+                    };
+                    let fields = complex.get("fields").and_then(Value::as_array);
+                    for field in fields.into_iter().flatten() {
+                        if let Some(ty) = field.get("type") {
+                            collect_json_names(ty, namespace_of(&fullname).as_deref(), names);
+                        }
+                    }
+                    names.push(fullname);
+                }
+                "array" => {
+                    if let Some(items) = complex.get("items") {
+                        collect_json_names(items, namespace, names);
+                    }
+                }
+                "map" => {
+                    if let Some(values) = complex.get("values") {
+                        collect_json_names(values, namespace, names);
+                    }
+                }
+                _ => (),
+            },
+            Some(ty) => collect_json_names(ty, namespace, names),
+            None => (),
+        },
+        _ => (),
+    }
+}
+
+/// Path of the definition of each record parsed by a `Parser`, keyed by fullname, to locate the
+/// defaults of its fields.
+pub(crate) type RecordPaths = HashMap<String, Vec<JsonPathSegment>>;
+
+/// Parse the `default` of every record field of `schema` into its `default_value`. In
+/// `ParsingMode::Strict`, fail with a `SchemaViolation` for every default which is not a valid
+/// value of the schema of its field, located through `record_paths`; otherwise leave the invalid
+/// defaults unparsed, to fail only if they are used.
+pub(crate) fn parse_defaults(
+    mut schema: Schema,
+    mode: ParsingMode,
+    record_paths: &RecordPaths,
+) -> AvroResult<Schema> {
+    let mut defaults = HashMap::new();
+    let mut violations = Vec::new();
+    let collected = collect_defaults(
+        &schema,
+        &mut Names::new(&schema),
+        record_paths,
+        &mut defaults,
+        &mut violations,
+    );
+    if mode == ParsingMode::Strict {
+        collected?;
+        if !violations.is_empty() {
+            return Err(Error::ValidateSchema(violations));
+        }
+    }
+    set_defaults(&mut schema, &mut defaults);
+    Ok(schema)
+}
+
+/// Parse the defaults of the fields of the records defined in `schema`, keyed by the fullname of
+/// the record and the position of the field. The defaults which are not valid values of their
+/// field are reported as `violations` instead.
+fn collect_defaults<'s>(
+    schema: &'s Schema,
+    names: &mut Names<'s>,
+    record_paths: &RecordPaths,
+    defaults: &mut HashMap<(String, usize), types::Value>,
+    violations: &mut Vec<SchemaViolation>,
+) -> AvroResult<()> {
+    match schema {
+        Schema::Record { name, fields, .. } => {
+            let fullname = name.fullname(None);
+            for field in fields {
+                if let Some(ref default) = field.default {
+                    match types::parse_default(&field.name, default, &field.schema, names) {
+                        Ok(value) => {
+                            defaults.insert((fullname.clone(), field.position), value);
+                        }
+                        Err(Error::GetDefaultValue { value, kind, .. }) => {
+                            let mut path = record_paths.get(&fullname).cloned().unwrap_or_default();
+                            path.extend(vec![
+                                "fields".into(),
+                                field.position.into(),
+                                "default".into(),
+                            ]);
+                            violations.push(SchemaViolation {
+                                location: SchemaLocation::new(&path),
+                                reason: ViolationReason::InvalidDefault { value, kind },
+                            });
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+                collect_defaults(&field.schema, names, record_paths, defaults, violations)?;
+            }
+        }
+        Schema::Array(inner) | Schema::Map(inner) => {
+            collect_defaults(inner, names, record_paths, defaults, violations)?
+        }
+        Schema::Union(union) => {
+            for variant in union.variants() {
+                collect_defaults(variant, names, record_paths, defaults, violations)?;
+            }
+        }
+        Schema::Custom { inner, .. } | Schema::Attributed { inner, .. } => {
+            collect_defaults(inner, names, record_paths, defaults, violations)?
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Set the `default_value` of the fields of the records defined in `schema` to the values
+/// parsed by `collect_defaults`.
+fn set_defaults(schema: &mut Schema, defaults: &mut HashMap<(String, usize), types::Value>) {
+    match schema {
+        Schema::Record { name, fields, .. } => {
+            let fullname = name.fullname(None);
+            for field in fields {
+                field.default_value = defaults.remove(&(fullname.clone(), field.position));
+                set_defaults(&mut field.schema, defaults);
+            }
+        }
+        Schema::Array(inner) | Schema::Map(inner) => set_defaults(inner, defaults),
+        Schema::Union(union) => {
+            for variant in union.schemas.iter_mut() {
+                set_defaults(variant, defaults);
+            }
+        }
+        Schema::Custom { inner, .. } | Schema::Attributed { inner, .. } => {
+            set_defaults(inner, defaults)
+        }
+        _ => (),
+    }
+}
+
+/// Return a copy of the definitions of the named types of `schema`, by fullname, which can be kept
+/// along with the schema to resolve its references (see `Names::with_definitions`).
+pub(crate) fn named_definitions(schema: &Schema) -> HashMap<String, Schema> {
+    let mut names = HashMap::new();
+    collect_named_schemas(schema, &mut names);
+    names
+        .into_iter()
+        .map(|(name, schema)| (name, schema.clone()))
+        .collect()
+}
+
+/// Whether `schema` is annotated with a custom logical type anywhere, in which case its values are
+/// read differently than the ones of a schema which is otherwise the same.
+pub(crate) fn has_custom_logical_types(schema: &Schema) -> bool {
+    match schema {
+        Schema::Custom { .. } => true,
+        Schema::Record { fields, .. } => fields
+            .iter()
+            .any(|field| has_custom_logical_types(&field.schema)),
+        Schema::Array(inner) | Schema::Map(inner) => has_custom_logical_types(inner),
+        Schema::Union(union) => union.variants().iter().any(has_custom_logical_types),
+        Schema::Attributed { inner, .. } => has_custom_logical_types(inner),
+        _ => false,
+    }
+}
+
+/// Insert every named type defined in `schema` into `names`, keyed by fullname.
+///
+/// References are not followed, and the first definition of a name wins.
+fn collect_named_schemas<'s>(schema: &'s Schema, names: &mut HashMap<String, &'s Schema>) {
+    match schema {
+        Schema::Record {
+            ref name,
+            ref fields,
+            ..
+        } => {
+            names.entry(name.fullname(None)).or_insert(schema);
+            for field in fields {
+                collect_named_schemas(&field.schema, names);
+            }
+        }
+        Schema::Enum { ref name, .. } | Schema::Fixed { ref name, .. } => {
+            names.entry(name.fullname(None)).or_insert(schema);
+        }
+        Schema::Array(ref inner) | Schema::Map(ref inner) => collect_named_schemas(inner, names),
+        Schema::Union(ref union) => {
+            for variant in union.variants() {
+                collect_named_schemas(variant, names);
+            }
+        }
+        Schema::Decimal { ref inner, .. } => collect_named_schemas(inner, names),
+        // References to the fixed of a uuid are uuids as well.
+        Schema::Uuid { ref inner } => {
+            if let Schema::Fixed { ref name, .. } = **inner {
+                names.entry(name.fullname(None)).or_insert(schema);
+            }
+        }
+        // References to a named type annotated with a custom logical type are annotated as well.
+        Schema::Custom { ref inner, .. } => {
+            if let Schema::Record { ref name, .. }
+            | Schema::Enum { ref name, .. }
+            | Schema::Fixed { ref name, .. } = **inner
+            {
+                names.entry(name.fullname(None)).or_insert(schema);
+            }
+            collect_named_schemas(inner, names)
+        }
+        Schema::Attributed { ref inner, .. } => collect_named_schemas(inner, names),
+        _ => (),
+    }
+}
+
 type DecimalMetadata = usize;
 pub(crate) type Precision = DecimalMetadata;
 pub(crate) type Scale = DecimalMetadata;
@@ -396,116 +1098,788 @@ fn parse_json_integer_for_decimal(value: &serde_json::Number) -> Result<DecimalM
     })
 }
 
-#[derive(Default)]
-struct Parser {
-    input_schemas: HashMap<String, Value>,
-    input_order: Vec<String>,
-    parsed_schemas: HashMap<String, Schema>,
+/// How strictly a schema is validated when it is parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParsingMode {
+    /// The schema must be valid per the specification: names, namespaces and enum symbols
+    /// match `[A-Za-z_][A-Za-z0-9_]*`, fixed sizes are not negative, record field names and enum
+    /// symbols are unique, decimals have a scale no greater than their precision, and defaults
+    /// are valid values of their fields. Every violation is reported at once.
+    Strict,
+    /// Only what is needed to read and write data is checked, e.g. to read legacy data written
+    /// with an invalid schema. Invalid defaults are only reported if they are used.
+    Lenient,
 }
 
-impl Schema {
-    /// Converts `self` into its [Parsing Canonical Form].
-    ///
-    /// [Parsing Canonical Form]:
-    /// https://avro.apache.org/docs/1.8.2/spec.html#Parsing+Canonical+Form+for+Schemas
-    pub fn canonical_form(&self) -> String {
-        let json = serde_json::to_value(self)
-            .unwrap_or_else(|e| panic!("cannot parse Schema from JSON: {0}", e));
-        parsing_canonical_form(&json)
+// Schemas are parsed strictly unless asked otherwise; like `ResolutionMode`, this impl is
+// written by hand for the minimum supported Rust version.
+#[allow(clippy::derivable_impls)]
+impl Default for ParsingMode {
+    fn default() -> Self {
+        ParsingMode::Strict
     }
+}
 
-    /// Generate [fingerprint] of Schema's [Parsing Canonical Form].
-    ///
-    /// [Parsing Canonical Form]:
-    /// https://avro.apache.org/docs/1.8.2/spec.html#Parsing+Canonical+Form+for+Schemas
-    /// [fingerprint]:
-    /// https://avro.apache.org/docs/current/spec.html#schema_fingerprints
-    pub fn fingerprint<D: Digest>(&self) -> SchemaFingerprint {
-        let mut d = D::new();
-        d.update(self.canonical_form());
-        SchemaFingerprint {
-            bytes: d.finalize().to_vec(),
+/// Location of an error within a JSON schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaLocation {
+    /// Path to the offending value within the JSON schema, e.g. `fields[12].type[1].items`. The
+    /// path of the root of the schema is empty.
+    pub path: String,
+    /// Line of the offending value, starting at 1, when the schema is parsed from a string.
+    pub line: Option<usize>,
+    /// Column of the offending value, starting at 1, when the schema is parsed from a string.
+    pub column: Option<usize>,
+    segments: Vec<JsonPathSegment>,
+}
+
+impl SchemaLocation {
+    fn new(segments: &[JsonPathSegment]) -> Self {
+        SchemaLocation {
+            path: format_json_path(segments),
+            line: None,
+            column: None,
+            segments: segments.to_vec(),
         }
     }
 
-    /// Create a `Schema` from a string representing a JSON Avro schema.
-    pub fn parse_str(input: &str) -> Result<Schema, Error> {
-        let mut parser = Parser::default();
-        parser.parse_str(input)
+    /// Find the line and column of this location within the JSON schema `input`.
+    fn locate(&mut self, input: &str) {
+        if let Some((line, column)) = locate_json_path(input, &self.segments) {
+            self.line = Some(line);
+            self.column = Some(column);
+        }
     }
+}
 
-    /// Create a array of `Schema`'s from a list of named JSON Avro schemas (Record, Enum, and
-    /// Fixed).
-    ///
-    /// It is allowed that the schemas have cross-dependencies; these will be resolved
-    /// during parsing.
-    ///
-    /// If two of the input schemas have the same fullname, an Error will be returned.
-    pub fn parse_list(input: &[&str]) -> Result<Vec<Schema>, Error> {
-        let mut input_schemas: HashMap<String, Value> = HashMap::with_capacity(input.len());
-        let mut input_order: Vec<String> = Vec::with_capacity(input.len());
-        for js in input {
-            let schema: Value = serde_json::from_str(js).map_err(Error::ParseSchemaJson)?;
-            if let Value::Object(inner) = &schema {
-                let fullname = Name::parse(&inner)?.fullname(None);
-                let previous_value = input_schemas.insert(fullname.clone(), schema);
-                if previous_value.is_some() {
-                    return Err(Error::NameCollision(fullname));
-                }
-                input_order.push(fullname);
-            } else {
-                return Err(Error::GetNameField);
-            }
+impl fmt::Display for SchemaLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "the root of the schema")?;
+        } else {
+            write!(f, "{}", self.path)?;
         }
-        let mut parser = Parser {
-            input_schemas,
-            input_order,
-            parsed_schemas: HashMap::with_capacity(input.len()),
-        };
-        parser.parse_list()
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, " (line {}, column {})", line, column)?;
+        }
+        Ok(())
     }
+}
 
-    pub fn parse(value: &Value) -> AvroResult<Schema> {
-        let mut parser = Parser::default();
-        parser.parse(value)
+/// A violation of the specification found when parsing a schema in `ParsingMode::Strict`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// Location of the violation within the JSON schema.
+    pub location: SchemaLocation,
+    /// What is wrong at that location.
+    pub reason: ViolationReason,
+}
+
+/// The violations of the specification found by `ParsingMode::Strict`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ViolationReason {
+    /// A name, or one of the parts of a fullname, does not match `[A-Za-z_][A-Za-z0-9_]*`.
+    InvalidName(String),
+    /// A part of a namespace does not match `[A-Za-z_][A-Za-z0-9_]*`.
+    InvalidNamespace(String),
+    /// An enum symbol does not match `[A-Za-z_][A-Za-z0-9_]*`.
+    InvalidEnumSymbol(String),
+    /// An enum symbol is listed more than once.
+    DuplicateEnumSymbol(String),
+    /// Two fields of a record have the same name.
+    DuplicateFieldName(String),
+    /// The size of a fixed is negative.
+    NegativeFixedSize(i64),
+    /// The scale of a decimal is greater than its precision.
+    DecimalScaleGreaterThanPrecision { scale: u64, precision: u64 },
+    /// The default of a record field is not a valid value of a schema of the given kind.
+    InvalidDefault { value: Value, kind: SchemaKind },
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.reason)
     }
 }
 
-impl Parser {
-    /// Create a `Schema` from a string representing a JSON Avro schema.
-    fn parse_str(&mut self, input: &str) -> Result<Schema, Error> {
-        // TODO: (#82) this should be a ParseSchemaError wrapping the JSON error
-        let value = serde_json::from_str(input).map_err(Error::ParseSchemaJson)?;
-        self.parse(&value)
+impl fmt::Display for ViolationReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ViolationReason::InvalidName(name) => write!(f, "invalid name {:?}", name),
+            ViolationReason::InvalidNamespace(namespace) => {
+                write!(f, "invalid namespace {:?}", namespace)
+            }
+            ViolationReason::InvalidEnumSymbol(symbol) => {
+                write!(f, "invalid enum symbol {:?}", symbol)
+            }
+            ViolationReason::DuplicateEnumSymbol(symbol) => {
+                write!(f, "duplicate enum symbol {:?}", symbol)
+            }
+            ViolationReason::DuplicateFieldName(name) => {
+                write!(f, "duplicate field name {:?}", name)
+            }
+            ViolationReason::NegativeFixedSize(size) => write!(f, "negative fixed size {}", size),
+            ViolationReason::DecimalScaleGreaterThanPrecision { scale, precision } => write!(
+                f,
+                "decimal scale {} is greater than its precision {}",
+                scale, precision
+            ),
+            ViolationReason::InvalidDefault { value, kind } => {
+                write!(
+                    f,
+                    "invalid default {} for a schema of kind {:?}",
+                    value, kind
+                )
+            }
+        }
     }
+}
 
-    /// Create an array of `Schema`'s from an iterator of JSON Avro schemas. It is allowed that
-    /// the schemas have cross-dependencies; these will be resolved during parsing.
-    fn parse_list(&mut self) -> Result<Vec<Schema>, Error> {
-        while !self.input_schemas.is_empty() {
-            let next_name = self
-                .input_schemas
-                .keys()
-                .next()
-                .expect("Input schemas unexpectedly empty")
-                .to_owned();
-            let (name, value) = self
+/// Check a JSON schema for violations of the specification, before it is parsed in
+/// `ParsingMode::Strict`.
+pub(crate) fn validate(value: &Value) -> AvroResult<()> {
+    let mut validator = Validator::default();
+    validator.validate(value);
+    validator.finish()
+}
+
+/// Fail with the violations found by both the validation of a JSON schema and its parsing, if
+/// any. Otherwise, the validation fails first, since an invalid schema may not parse.
+fn with_violations<T>(validated: AvroResult<()>, parsed: AvroResult<T>) -> AvroResult<T> {
+    match (validated, parsed) {
+        (Err(Error::ValidateSchema(mut violations)), Err(Error::ValidateSchema(more))) => {
+            violations.extend(more);
+            Err(Error::ValidateSchema(violations))
+        }
+        (Err(error), _) => Err(error),
+        (Ok(()), parsed) => parsed,
+    }
+}
+
+/// Attach the line and column of its location within the JSON schema `input` to an error raised
+/// when parsing it.
+pub(crate) fn locate_error(mut error: Error, input: &str) -> Error {
+    match error {
+        Error::ParseSchemaAt {
+            ref mut location, ..
+        } => location.locate(input),
+        Error::ValidateSchema(ref mut violations) => {
+            for violation in violations {
+                violation.location.locate(input);
+            }
+        }
+        _ => (),
+    }
+    error
+}
+
+/// Collects the `SchemaViolation`s of JSON schemas, before they are parsed.
+#[derive(Default)]
+struct Validator {
+    path: Vec<JsonPathSegment>,
+    violations: Vec<SchemaViolation>,
+}
+
+impl Validator {
+    fn validate(&mut self, value: &Value) {
+        match value {
+            Value::Array(items) => {
+                for (position, item) in items.iter().enumerate() {
+                    self.at(position, |v| v.validate(item));
+                }
+            }
+            Value::Object(complex) => self.validate_complex(complex),
+            _ => (),
+        }
+    }
+
+    fn validate_complex(&mut self, complex: &Map<String, Value>) {
+        if complex.get("logicalType").and_then(Value::as_str) == Some("decimal") {
+            let precision = complex.get("precision").and_then(Value::as_u64);
+            let scale = complex.get("scale").and_then(Value::as_u64);
+            if let (Some(precision), Some(scale)) = (precision, scale) {
+                if scale > precision {
+                    self.at("scale", |v| {
+                        v.report(ViolationReason::DecimalScaleGreaterThanPrecision {
+                            scale,
+                            precision,
+                        })
+                    });
+                }
+            }
+        }
+
+        match complex.get("type") {
+            Some(Value::String(t)) => match t.as_str() {
+                "record" | "error" => {
+                    self.validate_name(complex);
+                    if let Some(fields) = complex.get("fields").and_then(Value::as_array) {
+                        self.at("fields", |v| v.validate_fields(fields));
+                    }
+                }
+                "enum" => {
+                    self.validate_name(complex);
+                    if let Some(symbols) = complex.get("symbols").and_then(Value::as_array) {
+                        let mut seen = HashSet::new();
+                        for (position, symbol) in symbols.iter().enumerate() {
+                            if let Some(symbol) = symbol.as_str() {
+                                self.at("symbols", |v| {
+                                    v.at(position, |v| {
+                                        if !NAME.is_match(symbol) {
+                                            v.report(ViolationReason::InvalidEnumSymbol(
+                                                symbol.to_string(),
+                                            ));
+                                        }
+                                        if !seen.insert(symbol) {
+                                            v.report(ViolationReason::DuplicateEnumSymbol(
+                                                symbol.to_string(),
+                                            ));
+                                        }
+                                    })
+                                });
+                            }
+                        }
+                    }
+                }
+                "fixed" => {
+                    self.validate_name(complex);
+                    if let Some(size) = complex.get("size").and_then(Value::as_i64) {
+                        if size < 0 {
+                            self.at("size", |v| {
+                                v.report(ViolationReason::NegativeFixedSize(size))
+                            });
+                        }
+                    }
+                }
+                "array" => {
+                    if let Some(items) = complex.get("items") {
+                        self.at("items", |v| v.validate(items));
+                    }
+                }
+                "map" => {
+                    if let Some(values) = complex.get("values") {
+                        self.at("values", |v| v.validate(values));
+                    }
+                }
+                _ => (),
+            },
+            Some(inner) => self.at("type", |v| v.validate(inner)),
+            None => (),
+        }
+    }
+
+    fn validate_fields(&mut self, fields: &[Value]) {
+        let mut seen = HashSet::new();
+        for (position, field) in fields.iter().enumerate() {
+            if let Some(field) = field.as_object() {
+                self.at(position, |v| {
+                    if let Some(name) = field.get("name").and_then(Value::as_str) {
+                        v.at("name", |v| {
+                            if !NAME.is_match(name) {
+                                v.report(ViolationReason::InvalidName(name.to_string()));
+                            }
+                            if !seen.insert(name) {
+                                v.report(ViolationReason::DuplicateFieldName(name.to_string()));
+                            }
+                        });
+                    }
+                    v.validate_aliases(field);
+                    if let Some(schema) = field.get("type") {
+                        v.at("type", |v| v.validate(schema));
+                    }
+                });
+            }
+        }
+    }
+
+    /// Validate the name, namespace and aliases of a named type.
+    fn validate_name(&mut self, complex: &Map<String, Value>) {
+        if let Some(name) = complex.get("name").and_then(Value::as_str) {
+            if !name.split('.').all(|part| NAME.is_match(part)) {
+                self.at("name", |v| {
+                    v.report(ViolationReason::InvalidName(name.to_string()))
+                });
+            }
+        }
+        if let Some(namespace) = complex.get("namespace").and_then(Value::as_str) {
+            // An empty namespace stands for the null namespace.
+            if !namespace.is_empty() && !namespace.split('.').all(|part| NAME.is_match(part)) {
+                self.at("namespace", |v| {
+                    v.report(ViolationReason::InvalidNamespace(namespace.to_string()))
+                });
+            }
+        }
+        self.validate_aliases(complex);
+    }
+
+    fn validate_aliases(&mut self, complex: &Map<String, Value>) {
+        if let Some(aliases) = complex.get("aliases").and_then(Value::as_array) {
+            for (position, alias) in aliases.iter().enumerate() {
+                if let Some(alias) = alias.as_str() {
+                    if !alias.split('.').all(|part| NAME.is_match(part)) {
+                        self.at("aliases", |v| {
+                            v.at(position, |v| {
+                                v.report(ViolationReason::InvalidName(alias.to_string()))
+                            })
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Run `f` with `segment` appended to the current path.
+    fn at<T: Into<JsonPathSegment>>(&mut self, segment: T, f: impl FnOnce(&mut Self)) {
+        self.path.push(segment.into());
+        f(self);
+        self.path.pop();
+    }
+
+    fn report(&mut self, reason: ViolationReason) {
+        self.violations.push(SchemaViolation {
+            location: SchemaLocation::new(&self.path),
+            reason,
+        });
+    }
+
+    /// Fail with every violation found, if any.
+    fn finish(self) -> AvroResult<()> {
+        if self.violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::ValidateSchema(self.violations))
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Parser {
+    input_schemas: HashMap<String, Value>,
+    parsed_schemas: HashMap<String, Schema>,
+    // Fullnames of the input schemas being parsed, innermost last.
+    pending: Vec<String>,
+    // Fullname of the input schema which failed to parse, if any.
+    failed_input: Option<String>,
+    // Fullnames of the named types already defined (or being defined) in the schema currently
+    // being parsed. Any further reference to one of them is parsed as a `Schema::Ref`.
+    defined_names: HashSet<String>,
+    // Definitions of the fixed types among them, which logical types may annotate by name.
+    defined_fixeds: HashMap<String, Schema>,
+    // Namespace of the most tightly enclosing named type, inherited by the named types defined
+    // within it and used to qualify the names they refer to.
+    namespace: Option<String>,
+    // Path to the JSON value currently being parsed, to locate errors.
+    path: Vec<JsonPathSegment>,
+    // Custom logical types the schemas annotated with are parsed into a `Schema::Custom`.
+    logical_types: LogicalTypes,
+    // Custom attributes of the schemas which are not named types, collected while parsing the
+    // type of a record field, along with the length of `path` at that field.
+    type_attributes: Option<(usize, TypeAttributes)>,
+    // Path of each input schema within the JSON document it is part of, keyed by fullname. The
+    // paths of the other input schemas start at their root.
+    input_paths: HashMap<String, Vec<JsonPathSegment>>,
+    // Path of the definition of each record parsed.
+    record_paths: RecordPaths,
+    // Fullname of the input or parsed schema within which each of the named types nested in them
+    // is defined, for the other schemas to refer to them.
+    nested_names: HashMap<String, String>,
+}
+
+impl Schema {
+    /// Converts `self` into its [Parsing Canonical Form].
+    ///
+    /// [Parsing Canonical Form]:
+    /// https://avro.apache.org/docs/1.8.2/spec.html#Parsing+Canonical+Form+for+Schemas
+    pub fn canonical_form(&self) -> String {
+        let json = serde_json::to_value(self)
+            .unwrap_or_else(|e| panic!("cannot parse Schema from JSON: {0}", e));
+        parsing_canonical_form(&json, None, &mut HashSet::new())
+    }
+
+    /// Generate [fingerprint] of Schema's [Parsing Canonical Form].
+    ///
+    /// [Parsing Canonical Form]:
+    /// https://avro.apache.org/docs/1.8.2/spec.html#Parsing+Canonical+Form+for+Schemas
+    /// [fingerprint]:
+    /// https://avro.apache.org/docs/current/spec.html#schema_fingerprints
+    pub fn fingerprint<D: Digest>(&self) -> SchemaFingerprint {
+        let mut d = D::new();
+        d.update(self.canonical_form());
+        SchemaFingerprint {
+            bytes: d.finalize().to_vec(),
+        }
+    }
+
+    /// Returns the custom attributes of this schema, i.e. the properties of its JSON
+    /// representation which are not part of its definition, if it is a named type.
+    ///
+    /// The custom attributes of the other schemas are kept by the record field they are part of,
+    /// in its `type_attributes`, or else by the `Schema::Attributed` root of the schema.
+    pub fn attributes(&self) -> Option<&BTreeMap<String, Value>> {
+        match self {
+            Schema::Record { attributes, .. }
+            | Schema::Enum { attributes, .. }
+            | Schema::Fixed { attributes, .. } => Some(attributes),
+            Schema::Custom { inner, .. } => inner.attributes(),
+            Schema::Attributed {
+                type_attributes, ..
+            } => type_attributes.get(""),
+            _ => None,
+        }
+    }
+
+    /// Returns the custom attribute of this schema with the given key, if any.
+    pub fn attribute(&self, key: &str) -> Option<&Value> {
+        self.attributes().and_then(|attributes| attributes.get(key))
+    }
+
+    /// Sets a custom attribute of this schema, returning its previous value if any.
+    ///
+    /// Fails unless this schema is a named type or a `Schema::Attributed`, the only schemas
+    /// which hold their custom attributes.
+    pub fn set_attribute(&mut self, key: &str, value: Value) -> AvroResult<Option<Value>> {
+        match self {
+            Schema::Record { attributes, .. }
+            | Schema::Enum { attributes, .. }
+            | Schema::Fixed { attributes, .. } => Ok(attributes.insert(key.to_owned(), value)),
+            Schema::Custom { inner, .. } => inner.set_attribute(key, value),
+            Schema::Attributed {
+                type_attributes, ..
+            } => Ok(type_attributes
+                .entry(String::new())
+                .or_default()
+                .insert(key.to_owned(), value)),
+            schema => Err(Error::SetAttribute(SchemaKind::from(&*schema))),
+        }
+    }
+
+    /// Removes a custom attribute of this schema, returning its value if any.
+    pub fn remove_attribute(&mut self, key: &str) -> Option<Value> {
+        match self {
+            Schema::Record { attributes, .. }
+            | Schema::Enum { attributes, .. }
+            | Schema::Fixed { attributes, .. } => attributes.remove(key),
+            Schema::Custom { inner, .. } => inner.remove_attribute(key),
+            Schema::Attributed {
+                type_attributes, ..
+            } => type_attributes
+                .get_mut("")
+                .and_then(|attributes| attributes.remove(key)),
+            _ => None,
+        }
+    }
+
+    /// Create a `Schema` from a string representing a JSON Avro schema, validated in
+    /// `ParsingMode::Strict`.
+    pub fn parse_str(input: &str) -> Result<Schema, Error> {
+        Self::parse_str_with_mode(input, ParsingMode::Strict)
+    }
+
+    /// Create a `Schema` from a string representing a JSON Avro schema, validated in the given
+    /// `ParsingMode`.
+    pub fn parse_str_with_mode(input: &str, mode: ParsingMode) -> Result<Schema, Error> {
+        Self::parse_str_with_logical_types(input, mode, &LogicalTypes::default())
+    }
+
+    /// Create a `Schema` from a string representing a JSON Avro schema, validated in the given
+    /// `ParsingMode`, whose schemas annotated with one of the custom `logical_types` are parsed
+    /// into a `Schema::Custom`.
+    pub fn parse_str_with_logical_types(
+        input: &str,
+        mode: ParsingMode,
+        logical_types: &LogicalTypes,
+    ) -> Result<Schema, Error> {
+        // TODO: (#82) this should be a ParseSchemaError wrapping the JSON error
+        let value = serde_json::from_str(input).map_err(Error::ParseSchemaJson)?;
+        Self::parse_with_logical_types(&value, mode, logical_types)
+            .map_err(|error| locate_error(error, input))
+    }
+
+    /// Create a array of `Schema`'s from a list of named JSON Avro schemas (Record, Enum, and
+    /// Fixed).
+    ///
+    /// It is allowed that the schemas have cross-dependencies, including on the named types
+    /// nested within each other; these will be resolved during parsing.
+    ///
+    /// If two of the input schemas have the same fullname, an Error will be returned.
+    ///
+    /// The schemas are validated in `ParsingMode::Strict`.
+    pub fn parse_list(input: &[&str]) -> Result<Vec<Schema>, Error> {
+        Self::parse_list_with_mode(input, ParsingMode::Strict)
+    }
+
+    /// Create a array of `Schema`'s from a list of named JSON Avro schemas, validated in the
+    /// given `ParsingMode`. The paths of `SchemaViolation`s start with the position of their
+    /// schema in the list.
+    pub fn parse_list_with_mode(input: &[&str], mode: ParsingMode) -> Result<Vec<Schema>, Error> {
+        let mut input_schemas: HashMap<String, Value> = HashMap::with_capacity(input.len());
+        let mut input_order: Vec<String> = Vec::with_capacity(input.len());
+        let mut input_paths = HashMap::with_capacity(input.len());
+        let mut validator = Validator::default();
+        for (position, js) in input.iter().enumerate() {
+            let schema: Value = serde_json::from_str(js).map_err(Error::ParseSchemaJson)?;
+            if mode == ParsingMode::Strict {
+                validator.at(position, |v| v.validate(&schema));
+            }
+            if let Value::Object(inner) = &schema {
+                let fullname = Name::parse(&inner)?.fullname(None);
+                let previous_value = input_schemas.insert(fullname.clone(), schema);
+                if previous_value.is_some() {
+                    return Err(Error::NameCollision(fullname));
+                }
+                input_paths.insert(fullname.clone(), vec![position.into()]);
+                input_order.push(fullname);
+            } else {
+                return Err(Error::GetNameField);
+            }
+        }
+        let validated = validator.finish();
+        let mut parser = Parser::with_inputs(input_schemas, HashMap::new());
+        parser.input_paths = input_paths;
+        if let Err(error) = parser.parse_list() {
+            return with_violations(validated, Err(error));
+        }
+        let (mut parsed_schemas, record_paths) = parser.into_parsed();
+        let mut schemas = Vec::with_capacity(input_order.len());
+        let mut violations: Vec<SchemaViolation> = Vec::new();
+        for name in input_order.iter() {
+            let parsed = parsed_schemas
+                .remove(name)
+                .expect("One of the input schemas was unexpectedly not parsed");
+            match parse_defaults(parsed, mode, &record_paths) {
+                Ok(schema) => schemas.push(schema),
+                // The input schemas which refer to the same record report its defaults once.
+                Err(Error::ValidateSchema(found)) => {
+                    for violation in found {
+                        if !violations.contains(&violation) {
+                            violations.push(violation);
+                        }
+                    }
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        if violations.is_empty() {
+            with_violations(validated, Ok(schemas))
+        } else {
+            with_violations(validated, Err(Error::ValidateSchema(violations)))
+        }
+    }
+
+    /// Create a `Schema` from a `serde_json::Value` representing a JSON Avro schema, validated
+    /// in `ParsingMode::Strict`.
+    pub fn parse(value: &Value) -> AvroResult<Schema> {
+        Self::parse_with_mode(value, ParsingMode::Strict)
+    }
+
+    /// Create a `Schema` from a `serde_json::Value` representing a JSON Avro schema, validated
+    /// in the given `ParsingMode`.
+    pub fn parse_with_mode(value: &Value, mode: ParsingMode) -> AvroResult<Schema> {
+        Self::parse_with_logical_types(value, mode, &LogicalTypes::default())
+    }
+
+    /// Create a `Schema` from a `serde_json::Value` representing a JSON Avro schema, validated
+    /// in the given `ParsingMode`, whose schemas annotated with one of the custom `logical_types`
+    /// are parsed into a `Schema::Custom`.
+    pub fn parse_with_logical_types(
+        value: &Value,
+        mode: ParsingMode,
+        logical_types: &LogicalTypes,
+    ) -> AvroResult<Schema> {
+        let validated = match mode {
+            ParsingMode::Strict => validate(value),
+            ParsingMode::Lenient => Ok(()),
+        };
+        let mut parser = Parser {
+            logical_types: logical_types.clone(),
+            ..Parser::default()
+        };
+        let parsed = parser
+            .parse_root(value)
+            .and_then(|schema| parse_defaults(schema, mode, &parser.record_paths))
+            .map_err(|error| parser.locate(error));
+        with_violations(validated, parsed)
+    }
+}
+
+impl Parser {
+    /// Create a parser of the given named JSON schemas, keyed by fullname, which can refer to each
+    /// other and to the already parsed schemas.
+    pub(crate) fn with_inputs(
+        input_schemas: HashMap<String, Value>,
+        parsed_schemas: HashMap<String, Schema>,
+    ) -> Self {
+        // The schemas themselves are found by their own fullname.
+        let is_nested =
+            |name: &String| !input_schemas.contains_key(name) && !parsed_schemas.contains_key(name);
+        let mut nested_names = HashMap::new();
+        for (fullname, value) in &input_schemas {
+            let mut names = Vec::new();
+            collect_json_names(value, None, &mut names);
+            for name in names.into_iter().filter(is_nested) {
+                nested_names.entry(name).or_insert_with(|| fullname.clone());
+            }
+        }
+        for (fullname, schema) in &parsed_schemas {
+            let mut names = HashMap::new();
+            collect_named_schemas(schema, &mut names);
+            for name in names.keys().filter(|name| is_nested(name)) {
+                nested_names
+                    .entry(name.clone())
+                    .or_insert_with(|| fullname.clone());
+            }
+        }
+        Parser {
+            input_schemas,
+            parsed_schemas,
+            nested_names,
+            ..Parser::default()
+        }
+    }
+
+    /// Return the parsed schemas, keyed by fullname, along with the paths of the records they
+    /// define.
+    pub(crate) fn into_parsed(self) -> (HashMap<String, Schema>, RecordPaths) {
+        (self.parsed_schemas, self.record_paths)
+    }
+
+    /// Return the paths of the records parsed so far.
+    pub(crate) fn record_paths(&self) -> &RecordPaths {
+        &self.record_paths
+    }
+
+    /// Return the fullname of the input schema which failed to parse, if any.
+    pub(crate) fn failed_input(&self) -> Option<&str> {
+        self.failed_input.as_deref()
+    }
+
+    /// Parse every input schema into the parsed schemas. It is allowed that the schemas have
+    /// cross-dependencies; these will be resolved during parsing, unless they are cyclic.
+    pub(crate) fn parse_list(&mut self) -> AvroResult<()> {
+        while !self.input_schemas.is_empty() {
+            let next_name = self
+                .input_schemas
+                .keys()
+                .next()
+                .expect("Input schemas unexpectedly empty")
+                .to_owned();
+            let (name, value) = self
                 .input_schemas
                 .remove_entry(&next_name)
                 .expect("Key unexpectedly missing");
-            let parsed = self.parse(&value)?;
+            let parsed = self.parse_standalone(&name, &value)?;
             self.parsed_schemas.insert(name, parsed);
         }
+        Ok(())
+    }
 
-        let mut parsed_schemas = Vec::with_capacity(self.parsed_schemas.len());
-        for name in self.input_order.drain(0..) {
-            let parsed = self
-                .parsed_schemas
-                .remove(&name)
-                .expect("One of the input schemas was unexpectedly not parsed");
-            parsed_schemas.push(parsed);
+    /// Create a `Schema` from a `serde_json::Value` representing a JSON Avro schema which can
+    /// refer to the parsed schemas, qualifying its references with the given namespace.
+    pub(crate) fn parse_in_namespace(
+        &mut self,
+        value: &Value,
+        namespace: Option<String>,
+    ) -> AvroResult<Schema> {
+        let enclosing_namespace = std::mem::replace(&mut self.namespace, namespace);
+        let parsed = self.parse_root(value).map_err(|error| self.locate(error));
+        self.namespace = enclosing_namespace;
+        parsed
+    }
+
+    /// Parse the JSON fields of a record, such as the parameters of a protocol message, which can
+    /// refer to the parsed schemas, qualifying their references with the given namespace.
+    pub(crate) fn parse_fields_in_namespace(
+        &mut self,
+        fields: &[Value],
+        namespace: Option<String>,
+    ) -> AvroResult<Vec<RecordField>> {
+        let enclosing_namespace = std::mem::replace(&mut self.namespace, namespace);
+        let parsed: AvroResult<Vec<RecordField>> = fields
+            .iter()
+            .enumerate()
+            .map(|(position, field)| {
+                self.at(position, |parser| match field {
+                    Value::Object(field) => RecordField::parse(field, position, parser),
+                    _ => Err(Error::GetRecordFieldsJson),
+                })
+            })
+            .collect();
+        self.namespace = enclosing_namespace;
+        parsed.map_err(|error| self.locate(error))
+    }
+
+    /// Create a `Schema` from a `serde_json::Value` representing a standalone JSON Avro schema,
+    /// i.e. one which cannot refer to the named types defined by the schema currently being
+    /// parsed.
+    fn parse_standalone(&mut self, name: &str, value: &Value) -> AvroResult<Schema> {
+        let enclosing_names = std::mem::take(&mut self.defined_names);
+        let enclosing_fixeds = std::mem::take(&mut self.defined_fixeds);
+        let enclosing_namespace = self.namespace.take();
+        let input_path = self.input_paths.get(name).cloned().unwrap_or_default();
+        let enclosing_path = std::mem::replace(&mut self.path, input_path);
+        let enclosing_attributes = self.type_attributes.take();
+        self.pending.push(name.to_string());
+        let parsed = self.parse(value).map_err(|error| {
+            // The innermost schema which failed is the one to blame.
+            if self.failed_input.is_none() {
+                self.failed_input = Some(name.to_string());
+            }
+            self.locate(error)
+        });
+        self.pending.pop();
+        self.defined_names = enclosing_names;
+        self.defined_fixeds = enclosing_fixeds;
+        self.namespace = enclosing_namespace;
+        self.path = enclosing_path;
+        self.type_attributes = enclosing_attributes;
+        parsed
+    }
+
+    /// Run `f` with `segment` appended to the path of the JSON value being parsed, locating the
+    /// errors it raises there.
+    fn at<T>(
+        &mut self,
+        segment: impl Into<JsonPathSegment>,
+        f: impl FnOnce(&mut Self) -> AvroResult<T>,
+    ) -> AvroResult<T> {
+        self.path.push(segment.into());
+        let result = f(self).map_err(|error| self.locate(error));
+        self.path.pop();
+        result
+    }
+
+    /// Attach the location of the JSON value being parsed to `error`, unless it is located
+    /// already.
+    fn locate(&self, error: Error) -> Error {
+        match error {
+            Error::ParseSchemaAt { .. } | Error::ValidateSchema(_) => error,
+            error => Error::ParseSchemaAt {
+                location: SchemaLocation::new(&self.path),
+                source: Box::new(error),
+            },
         }
-        Ok(parsed_schemas)
+    }
+
+    /// Create a `Schema` from a `serde_json::Value` representing a JSON Avro schema which is not
+    /// the type of a record field, which is a `Schema::Attributed` if the schemas it contains
+    /// which are not named types have custom attributes.
+    fn parse_root(&mut self, value: &Value) -> AvroResult<Schema> {
+        let enclosing_attributes = self
+            .type_attributes
+            .replace((self.path.len(), BTreeMap::new()));
+        let schema = self.parse(value);
+        let type_attributes = std::mem::replace(&mut self.type_attributes, enclosing_attributes)
+            .map(|(_, type_attributes)| type_attributes)
+            .unwrap_or_default();
+        let schema = schema?;
+        Ok(if type_attributes.is_empty() {
+            schema
+        } else {
+            Schema::Attributed {
+                inner: Box::new(schema),
+                type_attributes,
+            }
+        })
     }
 
     /// Create a `Schema` from a `serde_json::Value` representing a JSON Avro
@@ -536,7 +1910,9 @@ impl Parser {
         }
     }
 
-    /// Given a name, tries to retrieve the parsed schema from `parsed_schemas`.
+    /// Given a name, returns a `Schema::Ref` if a type with that name has already been defined
+    /// in the schema being parsed. Otherwise, tries to retrieve the parsed schema from
+    /// `parsed_schemas`.
     /// If a parsed schema is not found, it checks if a json  with that name exists
     /// in `input_schemas` and then parses it  (removing it from `input_schemas`)
     /// and adds the parsed schema to `parsed_schemas`
@@ -544,18 +1920,101 @@ impl Parser {
     /// This method allows schemas definitions that depend on other types to
     /// parse their dependencies (or look them up if already parsed).
     fn fetch_schema(&mut self, name: &str) -> AvroResult<Schema> {
+        let fullname = self.qualify_reference(name);
+        let name = fullname.as_str();
+        if self.defined_names.contains(name) {
+            return Ok(Schema::Ref {
+                name: Name::new(name),
+            });
+        }
+        let parsed = match self.nested_names.get(name).cloned() {
+            // The named type is defined within another schema, which is parsed first.
+            Some(enclosing) => {
+                let enclosing = self.fetch_parsed_schema(&enclosing)?;
+                let mut nested = HashMap::new();
+                collect_named_schemas(&enclosing, &mut nested);
+                match nested.get(name) {
+                    Some(&schema) => schema.clone(),
+                    None => return Err(Error::ParsePrimitive(name.into())),
+                }
+            }
+            None => self.fetch_parsed_schema(name)?,
+        };
+        // The definition is inlined here, so the named types it contains are now defined in the
+        // schema being parsed as well.
+        let mut inlined = HashMap::new();
+        collect_named_schemas(&parsed, &mut inlined);
+        self.defined_names.extend(inlined.keys().cloned());
+        for (name, schema) in inlined {
+            // The fixed may be indexed along with the logical type it is annotated with.
+            let fixed = match schema {
+                Schema::Uuid { inner } | Schema::Custom { inner, .. } => inner,
+                schema => schema,
+            };
+            if let Schema::Fixed { .. } = fixed {
+                self.defined_fixeds.insert(name, fixed.clone());
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Return the parsed input schema with the given fullname, parsing it if need be.
+    fn fetch_parsed_schema(&mut self, name: &str) -> AvroResult<Schema> {
         if let Some(parsed) = self.parsed_schemas.get(name) {
             return Ok(parsed.clone());
         }
-        let value = self
-            .input_schemas
-            .remove(name)
-            .ok_or_else(|| Error::ParsePrimitive(name.into()))?;
-        let parsed = self.parse(&value)?;
+        let value = match self.input_schemas.remove(name) {
+            Some(value) => value,
+            None => {
+                return Err(match self.pending.iter().position(|p| p == name) {
+                    // The input schemas being parsed refer to each other.
+                    Some(start) => {
+                        let mut cycle = self.pending[start..].to_vec();
+                        cycle.push(name.to_string());
+                        Error::SchemaCycle(cycle)
+                    }
+                    None => Error::ParsePrimitive(name.into()),
+                });
+            }
+        };
+        let parsed = self.parse_standalone(name, &value)?;
         self.parsed_schemas.insert(name.to_string(), parsed.clone());
         Ok(parsed)
     }
 
+    /// Return the fullname a reference to a named type points to.
+    ///
+    /// A name containing no dots is looked up in the namespace of the enclosing definition
+    /// first, falling back to the null namespace if no such type is known.
+    fn qualify_reference(&self, name: &str) -> String {
+        if let Some(ref namespace) = self.namespace {
+            if !name.contains('.') {
+                let fullname = format!("{}.{}", namespace, name);
+                if self.defined_names.contains(&fullname)
+                    || self.parsed_schemas.contains_key(&fullname)
+                    || self.input_schemas.contains_key(&fullname)
+                    || self.pending.contains(&fullname)
+                    || self.nested_names.contains_key(&fullname)
+                {
+                    return fullname;
+                }
+            }
+        }
+        name.to_string()
+    }
+
+    /// Parse the `Name` of a named type. A name without an explicit namespace inherits the
+    /// namespace of the enclosing definition, while an empty namespace stands for the null
+    /// namespace.
+    fn parse_name(&self, complex: &Map<String, Value>) -> AvroResult<Name> {
+        parse_name_in(complex, self.namespace.as_deref())
+    }
+
+    /// Register the name of a named type being defined in the schema being parsed.
+    fn register_name(&mut self, name: &Name) {
+        self.defined_names.insert(name.fullname(None));
+    }
+
     fn parse_precision_and_scale(
         complex: &Map<String, Value>,
     ) -> Result<(Precision, Scale), Error> {
@@ -578,11 +2037,68 @@ impl Parser {
     }
 
     /// Parse a `serde_json::Value` representing a complex Avro type into a
-    /// `Schema`.
+    /// `Schema`, keeping its custom attributes and custom logical type.
+    fn parse_complex(&mut self, complex: &Map<String, Value>) -> AvroResult<Schema> {
+        let mut schema = self.parse_complex_type(complex)?;
+        // The logical types of the specification cannot be replaced by custom ones.
+        let logical_type = match complex.get("logicalType") {
+            Some(Value::String(name)) if !schema_attributes(&schema).contains(&"logicalType") => {
+                self.logical_types.get(name)
+            }
+            _ => None,
+        };
+        let schema = match (&mut schema, complex.get("type").and_then(|t| t.as_str())) {
+            // Named types defined here keep their custom attributes themselves.
+            (Schema::Record { attributes, .. }, Some("record"))
+            | (Schema::Enum { attributes, .. }, Some("enum"))
+            | (Schema::Fixed { attributes, .. }, Some("fixed")) => {
+                if logical_type.is_some() {
+                    attributes.remove("logicalType");
+                }
+                schema
+            }
+            (Schema::Uuid { .. }, Some("fixed")) => schema,
+            _ => {
+                let attributes = custom_attributes(complex, |key| {
+                    schema_attributes(&schema).contains(&key)
+                        || (logical_type.is_some() && key == "logicalType")
+                });
+                self.keep_type_attributes(attributes);
+                schema
+            }
+        };
+        if let Some(ref logical_type) = logical_type {
+            logical_type.check_schema(&schema)?;
+        }
+        Ok(match logical_type {
+            Some(logical_type) => Schema::Custom {
+                logical_type,
+                inner: Box::new(schema),
+            },
+            None => schema,
+        })
+    }
+
+    /// Keep the custom attributes of the schema being parsed, which is not a named type, if it is
+    /// part of the type of a record field.
+    fn keep_type_attributes(&mut self, attributes: BTreeMap<String, Value>) {
+        if attributes.is_empty() {
+            return;
+        }
+        if let Some((start, ref mut type_attributes)) = self.type_attributes {
+            type_attributes
+                .entry(format_json_path(&self.path[start..]))
+                .or_default()
+                .extend(attributes);
+        }
+    }
+
+    /// Parse a `serde_json::Value` representing a complex Avro type into a
+    /// `Schema`, ignoring the custom attributes of the `Schema` it is annotated with.
     ///
     /// Avro supports "recursive" definition of types.
     /// e.g: {"type": {"type": "string"}}
-    fn parse_complex(&mut self, complex: &Map<String, Value>) -> AvroResult<Schema> {
+    fn parse_complex_type(&mut self, complex: &Map<String, Value>) -> AvroResult<Schema> {
         fn logical_verify_type(
             complex: &Map<String, Value>,
             kinds: &[SchemaKind],
@@ -590,11 +2106,16 @@ impl Parser {
         ) -> AvroResult<Schema> {
             match complex.get("type") {
                 Some(value) => {
-                    let ty = parser.parse(value)?;
-                    if kinds
-                        .iter()
-                        .any(|&kind| SchemaKind::from(ty.clone()) == kind)
-                    {
+                    let ty = parser.at("type", |parser| parser.parse(value))?;
+                    // A fixed defined earlier is referred to by name.
+                    let kind = match ty {
+                        Schema::Ref { ref name } => parser
+                            .defined_fixeds
+                            .get(&name.fullname(None))
+                            .map_or(SchemaKind::Ref, SchemaKind::from),
+                        ref ty => SchemaKind::from(ty),
+                    };
+                    if kinds.contains(&kind) {
                         Ok(ty)
                     } else {
                         Err(Error::GetLogicalTypeVariant(value.clone()))
@@ -620,9 +2141,49 @@ impl Parser {
                         inner,
                     });
                 }
+                "big-decimal" => {
+                    logical_verify_type(complex, &[SchemaKind::Bytes], self)?;
+                    return Ok(Schema::BigDecimal);
+                }
                 "uuid" => {
-                    logical_verify_type(complex, &[SchemaKind::String], self)?;
-                    return Ok(Schema::Uuid);
+                    let inner = if complex.get("type").and_then(Value::as_str) == Some("fixed") {
+                        // The fixed is defined along with its logical type.
+                        match self.parse_fixed(complex)? {
+                            Schema::Fixed {
+                                name,
+                                doc,
+                                size,
+                                mut attributes,
+                            } => {
+                                attributes.remove("logicalType");
+                                Schema::Fixed {
+                                    name,
+                                    doc,
+                                    size,
+                                    attributes,
+                                }
+                            }
+                            schema => schema,
+                        }
+                    } else {
+                        logical_verify_type(
+                            complex,
+                            &[SchemaKind::String, SchemaKind::Fixed],
+                            self,
+                        )?
+                    };
+                    let fixed = match inner {
+                        Schema::Ref { ref name } => self.defined_fixeds.get(&name.fullname(None)),
+                        ref inner => Some(inner),
+                    };
+                    if let Some(&Schema::Fixed { size, .. }) = fixed {
+                        if size != 16 {
+                            return Err(Error::GetUuidFixedSize(size));
+                        }
+                    }
+                    return Ok(Schema::Uuid {
+                        inner: Box::new(inner),
+                    });
                 }
                 "date" => {
                     logical_verify_type(complex, &[SchemaKind::Int], self)?;
@@ -644,6 +2205,22 @@ impl Parser {
                     logical_verify_type(complex, &[SchemaKind::Long], self)?;
                     return Ok(Schema::TimestampMicros);
                 }
+                "local-timestamp-millis" => {
+                    logical_verify_type(complex, &[SchemaKind::Long], self)?;
+                    return Ok(Schema::LocalTimestampMillis);
+                }
+                "local-timestamp-micros" => {
+                    logical_verify_type(complex, &[SchemaKind::Long], self)?;
+                    return Ok(Schema::LocalTimestampMicros);
+                }
+                "timestamp-nanos" => {
+                    logical_verify_type(complex, &[SchemaKind::Long], self)?;
+                    return Ok(Schema::TimestampNanos);
+                }
+                "local-timestamp-nanos" => {
+                    logical_verify_type(complex, &[SchemaKind::Long], self)?;
+                    return Ok(Schema::LocalTimestampNanos);
+                }
                 "duration" => {
                     logical_verify_type(complex, &[SchemaKind::Fixed], self)?;
                     return Ok(Schema::Duration);
@@ -660,15 +2237,18 @@ impl Parser {
         }
         match complex.get("type") {
             Some(&Value::String(ref t)) => match t.as_str() {
-                "record" => self.parse_record(complex),
-                "enum" => Self::parse_enum(complex),
+                // The errors declared by protocols are records.
+                "record" | "error" => self.parse_record(complex),
+                "enum" => self.parse_enum(complex),
                 "array" => self.parse_array(complex),
                 "map" => self.parse_map(complex),
-                "fixed" => Self::parse_fixed(complex),
-                other => self.parse_known_schema(other),
+                "fixed" => self.parse_fixed(complex),
+                other => self.at("type", |parser| parser.parse_known_schema(other)),
             },
-            Some(&Value::Object(ref data)) => self.parse_complex(data),
-            Some(&Value::Array(ref variants)) => self.parse_union(variants),
+            Some(&Value::Object(ref data)) => self.at("type", |parser| parser.parse_complex(data)),
+            Some(&Value::Array(ref variants)) => {
+                self.at("type", |parser| parser.parse_union(variants))
+            }
             Some(unknown) => Err(Error::GetComplexType(unknown.clone())),
             None => Err(Error::GetComplexTypeField),
         }
@@ -677,22 +2257,36 @@ impl Parser {
     /// Parse a `serde_json::Value` representing a Avro record type into a
     /// `Schema`.
     fn parse_record(&mut self, complex: &Map<String, Value>) -> AvroResult<Schema> {
-        let name = Name::parse(complex)?;
+        let name = self.parse_name(complex)?;
+        // Registered before parsing the fields, so that they can refer to the record itself.
+        self.register_name(&name);
+        self.record_paths
+            .insert(name.fullname(None), self.path.clone());
 
         let mut lookup = HashMap::new();
 
-        let fields: Vec<RecordField> = complex
+        // The named types defined in the fields inherit the namespace of the record.
+        let enclosing_namespace =
+            std::mem::replace(&mut self.namespace, namespace_of(&name.fullname(None)));
+        let fields: AvroResult<Vec<RecordField>> = complex
             .get("fields")
             .and_then(|fields| fields.as_array())
             .ok_or(Error::GetRecordFieldsJson)
             .and_then(|fields| {
-                fields
-                    .iter()
-                    .filter_map(|field| field.as_object())
-                    .enumerate()
-                    .map(|(position, field)| RecordField::parse(field, position, self))
-                    .collect::<Result<_, _>>()
-            })?;
+                self.at("fields", |parser| {
+                    fields
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(index, field)| field.as_object().map(|field| (index, field)))
+                        .enumerate()
+                        .map(|(position, (index, field))| {
+                            parser.at(index, |parser| RecordField::parse(field, position, parser))
+                        })
+                        .collect::<Result<_, _>>()
+                })
+            });
+        self.namespace = enclosing_namespace;
+        let fields = fields?;
 
         for field in &fields {
             lookup.insert(field.name.clone(), field.position);
@@ -703,13 +2297,15 @@ impl Parser {
             doc: complex.doc(),
             fields,
             lookup,
+            attributes: custom_attributes(complex, |key| RECORD_ATTRIBUTES.contains(&key)),
         })
     }
 
     /// Parse a `serde_json::Value` representing a Avro enum type into a
     /// `Schema`.
-    fn parse_enum(complex: &Map<String, Value>) -> AvroResult<Schema> {
-        let name = Name::parse(complex)?;
+    fn parse_enum(&mut self, complex: &Map<String, Value>) -> AvroResult<Schema> {
+        let name = self.parse_name(complex)?;
+        self.register_name(&name);
 
         let symbols: Vec<String> = complex
             .get("symbols")
@@ -725,11 +2321,6 @@ impl Parser {
 
         let mut existing_symbols: HashSet<&String> = HashSet::with_capacity(symbols.len());
         for symbol in symbols.iter() {
-            // Ensure enum symbol names match [A-Za-z_][A-Za-z0-9_]*
-            if !ENUM_SYMBOL_NAME.is_match(symbol) {
-                return Err(Error::EnumSymbolName(symbol.to_string()));
-            }
-
             // Ensure there are no duplicate symbols
             if existing_symbols.contains(&symbol) {
                 return Err(Error::EnumSymbolDuplicate(symbol.to_string()));
@@ -738,10 +2329,27 @@ impl Parser {
             existing_symbols.insert(symbol);
         }
 
+        let default = match complex.get("default") {
+            Some(Value::String(default)) => {
+                // Ensure the default is one of the symbols
+                if !symbols.contains(default) {
+                    return Err(Error::GetEnumDefault {
+                        symbol: default.to_string(),
+                        symbols,
+                    });
+                }
+                Some(default.to_string())
+            }
+            Some(other) => return Err(Error::GetEnumDefaultFromJson(other.clone())),
+            None => None,
+        };
+
         Ok(Schema::Enum {
             name,
             doc: complex.doc(),
             symbols,
+            default,
+            attributes: custom_attributes(complex, |key| ENUM_ATTRIBUTES.contains(&key)),
         })
     }
 
@@ -751,7 +2359,7 @@ impl Parser {
         complex
             .get("items")
             .ok_or(Error::GetArrayItemsField)
-            .and_then(|items| self.parse(items))
+            .and_then(|items| self.at("items", |parser| parser.parse(items)))
             .map(|schema| Schema::Array(Box::new(schema)))
     }
 
@@ -761,7 +2369,7 @@ impl Parser {
         complex
             .get("values")
             .ok_or(Error::GetMapValuesField)
-            .and_then(|items| self.parse(items))
+            .and_then(|values| self.at("values", |parser| parser.parse(values)))
             .map(|schema| Schema::Map(Box::new(schema)))
     }
 
@@ -770,25 +2378,33 @@ impl Parser {
     fn parse_union(&mut self, items: &[Value]) -> AvroResult<Schema> {
         items
             .iter()
-            .map(|v| self.parse(v))
+            .enumerate()
+            .map(|(index, v)| self.at(index, |parser| parser.parse(v)))
             .collect::<Result<Vec<_>, _>>()
             .and_then(|schemas| Ok(Schema::Union(UnionSchema::new(schemas)?)))
     }
 
     /// Parse a `serde_json::Value` representing a Avro fixed type into a
     /// `Schema`.
-    fn parse_fixed(complex: &Map<String, Value>) -> AvroResult<Schema> {
-        let name = Name::parse(complex)?;
+    fn parse_fixed(&mut self, complex: &Map<String, Value>) -> AvroResult<Schema> {
+        let name = self.parse_name(complex)?;
+        self.register_name(&name);
 
         let size = complex
             .get("size")
             .and_then(|v| v.as_i64())
             .ok_or(Error::GetFixedSizeField)?;
+        let size = usize::try_from(size).map_err(|_| Error::GetFixedSizeNegative(size))?;
 
-        Ok(Schema::Fixed {
-            name,
-            size: size as usize,
-        })
+        let fixed = Schema::Fixed {
+            name: name.clone(),
+            doc: complex.doc(),
+            size,
+            attributes: custom_attributes(complex, |key| FIXED_ATTRIBUTES.contains(&key)),
+        };
+        self.defined_fixeds
+            .insert(name.fullname(None), fixed.clone());
+        Ok(fixed)
     }
 }
 
@@ -830,6 +2446,7 @@ impl Serialize for Schema {
                 ref name,
                 ref doc,
                 ref fields,
+                ref attributes,
                 ..
             } => {
                 let mut map = serializer.serialize_map(None)?;
@@ -845,24 +2462,61 @@ impl Serialize for Schema {
                     map.serialize_entry("aliases", aliases)?;
                 }
                 map.serialize_entry("fields", fields)?;
+                for (key, value) in attributes {
+                    map.serialize_entry(key, value)?;
+                }
                 map.end()
             }
             Schema::Enum {
                 ref name,
+                ref doc,
                 ref symbols,
-                ..
+                ref default,
+                ref attributes,
             } => {
                 let mut map = serializer.serialize_map(None)?;
                 map.serialize_entry("type", "enum")?;
+                if let Some(ref n) = name.namespace {
+                    map.serialize_entry("namespace", n)?;
+                }
                 map.serialize_entry("name", &name.name)?;
+                if let Some(ref docstr) = doc {
+                    map.serialize_entry("doc", docstr)?;
+                }
+                if let Some(ref aliases) = name.aliases {
+                    map.serialize_entry("aliases", aliases)?;
+                }
                 map.serialize_entry("symbols", symbols)?;
+                if let Some(ref default) = default {
+                    map.serialize_entry("default", default)?;
+                }
+                for (key, value) in attributes {
+                    map.serialize_entry(key, value)?;
+                }
                 map.end()
             }
-            Schema::Fixed { ref name, ref size } => {
+            Schema::Fixed {
+                ref name,
+                ref doc,
+                ref size,
+                ref attributes,
+            } => {
                 let mut map = serializer.serialize_map(None)?;
                 map.serialize_entry("type", "fixed")?;
+                if let Some(ref n) = name.namespace {
+                    map.serialize_entry("namespace", n)?;
+                }
                 map.serialize_entry("name", &name.name)?;
+                if let Some(ref docstr) = doc {
+                    map.serialize_entry("doc", docstr)?;
+                }
+                if let Some(ref aliases) = name.aliases {
+                    map.serialize_entry("aliases", aliases)?;
+                }
                 map.serialize_entry("size", size)?;
+                for (key, value) in attributes {
+                    map.serialize_entry(key, value)?;
+                }
                 map.end()
             }
             Schema::Decimal {
@@ -877,12 +2531,46 @@ impl Serialize for Schema {
                 map.serialize_entry("precision", precision)?;
                 map.end()
             }
-            Schema::Uuid => {
+            Schema::BigDecimal => {
                 let mut map = serializer.serialize_map(None)?;
-                map.serialize_entry("type", "string")?;
-                map.serialize_entry("logicalType", "uuid")?;
+                map.serialize_entry("type", "bytes")?;
+                map.serialize_entry("logicalType", "big-decimal")?;
                 map.end()
             }
+            Schema::Uuid { ref inner } => match **inner {
+                Schema::Fixed {
+                    ref name,
+                    ref doc,
+                    ref size,
+                    ref attributes,
+                } => {
+                    let mut map = serializer.serialize_map(None)?;
+                    map.serialize_entry("type", "fixed")?;
+                    if let Some(ref n) = name.namespace {
+                        map.serialize_entry("namespace", n)?;
+                    }
+                    map.serialize_entry("name", &name.name)?;
+                    if let Some(ref docstr) = doc {
+                        map.serialize_entry("doc", docstr)?;
+                    }
+                    if let Some(ref aliases) = name.aliases {
+                        map.serialize_entry("aliases", aliases)?;
+                    }
+                    map.serialize_entry("size", size)?;
+                    map.serialize_entry("logicalType", "uuid")?;
+                    for (key, value) in attributes {
+                        map.serialize_entry(key, value)?;
+                    }
+                    map.end()
+                }
+                // A string, or a fixed defined elsewhere and referred to by name.
+                ref inner => {
+                    let mut map = serializer.serialize_map(None)?;
+                    map.serialize_entry("type", inner)?;
+                    map.serialize_entry("logicalType", "uuid")?;
+                    map.end()
+                }
+            },
             Schema::Date => {
                 let mut map = serializer.serialize_map(None)?;
                 map.serialize_entry("type", "int")?;
@@ -913,6 +2601,30 @@ impl Serialize for Schema {
                 map.serialize_entry("logicalType", "timestamp-micros")?;
                 map.end()
             }
+            Schema::LocalTimestampMillis => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "long")?;
+                map.serialize_entry("logicalType", "local-timestamp-millis")?;
+                map.end()
+            }
+            Schema::LocalTimestampMicros => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "long")?;
+                map.serialize_entry("logicalType", "local-timestamp-micros")?;
+                map.end()
+            }
+            Schema::TimestampNanos => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "long")?;
+                map.serialize_entry("logicalType", "timestamp-nanos")?;
+                map.end()
+            }
+            Schema::LocalTimestampNanos => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("type", "long")?;
+                map.serialize_entry("logicalType", "local-timestamp-nanos")?;
+                map.end()
+            }
             Schema::Duration => {
                 let mut map = serializer.serialize_map(None)?;
 
@@ -920,12 +2632,40 @@ impl Serialize for Schema {
                 // duration should be or typically is.
                 let inner = Schema::Fixed {
                     name: Name::new("duration"),
+                    doc: None,
                     size: 12,
+                    attributes: BTreeMap::new(),
                 };
                 map.serialize_entry("type", &inner)?;
                 map.serialize_entry("logicalType", "duration")?;
                 map.end()
             }
+            Schema::Ref { ref name } => serializer.serialize_str(&name.fullname(None)),
+            Schema::Attributed {
+                ref inner,
+                ref type_attributes,
+            } => {
+                let mut schema = serde_json::to_value(&**inner).map_err(S::Error::custom)?;
+                for (path, attributes) in type_attributes {
+                    add_type_attributes(&mut schema, path, attributes);
+                }
+                schema.serialize(serializer)
+            }
+            Schema::Custom {
+                ref logical_type,
+                ref inner,
+            } => {
+                let mut map = match serde_json::to_value(&**inner).map_err(S::Error::custom)? {
+                    Value::Object(map) => map,
+                    inner => {
+                        let mut map = Map::new();
+                        map.insert("type".to_owned(), inner);
+                        map
+                    }
+                };
+                map.insert("logicalType".to_owned(), logical_type.name().into());
+                map.serialize(serializer)
+            }
         }
     }
 }
@@ -937,23 +2677,125 @@ impl Serialize for RecordField {
     {
         let mut map = serializer.serialize_map(None)?;
         map.serialize_entry("name", &self.name)?;
-        map.serialize_entry("type", &self.schema)?;
+        if self.type_attributes.is_empty() {
+            map.serialize_entry("type", &self.schema)?;
+        } else {
+            let mut schema = serde_json::to_value(&self.schema).map_err(S::Error::custom)?;
+            for (path, attributes) in &self.type_attributes {
+                if let Some(path) = path.strip_prefix("type") {
+                    add_type_attributes(&mut schema, path, attributes);
+                }
+            }
+            map.serialize_entry("type", &schema)?;
+        }
+
+        if let Some(ref docstr) = self.doc {
+            map.serialize_entry("doc", docstr)?;
+        }
 
         if let Some(ref default) = self.default {
             map.serialize_entry("default", default)?;
         }
 
+        if self.order != RecordFieldOrder::Ascending {
+            let order: &str = (&self.order).into();
+            map.serialize_entry("order", order)?;
+        }
+
+        if let Some(ref aliases) = self.aliases {
+            map.serialize_entry("aliases", aliases)?;
+        }
+
+        for (key, value) in &self.attributes {
+            map.serialize_entry(key, value)?;
+        }
+
         map.end()
     }
 }
 
+/// Add the custom `attributes` of a schema which is not a named type to the JSON of the schema
+/// it is part of, at `path` within it (see `RecordField::type_attributes`). The schemas given by
+/// name along the way are turned into JSON objects to hold them.
+fn add_type_attributes(mut json: &mut Value, path: &str, attributes: &BTreeMap<String, Value>) {
+    /// Turn a JSON schema into an object which can hold custom attributes, unless it is one
+    /// already: named types are wrapped as well, since the attributes would become their own.
+    fn expand(json: &mut Value) -> &mut Map<String, Value> {
+        let is_expanded = match json {
+            Value::Object(object) => !matches!(
+                object.get("type").and_then(Value::as_str),
+                Some("record") | Some("error") | Some("enum") | Some("fixed")
+            ),
+            _ => false,
+        };
+        if !is_expanded {
+            let mut object = Map::new();
+            object.insert("type".to_owned(), json.take());
+            *json = Value::Object(object);
+        }
+        match json {
+            Value::Object(object) => object,
+            _ => unreachable!(),
+        }
+    }
+
+    for part in path.split('.') {
+        let mut segments = part.split('[');
+        if let Some(key) = segments.next().filter(|key| !key.is_empty()) {
+            json = match key {
+                // The object of a type nested in another one, e.g. `{"type": {"type": "string"}}`,
+                // is serialized as the object of the outer type, unless the outer type is a
+                // logical type.
+                "type" => match json {
+                    Value::Object(object)
+                        if !object.contains_key("logicalType")
+                            && matches!(object.get("type"), Some(Value::String(_))) =>
+                    {
+                        json
+                    }
+                    json => match expand(json).get_mut("type") {
+                        Some(json) => json,
+                        None => return,
+                    },
+                },
+                key => match json.get_mut(key) {
+                    Some(json) => json,
+                    None => return,
+                },
+            };
+        }
+        for index in segments {
+            let index = match index.trim_end_matches(']').parse::<usize>() {
+                Ok(index) => index,
+                Err(_) => return,
+            };
+            json = match json.get_mut(index) {
+                Some(json) => json,
+                None => return,
+            };
+        }
+    }
+    let object = expand(json);
+    for (key, value) in attributes {
+        object.entry(key.as_str()).or_insert_with(|| value.clone());
+    }
+}
+
 /// Parses a **valid** avro schema into the Parsing Canonical Form.
 /// https://avro.apache.org/docs/1.8.2/spec.html#Parsing+Canonical+Form+for+Schemas
-fn parsing_canonical_form(schema: &serde_json::Value) -> String {
+///
+/// `namespace` is the namespace of the most tightly enclosing named type, used to qualify the
+/// names of the nested types and the references to them ([FULLNAMES] rule), and `defined` holds
+/// the fullnames of the named types defined so far.
+fn parsing_canonical_form(
+    schema: &serde_json::Value,
+    namespace: Option<&str>,
+    defined: &mut HashSet<String>,
+) -> String {
     match schema {
-        serde_json::Value::Object(map) => pcf_map(map),
-        serde_json::Value::String(s) => pcf_string(s),
-        serde_json::Value::Array(v) => pcf_array(v),
+        serde_json::Value::Object(map) => pcf_map(map, namespace, defined),
+        serde_json::Value::String(s) => pcf_string(&pcf_reference(s, namespace, defined)),
+        serde_json::Value::Array(v) => pcf_array(v, namespace, defined),
         json => panic!(
             "got invalid JSON value for canonical form of schema: {0}",
             json
@@ -961,37 +2803,57 @@ fn parsing_canonical_form(schema: &serde_json::Value) -> String {
     }
 }
 
-fn pcf_map(schema: &Map<String, serde_json::Value>) -> String {
-    // Look for the namespace variant up front.
-    let ns = schema.get("namespace").and_then(|v| v.as_str());
+fn pcf_map(
+    schema: &Map<String, serde_json::Value>,
+    namespace: Option<&str>,
+    defined: &mut HashSet<String>,
+) -> String {
+    // Look for the fullname of named types up front, it is the namespace of the nested types.
+    let fullname = match schema.get("type").and_then(|v| v.as_str()) {
+        Some("record") | Some("enum") | Some("fixed") => {
+            // Invariant: Only valid schemas. Must be a string.
+            let name = schema.get("name").and_then(|v| v.as_str()).unwrap();
+            let ns = schema
+                .get("namespace")
+                .and_then(|v| v.as_str())
+                .or(namespace);
+            Some(match ns {
+                Some(ns) if !name.contains('.') && !ns.is_empty() => {
+                    Cow::Owned(format!("{}.{}", ns, name))
+                }
+                _ => Cow::Borrowed(name),
+            })
+        }
+        _ => None,
+    };
+    if let Some(ref fullname) = fullname {
+        defined.insert(fullname.to_string());
+    }
+    let namespace = match fullname {
+        Some(ref fullname) => fullname.rfind('.').map(|position| &fullname[..position]),
+        None => namespace,
+    };
+    // Reduce primitive types to their simple form, once unused fields are stripped out.
+    // ([PRIMITIVE] rule)
+    if let Some(v @ serde_json::Value::String(_)) = schema.get("type") {
+        if schema.keys().all(|k| k == "type" || pcf_strips(k)) {
+            return parsing_canonical_form(v, namespace, defined);
+        }
+    }
+
     let mut fields = Vec::new();
     for (k, v) in schema {
-        // Reduce primitive types to their simple form. ([PRIMITIVE] rule)
-        if schema.len() == 1 && k == "type" {
-            // Invariant: function is only callable from a valid schema, so this is acceptable.
-            if let serde_json::Value::String(s) = v {
-                return pcf_string(s);
-            }
-        }
-
         // Strip out unused fields ([STRIP] rule)
-        if field_ordering_position(k).is_none() || k == "default" || k == "doc" || k == "aliases" {
+        if pcf_strips(k) {
             continue;
         }
 
         // Fully qualify the name, if it isn't already ([FULLNAMES] rule).
         if k == "name" {
-            // Invariant: Only valid schemas. Must be a string.
-            let name = v.as_str().unwrap();
-            let n = match ns {
-                Some(namespace) if !name.contains('.') => {
-                    Cow::Owned(format!("{}.{}", namespace, name))
-                }
-                _ => Cow::Borrowed(name),
-            };
-
-            fields.push((k, format!("{}:{}", pcf_string(k), pcf_string(&*n))));
-            continue;
+            if let Some(ref fullname) = fullname {
+                fields.push((k, format!("{}:{}", pcf_string(k), pcf_string(fullname))));
+                continue;
+            }
         }
 
         // Strip off quotes surrounding "size" type, if they exist ([INTEGERS] rule).
@@ -1004,10 +2866,32 @@ fn pcf_map(schema: &Map<String, serde_json::Value>) -> String {
             continue;
         }
 
-        // For anything else, recursively process the result.
+        // The type of a complex schema is a keyword, not a reference to a named type.
+        if k == "type" {
+            if let Some(kind @ "record")
+            | Some(kind @ "enum")
+            | Some(kind @ "fixed")
+            | Some(kind @ "array")
+            | Some(kind @ "map") = v.as_str()
+            {
+                fields.push((k, format!("{}:{}", pcf_string(k), pcf_string(kind))));
+                continue;
+            }
+        }
+
+        // For anything else, recursively process the result. Only types can refer to named
+        // types.
+        let namespace = match k.as_str() {
+            "type" | "items" | "values" | "fields" => namespace,
+            _ => None,
+        };
         fields.push((
             k,
-            format!("{}:{}", pcf_string(k), parsing_canonical_form(v)),
+            format!(
+                "{}:{}",
+                pcf_string(k),
+                parsing_canonical_form(v, namespace, defined)
+            ),
         ));
     }
 
@@ -1021,15 +2905,55 @@ fn pcf_map(schema: &Map<String, serde_json::Value>) -> String {
     format!("{{{}}}", inter)
 }
 
-fn pcf_array(arr: &[serde_json::Value]) -> String {
+fn pcf_strips(field: &str) -> bool {
+    field_ordering_position(field).is_none()
+        || field == "default"
+        || field == "doc"
+        || field == "aliases"
+}
+
+fn pcf_array(
+    arr: &[serde_json::Value],
+    namespace: Option<&str>,
+    defined: &mut HashSet<String>,
+) -> String {
     let inter = arr
         .iter()
-        .map(parsing_canonical_form)
+        .map(|v| parsing_canonical_form(v, namespace, defined))
         .collect::<Vec<String>>()
         .join(",");
     format!("[{}]", inter)
 }
 
+/// Fully qualify a reference to a named type, if it isn't already ([FULLNAMES] rule).
+///
+/// As when parsing, the name is looked up in the enclosing namespace first, falling back to the
+/// null namespace.
+fn pcf_reference<'a>(
+    name: &'a str,
+    namespace: Option<&str>,
+    defined: &HashSet<String>,
+) -> Cow<'a, str> {
+    match namespace {
+        Some(namespace) if !name.contains('.') && !is_primitive_type_name(name) => {
+            let fullname = format!("{}.{}", namespace, name);
+            if defined.contains(&fullname) {
+                Cow::Owned(fullname)
+            } else {
+                Cow::Borrowed(name)
+            }
+        }
+        _ => Cow::Borrowed(name),
+    }
+}
+
+fn is_primitive_type_name(name: &str) -> bool {
+    matches!(
+        name,
+        "null" | "boolean" | "int" | "long" | "float" | "double" | "bytes" | "string"
+    )
+}
+
 fn pcf_string(s: &str) -> String {
     format!("\"{}\"", s)
 }
@@ -1129,6 +3053,54 @@ mod tests {
         assert_eq!(variants.next(), None);
     }
 
+    #[test]
+    fn test_union_of_named_schemas() {
+        let schema = Schema::parse_str(
+            r#"
+            ["null",
+             {"type": "record", "name": "Cat", "namespace": "com.a", "fields": [{"name": "lives", "type": "int"}]},
+             {"type": "record", "name": "Dog", "namespace": "com.a", "fields": [{"name": "name", "type": "string"}]},
+             {"type": "enum", "name": "Color", "symbols": ["RED", "BLUE"]},
+             {"type": "enum", "name": "Size", "symbols": ["SMALL", "LARGE"]}]
+            "#,
+        )
+        .unwrap();
+        let union_schema = match schema {
+            Schema::Union(u) => u,
+            _ => unreachable!(),
+        };
+
+        let (index, _) = union_schema.find_schema_by_name("com.a.Dog").unwrap();
+        assert_eq!(index, 2);
+        assert!(union_schema.find_schema_by_name("Dog").is_none());
+
+        let dog = types::Value::Record(vec![(
+            "name".to_string(),
+            types::Value::String("Rex".to_string()),
+        )]);
+        assert_eq!(union_schema.find_schema(&dog).unwrap().0, 2);
+        let size = types::Value::Enum(1, "LARGE".to_string());
+        assert_eq!(union_schema.find_schema(&size).unwrap().0, 4);
+    }
+
+    #[test]
+    fn test_union_duplicate_named_schemas() {
+        let schema = Schema::parse_str(
+            r#"
+            [{"type": "fixed", "name": "Hash", "size": 16},
+             {"type": "fixed", "name": "Hash", "size": 32}]
+            "#,
+        );
+        assert!(schema.is_err());
+
+        let schema = Schema::parse_str(
+            r#"
+            [{"type": "fixed", "name": "Hash", "size": 16}, "Hash"]
+            "#,
+        );
+        assert!(schema.is_err());
+    }
+
     #[test]
     fn test_record_schema() {
         let schema = Schema::parse_str(
@@ -1157,25 +3129,123 @@ mod tests {
                     name: "a".to_string(),
                     doc: None,
                     default: Some(Value::Number(42i64.into())),
+                    default_value: Some(types::Value::Long(42)),
                     schema: Schema::Long,
                     order: RecordFieldOrder::Ascending,
                     position: 0,
+                    aliases: None,
+                    attributes: Default::default(),
+                    type_attributes: Default::default(),
                 },
                 RecordField {
                     name: "b".to_string(),
                     doc: None,
                     default: None,
+                    default_value: None,
                     schema: Schema::String,
                     order: RecordFieldOrder::Ascending,
                     position: 1,
+                    aliases: None,
+                    attributes: Default::default(),
+                    type_attributes: Default::default(),
                 },
             ],
             lookup,
+            attributes: Default::default(),
         };
 
         assert_eq!(expected, schema);
     }
 
+    #[test]
+    fn test_recursive_record_schema() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "Node",
+                "fields": [
+                    {"name": "value", "type": "long"},
+                    {"name": "next", "type": ["null", "Node"]}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+
+        let next = match schema {
+            Schema::Record { ref fields, .. } => &fields[1].schema,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            *next,
+            Schema::Union(
+                UnionSchema::new(vec![
+                    Schema::Null,
+                    Schema::Ref {
+                        name: Name::new("Node")
+                    }
+                ])
+                .unwrap()
+            )
+        );
+        assert_eq!(
+            schema.canonical_form(),
+            r#"{"name":"Node","type":"record","fields":[{"name":"value","type":"long"},{"name":"next","type":["null","Node"]}]}"#
+        );
+
+        let union_schema = match next {
+            Schema::Union(ref union_schema) => union_schema,
+            _ => unreachable!(),
+        };
+        let node = types::Value::Record(vec![
+            ("value".to_string(), types::Value::Long(1)),
+            (
+                "next".to_string(),
+                types::Value::Union(None, Box::new(types::Value::Null)),
+            ),
+        ]);
+        assert_eq!(union_schema.find_schema(&node), None);
+        assert_eq!(union_schema.find_schema_in(&node, &schema).unwrap().0, 1);
+    }
+
+    #[test]
+    fn test_previously_defined_named_schema() {
+        let schema = Schema::parse_str(
+            r#"
+            {
+                "type": "record",
+                "name": "Pair",
+                "fields": [
+                    {"name": "first", "type": {"type": "fixed", "name": "MD5", "size": 16}},
+                    {"name": "second", "type": "MD5"}
+                ]
+            }
+        "#,
+        )
+        .unwrap();
+
+        match schema {
+            Schema::Record { ref fields, .. } => {
+                assert_eq!(
+                    fields[1].schema,
+                    Schema::Ref {
+                        name: Name::new("MD5")
+                    }
+                );
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_undefined_named_schema() {
+        let schema = Schema::parse_str(
+            r#"{"type": "record", "name": "A", "fields": [{"name": "b", "type": "B"}]}"#,
+        );
+        assert!(schema.is_err());
+    }
+
     #[test]
     fn test_enum_schema() {
         let schema = Schema::parse_str(
@@ -1191,6 +3261,8 @@ mod tests {
                 "clubs".to_owned(),
                 "hearts".to_owned(),
             ],
+            default: None,
+            attributes: Default::default(),
         };
 
         assert_eq!(expected, schema);
@@ -1214,6 +3286,186 @@ mod tests {
         assert!(schema.is_err());
     }
 
+    #[test]
+    fn test_record_field_default_value() {
+        let schema = Schema::parse_str(
+            r#"{"type": "record", "name": "R", "fields": [
+                {"name": "a", "type": "int", "default": 5},
+                {"name": "b", "type": ["float", "null"], "default": 1.5},
+                {"name": "c", "type": "string"}
+            ]}"#,
+        )
+        .unwrap();
+        if let Schema::Record { ref fields, .. } = schema {
+            assert_eq!(fields[0].default_value, Some(types::Value::Int(5)));
+            assert_eq!(
+                fields[1].default_value,
+                Some(types::Value::Union(
+                    Some(types::UnionBranch {
+                        index: 0,
+                        name: None
+                    }),
+                    Box::new(types::Value::Float(1.5))
+                ))
+            );
+            assert_eq!(fields[2].default_value, None);
+        } else {
+            panic!("Expected a record schema, got {:?}", schema);
+        }
+
+        let error = Schema::parse_str(
+            r#"{"type": "record", "name": "R", "fields": [{"name": "a", "type": "int", "default": "5"}]}"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"Invalid schema: fields[0].default (line 1, column 84): invalid default "5" for a schema of kind Int"#
+        );
+    }
+
+    #[test]
+    fn test_strict_parsing_mode() {
+        let raw_schema = r#"{"type": "record", "name": "my-record", "fields": [
+            {"name": "a", "type": {"type": "enum", "name": "E", "symbols": ["A", "1-bad"]}},
+            {"name": "a", "type": {"type": "bytes", "logicalType": "decimal", "precision": 2, "scale": 3}},
+            {"name": "b", "type": "int", "default": "5"}
+        ]}"#;
+
+        match Schema::parse_str(raw_schema) {
+            Err(Error::ValidateSchema(violations)) => assert_eq!(
+                violations
+                    .iter()
+                    .map(|violation| (violation.location.to_string(), violation.reason.clone()))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (
+                        "name (line 1, column 28)".to_string(),
+                        ViolationReason::InvalidName("my-record".to_string())
+                    ),
+                    (
+                        "fields[0].type.symbols[1] (line 2, column 82)".to_string(),
+                        ViolationReason::InvalidEnumSymbol("1-bad".to_string())
+                    ),
+                    (
+                        "fields[1].name (line 3, column 22)".to_string(),
+                        ViolationReason::DuplicateFieldName("a".to_string())
+                    ),
+                    (
+                        "fields[1].type.scale (line 3, column 104)".to_string(),
+                        ViolationReason::DecimalScaleGreaterThanPrecision {
+                            scale: 3,
+                            precision: 2
+                        }
+                    ),
+                    (
+                        "fields[2].default (line 4, column 53)".to_string(),
+                        ViolationReason::InvalidDefault {
+                            value: "5".into(),
+                            kind: SchemaKind::Int
+                        }
+                    ),
+                ]
+            ),
+            other => panic!("Expected violations, got {:?}", other),
+        }
+
+        assert!(Schema::parse_str_with_mode(raw_schema, ParsingMode::Lenient).is_ok());
+
+        let raw_schemas = [
+            r#"{"type": "fixed", "name": "F", "size": 1}"#,
+            r#"{"type": "record", "name": "R", "fields": [{"name": "f", "type": "F", "default": 1}]}"#,
+        ];
+        match Schema::parse_list(&raw_schemas) {
+            Err(Error::ValidateSchema(violations)) => assert_eq!(
+                violations
+                    .iter()
+                    .map(|violation| violation.location.path.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["[1].fields[0].default"]
+            ),
+            other => panic!("Expected violations, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let raw_schema = r#"{"type": "record", "name": "R", "fields": [
+            {"name": "a", "type": "int"},
+            {"name": "b", "type": ["null", {"type": "array", "items": "Unknown"}]}
+        ]}"#;
+        let error = Schema::parse_str(raw_schema).unwrap_err();
+        let location = error.schema_location().unwrap();
+        assert_eq!(location.path, "fields[1].type[1].items");
+        assert_eq!((location.line, location.column), (Some(3), Some(71)));
+        assert!(matches!(
+            error,
+            Error::ParseSchemaAt { ref source, .. } if matches!(**source, Error::ParsePrimitive(_))
+        ));
+        assert!(matches!(error.without_location(), Error::ParsePrimitive(_)));
+        assert_eq!(
+            error.to_string(),
+            "Invalid schema at fields[1].type[1].items (line 3, column 71): Unknown primitive type: Unknown"
+        );
+
+        // Without the JSON text, only the path is known.
+        let value: Value = serde_json::from_str(raw_schema).unwrap();
+        let error = Schema::parse(&value).unwrap_err();
+        let location = error.schema_location().unwrap();
+        assert_eq!(location.path, "fields[1].type[1].items");
+        assert_eq!((location.line, location.column), (None, None));
+
+        let error = Schema::parse_str(r#"{"type": "record", "name": "R"}"#).unwrap_err();
+        assert_eq!(error.schema_location().unwrap().path, "");
+        let error =
+            Schema::parse_str(r#"{"type": "record", "name": "R", "fields": [{"type": "int"}]}"#)
+                .unwrap_err();
+        assert_eq!(error.schema_location().unwrap().path, "fields[0]");
+    }
+
+    #[test]
+    fn test_lenient_parsing_mode() {
+        let raw_schema = r#"{"type": "record", "name": "R", "fields": [
+            {"name": "a", "type": "int", "default": "five"}
+        ]}"#;
+        assert!(Schema::parse_str(raw_schema).is_err());
+        let schema = Schema::parse_str_with_mode(raw_schema, ParsingMode::Lenient).unwrap();
+        if let Schema::Record { ref fields, .. } = schema {
+            assert_eq!(fields[0].default_value, None);
+        }
+
+        // Negative sizes cannot describe any data.
+        let raw_schema = r#"{"type": "fixed", "name": "F", "size": -1}"#;
+        assert!(matches!(
+            Schema::parse_str_with_mode(raw_schema, ParsingMode::Lenient),
+            Err(Error::ParseSchemaAt { ref source, .. }) if matches!(**source, Error::GetFixedSizeNegative(-1))
+        ));
+    }
+
+    #[test]
+    fn test_enum_schema_default() {
+        let raw =
+            r#"{"type":"enum","name":"Suit","symbols":["diamonds","spades"],"default":"spades"}"#;
+        let schema = Schema::parse_str(raw).unwrap();
+
+        if let Schema::Enum { ref default, .. } = schema {
+            assert_eq!(default.as_deref(), Some("spades"));
+        } else {
+            panic!("Expected an enum schema, got {:?}", schema);
+        }
+        assert_eq!(serde_json::to_string(&schema).unwrap(), raw);
+
+        // The default must be one of the symbols.
+        let schema = Schema::parse_str(
+            r#"{"type": "enum", "name": "Suit", "symbols": ["diamonds", "spades"], "default": "clubs"}"#,
+        );
+        assert!(schema.is_err());
+
+        let schema = Schema::parse_str(
+            r#"{"type": "enum", "name": "Suit", "symbols": ["diamonds", "spades"], "default": 1}"#,
+        );
+        assert!(schema.is_err());
+    }
+
     #[test]
     fn test_fixed_schema() {
         let schema = Schema::parse_str(r#"{"type": "fixed", "name": "test", "size": 16}"#).unwrap();
@@ -1221,6 +3473,8 @@ mod tests {
         let expected = Schema::Fixed {
             name: Name::new("test"),
             size: 16usize,
+            doc: None,
+            attributes: Default::default(),
         };
 
         assert_eq!(expected, schema);
@@ -1315,6 +3569,309 @@ mod tests {
         let schema =
             Schema::parse_str(r#"{"type": "long", "logicalType": "timestamp-micros"}"#).unwrap();
         assert_eq!(schema, Schema::TimestampMicros);
+
+        let schema =
+            Schema::parse_str(r#"{"type": "long", "logicalType": "local-timestamp-millis"}"#)
+                .unwrap();
+        assert_eq!(schema, Schema::LocalTimestampMillis);
+
+        let schema =
+            Schema::parse_str(r#"{"type": "long", "logicalType": "local-timestamp-micros"}"#)
+                .unwrap();
+        assert_eq!(schema, Schema::LocalTimestampMicros);
+        assert_eq!(
+            serde_json::to_string(&schema).unwrap(),
+            r#"{"type":"long","logicalType":"local-timestamp-micros"}"#
+        );
+
+        let schema =
+            Schema::parse_str(r#"{"type": "long", "logicalType": "timestamp-nanos"}"#).unwrap();
+        assert_eq!(schema, Schema::TimestampNanos);
+
+        let schema =
+            Schema::parse_str(r#"{"type": "long", "logicalType": "local-timestamp-nanos"}"#)
+                .unwrap();
+        assert_eq!(schema, Schema::LocalTimestampNanos);
+        assert_eq!(
+            serde_json::to_string(&schema).unwrap(),
+            r#"{"type":"long","logicalType":"local-timestamp-nanos"}"#
+        );
+    }
+
+    #[test]
+    fn test_uuid_logical_type() {
+        let schema = Schema::parse_str(r#"{"type": "string", "logicalType": "uuid"}"#).unwrap();
+        assert_eq!(
+            schema,
+            Schema::Uuid {
+                inner: Box::new(Schema::String)
+            }
+        );
+
+        let schema = Schema::parse_str(
+            r#"{"type": "record", "name": "R", "namespace": "n", "fields": [
+                {"name": "a", "type": {"type": "fixed", "name": "Id", "size": 16, "logicalType": "uuid", "x": 1}},
+                {"name": "b", "type": "Id"}
+            ]}"#,
+        )
+        .unwrap();
+        let fixed_uuid = Schema::Uuid {
+            inner: Box::new(Schema::Fixed {
+                name: Name {
+                    name: "Id".to_string(),
+                    namespace: Some("n".to_string()),
+                    aliases: None,
+                },
+                doc: None,
+                size: 16,
+                attributes: vec![("x".to_string(), Value::from(1))]
+                    .into_iter()
+                    .collect(),
+            }),
+        };
+        match schema {
+            Schema::Record { ref fields, .. } => assert_eq!(fields[0].schema, fixed_uuid),
+            _ => panic!("Expected a record, got {:?}", schema),
+        }
+        assert_eq!(
+            serde_json::to_string(&fixed_uuid).unwrap(),
+            r#"{"type":"fixed","namespace":"n","name":"Id","size":16,"logicalType":"uuid","x":1}"#
+        );
+        assert_eq!(
+            schema.canonical_form(),
+            r#"{"name":"n.R","type":"record","fields":[{"name":"a","type":{"name":"n.Id","type":"fixed","logicalType":"uuid","size":16}},{"name":"b","type":"n.Id"}]}"#
+        );
+        // References to the fixed resolve to the uuid.
+        let mut names = Names::new(&schema);
+        assert_eq!(
+            names
+                .resolve(&Schema::Ref {
+                    name: Name::new("n.Id")
+                })
+                .unwrap(),
+            &fixed_uuid
+        );
+
+        assert!(matches!(
+            Schema::parse_str(r#"{"type": "fixed", "name": "Id", "size": 8, "logicalType": "uuid"}"#),
+            Err(Error::ParseSchemaAt { ref source, .. }) if matches!(**source, Error::GetUuidFixedSize(8))
+        ));
+    }
+
+    #[test]
+    fn test_logical_types_on_named_fixed() {
+        use crate::{decode::decode, encode::encode, types::Value, Decimal, Duration};
+
+        let schemas = Schema::parse_list(&[
+            r#"{"type": "fixed", "name": "F", "size": 8}"#,
+            r#"{"type": "fixed", "name": "Id", "size": 16}"#,
+            r#"{"type": "fixed", "name": "D", "size": 12}"#,
+            r#"{"type": "record", "name": "R", "fields": [
+                {"name": "a", "type": {"type": "F", "logicalType": "decimal", "precision": 4, "scale": 2}},
+                {"name": "b", "type": {"type": "F", "logicalType": "decimal", "precision": 4, "scale": 2}},
+                {"name": "c", "type": {"type": "Id", "logicalType": "uuid"}},
+                {"name": "d", "type": {"type": "Id", "logicalType": "uuid"}},
+                {"name": "e", "type": {"type": "D", "logicalType": "duration"}},
+                {"name": "f", "type": {"type": "D", "logicalType": "duration"}}
+            ]}"#,
+        ])
+        .unwrap();
+        let schema = &schemas[3];
+        let fields = match schema {
+            Schema::Record { fields, .. } => fields,
+            _ => panic!("Expected a record, got {:?}", schema),
+        };
+        // The first use of each fixed defines it, the second refers to it.
+        assert!(
+            matches!(fields[0].schema, Schema::Decimal { ref inner, .. } if matches!(**inner, Schema::Fixed { .. }))
+        );
+        assert!(
+            matches!(fields[1].schema, Schema::Decimal { ref inner, .. } if matches!(**inner, Schema::Ref { .. }))
+        );
+        assert!(
+            matches!(fields[3].schema, Schema::Uuid { ref inner } if matches!(**inner, Schema::Ref { .. }))
+        );
+        assert_eq!(fields[5].schema, Schema::Duration);
+        assert_eq!(
+            serde_json::to_value(&fields[3].schema).unwrap(),
+            serde_json::json!({"type": "Id", "logicalType": "uuid"})
+        );
+        assert_eq!(
+            &Schema::parse_str(&serde_json::to_string(schema).unwrap()).unwrap(),
+            schema
+        );
+
+        let decimal = Value::Decimal(Decimal::from(vec![0, 0, 0, 0, 0, 0, 4, 0xd2]));
+        let uuid = Value::Uuid("550e8400-e29b-41d4-a716-446655440000".parse().unwrap());
+        let duration = Value::Duration(Duration::from([1; 12]));
+        let record = Value::Record(vec![
+            ("a".to_string(), decimal.clone()),
+            ("b".to_string(), decimal),
+            ("c".to_string(), uuid.clone()),
+            ("d".to_string(), uuid),
+            ("e".to_string(), duration.clone()),
+            ("f".to_string(), duration),
+        ]);
+        assert!(record.validate(schema));
+        let mut buffer = Vec::new();
+        encode(&record, schema, &mut buffer).unwrap();
+        assert_eq!(buffer.len(), 2 * (8 + 16 + 12));
+        assert_eq!(decode(schema, &mut &buffer[..]).unwrap(), record);
+
+        // The reference is to a fixed of the wrong size, or not to a fixed.
+        for (definition, field) in &[
+            (
+                r#"{"type": "fixed", "name": "Id", "size": 8}"#,
+                r#"{"type": "Id", "logicalType": "uuid"}"#,
+            ),
+            (
+                r#"{"type": "enum", "name": "Id", "symbols": ["A"]}"#,
+                r#"{"type": "Id", "logicalType": "decimal", "precision": 4, "scale": 2}"#,
+            ),
+        ] {
+            let record = format!(
+                r#"{{"type": "record", "name": "R", "fields": [{{"name": "a", "type": {}}}]}}"#,
+                field
+            );
+            assert!(Schema::parse_list(&[definition, &record]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_unknown_logical_type() {
+        let schema =
+            Schema::parse_str(r#"{"type": "string", "logicalType": "varchar", "maxLength": 8}"#)
+                .unwrap();
+        assert!(matches!(
+            schema,
+            Schema::Attributed { ref inner, .. } if **inner == Schema::String
+        ));
+        assert_eq!(schema.attribute("maxLength"), Some(&Value::from(8)));
+
+        let schema = Schema::parse_str(
+            r#"{
+                "type": "record",
+                "name": "test",
+                "fields": [
+                    {"name": "a", "type": {"type": "string", "logicalType": "varchar", "maxLength": 8}},
+                    {"name": "b", "type": "string", "logicalType": "varchar", "maxLength": 8}
+                ]
+            }"#,
+        )
+        .unwrap();
+        if let Schema::Record { fields, .. } = schema {
+            assert!(matches!(fields[0].schema, Schema::String));
+            assert_eq!(
+                fields[0].type_attributes["type"]["logicalType"],
+                Value::String("varchar".to_string())
+            );
+            assert_eq!(
+                fields[0].type_attributes["type"]["maxLength"],
+                Value::from(8)
+            );
+            // The attributes of a type given by name are held by the field itself.
+            assert!(matches!(fields[1].schema, Schema::String));
+            assert!(fields[1].type_attributes.is_empty());
+            assert_eq!(fields[1].attribute("maxLength"), Some(&Value::from(8)));
+        } else {
+            panic!("Expected a record schema");
+        }
+    }
+
+    #[test]
+    fn test_schema_attributes() {
+        let mut schema = Schema::String;
+        assert_eq!(schema.attributes(), None);
+        assert!(matches!(
+            schema.set_attribute("x-pii", Value::Bool(true)),
+            Err(Error::SetAttribute(SchemaKind::String))
+        ));
+        assert_eq!(schema.remove_attribute("x-pii"), None);
+
+        let mut schema = Schema::parse_str(
+            r#"{"type": "record", "name": "test", "connect.name": "test", "fields": []}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            schema
+                .set_attribute("connect.name", Value::String("other".to_string()))
+                .unwrap(),
+            Some(Value::String("test".to_string()))
+        );
+        assert_eq!(
+            serde_json::to_string(&schema).unwrap(),
+            r#"{"type":"record","name":"test","fields":[],"connect.name":"other"}"#
+        );
+        // Custom attributes are not part of the Parsing Canonical Form.
+        assert_eq!(
+            schema.remove_attribute("connect.name"),
+            Some(Value::String("other".to_string()))
+        );
+        assert_eq!(
+            schema,
+            Schema::parse_str(
+                r#"{"type": "record", "name": "test", "connect.name": "test", "fields": []}"#
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_record_field_type_attributes() {
+        let input = r#"{"type":"record","name":"test","fields":[{"name":"a","type":["null",{"type":"array","items":{"type":{"type":"long","x-unit":"ms"},"logicalType":"timestamp-millis","x-tz":"UTC"},"x-max":10}]},{"name":"b","type":{"type":"map","values":["null",{"type":"string","x-pii":true}]}}]}"#;
+        let schema = Schema::parse_str(input).unwrap();
+        if let Schema::Record { ref fields, .. } = schema {
+            let paths: Vec<&str> = fields[0]
+                .type_attributes
+                .keys()
+                .map(String::as_str)
+                .collect();
+            assert_eq!(
+                paths,
+                vec!["type[1]", "type[1].items", "type[1].items.type"]
+            );
+            let paths: Vec<&str> = fields[1]
+                .type_attributes
+                .keys()
+                .map(String::as_str)
+                .collect();
+            assert_eq!(paths, vec!["type.values[1]"]);
+        } else {
+            panic!("Expected a record schema");
+        }
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap(),
+            serde_json::from_str::<Value>(input).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_record_field_attributes() {
+        let schema = Schema::parse_str(
+            r#"{
+                "type": "record",
+                "name": "test",
+                "fields": [
+                    {"name": "a", "type": "long", "doc": "A", "aliases": ["b"], "order": "ignore", "x-pii": true}
+                ]
+            }"#,
+        )
+        .unwrap();
+        if let Schema::Record { mut fields, .. } = schema {
+            let field = &mut fields[0];
+            assert_eq!(field.doc, Some("A".to_string()));
+            assert_eq!(field.aliases, Some(vec!["b".to_string()]));
+            assert_eq!(field.order, RecordFieldOrder::Ignore);
+            assert_eq!(field.attribute("x-pii"), Some(&Value::Bool(true)));
+            assert_eq!(field.remove_attribute("x-pii"), Some(Value::Bool(true)));
+            assert_eq!(field.set_attribute("x-owner", Value::Null), None);
+            assert_eq!(
+                serde_json::to_string(&field).unwrap(),
+                r#"{"name":"a","type":"long","doc":"A","order":"ignore","aliases":["b"],"x-owner":null}"#
+            );
+        } else {
+            panic!("Expected a record schema");
+        }
     }
 
     #[test]
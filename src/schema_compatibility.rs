@@ -1,154 +1,380 @@
 //! Logic for checking schema compatibility
-use crate::schema::{Schema, SchemaKind};
+use crate::{
+    schema::{Names, RecordField, Schema, SchemaKind},
+    Error,
+};
 use std::{
-    collections::{hash_map::DefaultHasher, HashSet},
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    fmt,
     hash::Hasher,
     ptr,
 };
+use strum_macros::{EnumString, IntoStaticStr};
 
 pub struct SchemaCompatibility;
 
-struct Checker {
+/// The reason why data written with a writer's schema cannot be read with a reader's schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IncompatibilityReason {
+    /// The writer's type is neither the reader's type, nor can it be promoted to it.
+    TypeMismatch,
+    /// The fullname of the writer's named type is neither the reader's fullname, nor one of
+    /// its aliases.
+    NameMismatch { writer: String, reader: String },
+    /// The writer's and the reader's fixed types have different sizes.
+    FixedSizeMismatch { writer: usize, reader: usize },
+    /// A field of the reader's record is not in the writer's record, and has no default value.
+    MissingDefault { field: String },
+    /// Symbols of the writer's enum are not in the reader's enum, which has no default symbol.
+    MissingEnumSymbols { symbols: Vec<String> },
+    /// The writer's type cannot be read as any branch of the reader's union.
+    MissingUnionBranch,
+    /// A named type is referenced, but not defined.
+    UndefinedName { name: String },
+}
+
+impl fmt::Display for IncompatibilityReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IncompatibilityReason::TypeMismatch => write!(f, "type mismatch"),
+            IncompatibilityReason::NameMismatch { writer, reader } => {
+                write!(f, "name mismatch: {} cannot be read as {}", writer, reader)
+            }
+            IncompatibilityReason::FixedSizeMismatch { writer, reader } => {
+                write!(f, "fixed size mismatch: {} instead of {}", writer, reader)
+            }
+            IncompatibilityReason::MissingDefault { field } => {
+                write!(f, "missing default value for field {}", field)
+            }
+            IncompatibilityReason::MissingEnumSymbols { symbols } => {
+                write!(f, "missing enum symbols {:?}", symbols)
+            }
+            IncompatibilityReason::MissingUnionBranch => {
+                write!(f, "no readable branch in the reader's union")
+            }
+            IncompatibilityReason::UndefinedName { name } => {
+                write!(f, "undefined named type {}", name)
+            }
+        }
+    }
+}
+
+/// An incompatibility found while checking that data written with a writer's schema can be
+/// read with a reader's schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Incompatibility {
+    /// Location of the incompatibility within the schemas, as a path similar to a JSON pointer,
+    /// e.g. `/fields/address/fields/zip`. Branches of unions are designated by their index.
+    pub path: String,
+    /// Type of the writer's schema at this location.
+    pub writer_type: SchemaKind,
+    /// Type of the reader's schema at this location.
+    pub reader_type: SchemaKind,
+    /// Why the writer's schema cannot be read with the reader's schema at this location.
+    pub reason: IncompatibilityReason,
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} (writer: {:?}, reader: {:?})",
+            self.path, self.reason, self.writer_type, self.reader_type
+        )
+    }
+}
+
+/// The compatibility levels enforced by schema registries between a new version of a schema and
+/// its previous versions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumString, IntoStaticStr)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum CompatibilityLevel {
+    /// No compatibility is required.
+    None,
+    /// The new schema can read data written with the latest version.
+    Backward,
+    /// The new schema can read data written with any previous version.
+    BackwardTransitive,
+    /// The latest version can read data written with the new schema.
+    Forward,
+    /// Any previous version can read data written with the new schema.
+    ForwardTransitive,
+    /// Both `Backward` and `Forward`.
+    Full,
+    /// Both `BackwardTransitive` and `ForwardTransitive`.
+    FullTransitive,
+}
+
+/// The direction in which a new version of a schema and a previous version are checked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompatibilityDirection {
+    /// The new schema reads data written with the previous version.
+    Backward,
+    /// The previous version reads data written with the new schema.
+    Forward,
+}
+
+/// An incompatibility between a new version of a schema and one of its previous versions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionIncompatibility {
+    /// Position of the previous version in the history the new schema is checked against.
+    pub version: usize,
+    /// Direction in which the versions are not compatible.
+    pub direction: CompatibilityDirection,
+    /// The incompatibility, where the writer's schema is the previous version for a
+    /// `Backward` check, and the new schema for a `Forward` check.
+    pub incompatibility: Incompatibility,
+}
+
+impl fmt::Display for VersionIncompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} incompatibility with version {}: {}",
+            self.direction, self.version, self.incompatibility
+        )
+    }
+}
+
+struct Checker<'s> {
     recursion: HashSet<(u64, u64)>,
+    writers_names: Names<'s>,
+    readers_names: Names<'s>,
+    path: Vec<String>,
+    incompatibilities: Vec<Incompatibility>,
 }
 
-impl Checker {
-    /// Create a new checker, with recursion set to an empty set.
-    pub(crate) fn new() -> Self {
+impl<'s> Checker<'s> {
+    /// Create a new checker for the given root schemas, with recursion set to an empty set.
+    pub(crate) fn new(writers_schema: &'s Schema, readers_schema: &'s Schema) -> Self {
         Self {
             recursion: HashSet::new(),
+            writers_names: Names::new(writers_schema),
+            readers_names: Names::new(readers_schema),
+            path: Vec::new(),
+            incompatibilities: Vec::new(),
         }
     }
 
-    pub(crate) fn can_read(&mut self, writers_schema: &Schema, readers_schema: &Schema) -> bool {
-        self.full_match_schemas(writers_schema, readers_schema)
+    pub(crate) fn can_read(
+        &mut self,
+        writers_schema: &'s Schema,
+        readers_schema: &'s Schema,
+    ) -> bool {
+        self.full_match_schemas(writers_schema, readers_schema);
+        self.incompatibilities.is_empty()
     }
 
+    /// Check that the writer's schema can be read with the reader's schema, recording every
+    /// incompatibility found along the way.
     pub(crate) fn full_match_schemas(
         &mut self,
-        writers_schema: &Schema,
-        readers_schema: &Schema,
-    ) -> bool {
+        writers_schema: &'s Schema,
+        readers_schema: &'s Schema,
+    ) {
+        // References are followed to their definition, which also makes sure that recursive
+        // types are detected as such below.
+        let writers_schema = match self.writers_names.resolve(writers_schema) {
+            Ok(schema) => schema,
+            Err(e) => return self.report_undefined(writers_schema, readers_schema, e),
+        };
+        let readers_schema = match self.readers_names.resolve(readers_schema) {
+            Ok(schema) => schema,
+            Err(e) => return self.report_undefined(writers_schema, readers_schema, e),
+        };
+
+        // Custom logical types do not change how the values of their base schema are encoded.
+        if let Schema::Custom { inner, .. } = writers_schema {
+            return self.full_match_schemas(inner, readers_schema);
+        }
+        if let Schema::Custom { inner, .. } = readers_schema {
+            return self.full_match_schemas(writers_schema, inner);
+        }
+
         if self.recursion_in_progress(writers_schema, readers_schema) {
-            return true;
+            return;
         }
 
-        if !SchemaCompatibility::match_schemas(writers_schema, readers_schema) {
-            return false;
+        // Each branch of a writer's union must be readable.
+        if let Schema::Union(w_union) = writers_schema {
+            for (i, schema) in w_union.variants().iter().enumerate() {
+                self.path.push(i.to_string());
+                self.full_match_schemas(schema, readers_schema);
+                self.path.pop();
+            }
+            return;
         }
 
-        let w_type = SchemaKind::from(writers_schema);
-        let r_type = SchemaKind::from(readers_schema);
+        if let Schema::Union(_) = readers_schema {
+            return self.match_union_schemas(writers_schema, readers_schema);
+        }
 
-        if w_type != SchemaKind::Union && (r_type.is_primitive() || r_type == SchemaKind::Fixed) {
-            return true;
+        if let Some(reason) = SchemaCompatibility::mismatch(writers_schema, readers_schema) {
+            return self.report(writers_schema, readers_schema, reason);
         }
 
-        match r_type {
-            SchemaKind::Record => self.match_record_schemas(writers_schema, readers_schema),
-            SchemaKind::Map => {
-                if let Schema::Map(w_m) = writers_schema {
-                    if let Schema::Map(r_m) = readers_schema {
-                        self.full_match_schemas(w_m, r_m)
-                    } else {
-                        unreachable!("readers_schema should have been Schema::Map")
-                    }
-                } else {
-                    unreachable!("writers_schema should have been Schema::Map")
-                }
+        match (writers_schema, readers_schema) {
+            (
+                Schema::Record {
+                    fields: w_fields,
+                    lookup: w_lookup,
+                    ..
+                },
+                Schema::Record {
+                    fields: r_fields, ..
+                },
+            ) => self.match_record_schemas(w_fields, w_lookup, r_fields, readers_schema),
+            (Schema::Map(w_m), Schema::Map(r_m)) => {
+                self.path.push("values".to_string());
+                self.full_match_schemas(w_m, r_m);
+                self.path.pop();
             }
-            SchemaKind::Array => {
-                if let Schema::Array(w_a) = writers_schema {
-                    if let Schema::Array(r_a) = readers_schema {
-                        self.full_match_schemas(w_a, r_a)
-                    } else {
-                        unreachable!("readers_schema should have been Schema::Array")
-                    }
-                } else {
-                    unreachable!("writers_schema should have been Schema::Array")
-                }
+            (Schema::Array(w_a), Schema::Array(r_a)) => {
+                self.path.push("items".to_string());
+                self.full_match_schemas(w_a, r_a);
+                self.path.pop();
             }
-            SchemaKind::Union => self.match_union_schemas(writers_schema, readers_schema),
-            SchemaKind::Enum => {
-                // reader's symbols must contain all writer's symbols
-                if let Schema::Enum {
+            (
+                Schema::Enum {
                     symbols: w_symbols, ..
-                } = writers_schema
-                {
-                    if let Schema::Enum {
-                        symbols: r_symbols, ..
-                    } = readers_schema
-                    {
-                        return w_symbols.iter().find(|e| !r_symbols.contains(e)).is_none();
-                    }
-                }
-                false
-            }
-            _ => {
-                if w_type == SchemaKind::Union {
-                    if let Schema::Union(r) = writers_schema {
-                        if r.schemas.len() == 1 {
-                            return self.full_match_schemas(&r.schemas[0], readers_schema);
-                        }
-                    }
+                },
+                Schema::Enum {
+                    symbols: r_symbols,
+                    default: r_default,
+                    ..
+                },
+            ) => {
+                // reader's symbols must contain all writer's symbols, unless the reader has a
+                // default symbol to fall back on
+                let missing = w_symbols
+                    .iter()
+                    .filter(|symbol| !r_symbols.contains(symbol))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if r_default.is_none() && !missing.is_empty() {
+                    self.report(
+                        writers_schema,
+                        readers_schema,
+                        IncompatibilityReason::MissingEnumSymbols { symbols: missing },
+                    );
                 }
-                false
             }
+            _ => (),
         }
     }
 
-    fn match_record_schemas(&mut self, writers_schema: &Schema, readers_schema: &Schema) -> bool {
-        let w_type = SchemaKind::from(writers_schema);
-
-        if w_type == SchemaKind::Union {
-            return false;
+    fn match_record_schemas(
+        &mut self,
+        w_fields: &'s [RecordField],
+        w_lookup: &HashMap<String, usize>,
+        r_fields: &'s [RecordField],
+        readers_schema: &'s Schema,
+    ) {
+        for field in r_fields.iter() {
+            self.path.push("fields".to_string());
+            self.path.push(field.name.clone());
+            // The writer's field may also be known by one of the reader's aliases.
+            let pos = w_lookup.get(&field.name).or_else(|| {
+                field
+                    .aliases
+                    .iter()
+                    .flatten()
+                    .find_map(|alias| w_lookup.get(alias))
+            });
+            if let Some(pos) = pos {
+                self.full_match_schemas(&w_fields[*pos].schema, &field.schema);
+            } else if field.default.is_none() {
+                self.incompatibilities.push(Incompatibility {
+                    path: self.current_path(),
+                    writer_type: SchemaKind::Record,
+                    reader_type: SchemaKind::from(readers_schema),
+                    reason: IncompatibilityReason::MissingDefault {
+                        field: field.name.clone(),
+                    },
+                });
+            }
+            self.path.pop();
+            self.path.pop();
         }
+    }
 
-        if let Schema::Record {
-            fields: w_fields,
-            lookup: w_lookup,
-            ..
-        } = writers_schema
-        {
-            if let Schema::Record {
-                fields: r_fields, ..
-            } = readers_schema
-            {
-                for field in r_fields.iter() {
-                    if let Some(pos) = w_lookup.get(&field.name) {
-                        if !self.full_match_schemas(&w_fields[*pos].schema, &field.schema) {
-                            return false;
-                        }
-                    } else if field.default.is_none() {
-                        return false;
-                    }
-                }
+    fn match_union_schemas(&mut self, writers_schema: &'s Schema, readers_schema: &'s Schema) {
+        let r_union = match readers_schema {
+            Schema::Union(r_union) => r_union,
+            _ => unreachable!("readers_schema should have been Schema::Union"),
+        };
+        let w_type = SchemaKind::from(writers_schema);
+        let w_name = match writers_schema {
+            Schema::Record { name, .. }
+            | Schema::Enum { name, .. }
+            | Schema::Fixed { name, .. } => Some(name.fullname(None)),
+            _ => None,
+        };
+        let candidates = r_union.best_matches(w_type, w_name.as_deref(), &mut self.readers_names);
+
+        // The first branch which can be read without any incompatibility is the one the
+        // writer's data is read as. Otherwise, the incompatibilities with the best branch of
+        // the same type are the most relevant ones.
+        let mut best = None;
+        for (i, schema) in candidates {
+            let recursion = self.recursion.clone();
+            let found = self.incompatibilities.len();
+            self.path.push(i.to_string());
+            self.full_match_schemas(writers_schema, schema);
+            self.path.pop();
+            if self.incompatibilities.len() == found {
+                return;
             }
+            let incompatibilities = self.incompatibilities.split_off(found);
+            self.recursion = recursion;
+            let same_type = self
+                .readers_names
+                .resolve(schema)
+                .map(SchemaKind::from)
+                .ok()
+                == Some(w_type);
+            if best.is_none() && same_type {
+                best = Some(incompatibilities);
+            }
+        }
+        match best {
+            Some(incompatibilities) => self.incompatibilities.extend(incompatibilities),
+            None => self.report(
+                writers_schema,
+                readers_schema,
+                IncompatibilityReason::MissingUnionBranch,
+            ),
         }
-        true
     }
 
-    fn match_union_schemas(&mut self, writers_schema: &Schema, readers_schema: &Schema) -> bool {
-        let w_type = SchemaKind::from(writers_schema);
-        let r_type = SchemaKind::from(readers_schema);
+    fn report(
+        &mut self,
+        writers_schema: &Schema,
+        readers_schema: &Schema,
+        reason: IncompatibilityReason,
+    ) {
+        self.incompatibilities.push(Incompatibility {
+            path: self.current_path(),
+            writer_type: SchemaKind::from(writers_schema),
+            reader_type: SchemaKind::from(readers_schema),
+            reason,
+        });
+    }
 
-        assert_eq!(r_type, SchemaKind::Union);
+    fn report_undefined(&mut self, writers_schema: &Schema, readers_schema: &Schema, e: Error) {
+        let name = match e {
+            Error::GetNamedSchema(name) => name,
+            e => e.to_string(),
+        };
+        self.report(
+            writers_schema,
+            readers_schema,
+            IncompatibilityReason::UndefinedName { name },
+        );
+    }
 
-        if w_type == SchemaKind::Union {
-            if let Schema::Union(u) = writers_schema {
-                u.schemas
-                    .iter()
-                    .all(|schema| self.full_match_schemas(schema, readers_schema))
-            } else {
-                unreachable!("writers_schema should have been Schema::Union")
-            }
-        } else if let Schema::Union(u) = readers_schema {
-            u.schemas
-                .iter()
-                .any(|schema| self.full_match_schemas(writers_schema, schema))
-        } else {
-            unreachable!("readers_schema should have been Schema::Union")
-        }
+    fn current_path(&self) -> String {
+        format!("/{}", self.path.join("/"))
     }
 
     fn recursion_in_progress(&mut self, writers_schema: &Schema, readers_schema: &Schema) -> bool {
@@ -171,7 +397,7 @@ impl SchemaCompatibility {
     /// `can_read` performs a full, recursive check that a datum written using the
     /// writers_schema can be read using the readers_schema.
     pub fn can_read(writers_schema: &Schema, readers_schema: &Schema) -> bool {
-        let mut c = Checker::new();
+        let mut c = Checker::new(writers_schema, readers_schema);
         c.can_read(writers_schema, readers_schema)
     }
 
@@ -182,122 +408,111 @@ impl SchemaCompatibility {
             && SchemaCompatibility::can_read(readers_schema, writers_schema)
     }
 
-    ///  `match_schemas` performs a basic check that a datum written with the
-    ///  writers_schema could be read using the readers_schema. This check only includes
-    ///  matching the types, including schema promotion, and matching the full name for
-    ///  named types. Aliases for named types are not supported here, and the rust
-    ///  implementation of Avro in general does not include support for aliases (I think).
-    pub(crate) fn match_schemas(writers_schema: &Schema, readers_schema: &Schema) -> bool {
-        let w_type = SchemaKind::from(writers_schema);
-        let r_type = SchemaKind::from(readers_schema);
-
-        if w_type == SchemaKind::Union || r_type == SchemaKind::Union {
-            return true;
-        }
+    /// `read_incompatibilities` performs the same check as `can_read`, returning every reason
+    /// why a datum written using the writers_schema cannot be read using the readers_schema.
+    /// The result is empty if the schemas are compatible.
+    pub fn read_incompatibilities(
+        writers_schema: &Schema,
+        readers_schema: &Schema,
+    ) -> Vec<Incompatibility> {
+        let mut c = Checker::new(writers_schema, readers_schema);
+        c.full_match_schemas(writers_schema, readers_schema);
+        c.incompatibilities
+    }
 
-        if w_type == r_type {
-            if r_type.is_primitive() {
-                return true;
+    /// `check_compatibility` checks that new_schema can be registered as the next version of
+    /// history, which is ordered from the oldest version to the latest one, at the given
+    /// compatibility level. Every incompatibility found is returned, along with the previous
+    /// version it was found with. The result is empty if the new schema is compatible.
+    pub fn check_compatibility(
+        level: CompatibilityLevel,
+        new_schema: &Schema,
+        history: &[Schema],
+    ) -> Vec<VersionIncompatibility> {
+        let (backward, forward, transitive) = match level {
+            CompatibilityLevel::None => return Vec::new(),
+            CompatibilityLevel::Backward => (true, false, false),
+            CompatibilityLevel::BackwardTransitive => (true, false, true),
+            CompatibilityLevel::Forward => (false, true, false),
+            CompatibilityLevel::ForwardTransitive => (false, true, true),
+            CompatibilityLevel::Full => (true, true, false),
+            CompatibilityLevel::FullTransitive => (true, true, true),
+        };
+        let first = if transitive {
+            0
+        } else {
+            history.len().saturating_sub(1)
+        };
+
+        let mut incompatibilities = Vec::new();
+        for (version, previous_schema) in history.iter().enumerate().skip(first) {
+            if backward {
+                incompatibilities.extend(
+                    SchemaCompatibility::read_incompatibilities(previous_schema, new_schema)
+                        .into_iter()
+                        .map(|incompatibility| VersionIncompatibility {
+                            version,
+                            direction: CompatibilityDirection::Backward,
+                            incompatibility,
+                        }),
+                );
+            }
+            if forward {
+                incompatibilities.extend(
+                    SchemaCompatibility::read_incompatibilities(new_schema, previous_schema)
+                        .into_iter()
+                        .map(|incompatibility| VersionIncompatibility {
+                            version,
+                            direction: CompatibilityDirection::Forward,
+                            incompatibility,
+                        }),
+                );
             }
-
-            match r_type {
-                SchemaKind::Record => {
-                    if let Schema::Record { name: w_name, .. } = writers_schema {
-                        if let Schema::Record { name: r_name, .. } = readers_schema {
-                            return w_name.fullname(None) == r_name.fullname(None);
-                        } else {
-                            unreachable!("readers_schema should have been Schema::Record")
-                        }
-                    } else {
-                        unreachable!("writers_schema should have been Schema::Record")
-                    }
-                }
-                SchemaKind::Fixed => {
-                    if let Schema::Fixed {
-                        name: w_name,
-                        size: w_size,
-                    } = writers_schema
-                    {
-                        if let Schema::Fixed {
-                            name: r_name,
-                            size: r_size,
-                        } = readers_schema
-                        {
-                            return w_name.fullname(None) == r_name.fullname(None)
-                                && w_size == r_size;
-                        } else {
-                            unreachable!("readers_schema should have been Schema::Fixed")
-                        }
-                    } else {
-                        unreachable!("writers_schema should have been Schema::Fixed")
-                    }
-                }
-                SchemaKind::Enum => {
-                    if let Schema::Enum { name: w_name, .. } = writers_schema {
-                        if let Schema::Enum { name: r_name, .. } = readers_schema {
-                            return w_name.fullname(None) == r_name.fullname(None);
-                        } else {
-                            unreachable!("readers_schema should have been Schema::Enum")
-                        }
-                    } else {
-                        unreachable!("writers_schema should have been Schema::Enum")
-                    }
-                }
-                SchemaKind::Map => {
-                    if let Schema::Map(w_m) = writers_schema {
-                        if let Schema::Map(r_m) = readers_schema {
-                            return SchemaCompatibility::match_schemas(w_m, r_m);
-                        } else {
-                            unreachable!("readers_schema should have been Schema::Map")
-                        }
-                    } else {
-                        unreachable!("writers_schema should have been Schema::Map")
-                    }
-                }
-                SchemaKind::Array => {
-                    if let Schema::Array(w_a) = writers_schema {
-                        if let Schema::Array(r_a) = readers_schema {
-                            return SchemaCompatibility::match_schemas(w_a, r_a);
-                        } else {
-                            unreachable!("readers_schema should have been Schema::Array")
-                        }
-                    } else {
-                        unreachable!("writers_schema should have been Schema::Array")
-                    }
-                }
-                _ => (),
-            };
-        }
-
-        if w_type == SchemaKind::Int
-            && vec![SchemaKind::Long, SchemaKind::Float, SchemaKind::Double]
-                .iter()
-                .any(|&t| t == r_type)
-        {
-            return true;
-        }
-
-        if w_type == SchemaKind::Long
-            && vec![SchemaKind::Float, SchemaKind::Double]
-                .iter()
-                .any(|&t| t == r_type)
-        {
-            return true;
         }
+        incompatibilities
+    }
 
-        if w_type == SchemaKind::Float && r_type == SchemaKind::Double {
-            return true;
-        }
+    ///  `mismatch` performs a basic check that a datum written with the writers_schema could
+    ///  be read using the readers_schema, returning the reason why it cannot otherwise. This
+    ///  check only includes matching the types, including schema promotion, and matching the
+    ///  full name (or one of the reader's aliases) and size for named types.
+    pub(crate) fn mismatch(
+        writers_schema: &Schema,
+        readers_schema: &Schema,
+    ) -> Option<IncompatibilityReason> {
+        let w_type = SchemaKind::from(writers_schema);
+        let r_type = SchemaKind::from(readers_schema);
 
-        if w_type == SchemaKind::String && r_type == SchemaKind::Bytes {
-            return true;
+        if w_type != r_type {
+            if w_type.is_promotable_to(r_type) {
+                return None;
+            }
+            return Some(IncompatibilityReason::TypeMismatch);
         }
 
-        if w_type == SchemaKind::Bytes && r_type == SchemaKind::String {
-            return true;
+        match (writers_schema, readers_schema) {
+            (Schema::Record { name: w_name, .. }, Schema::Record { name: r_name, .. })
+            | (Schema::Enum { name: w_name, .. }, Schema::Enum { name: r_name, .. })
+            | (Schema::Fixed { name: w_name, .. }, Schema::Fixed { name: r_name, .. })
+                if !r_name.matches(w_name) =>
+            {
+                Some(IncompatibilityReason::NameMismatch {
+                    writer: w_name.fullname(None),
+                    reader: r_name.fullname(None),
+                })
+            }
+            (Schema::Fixed { size: w_size, .. }, Schema::Fixed { size: r_size, .. })
+                if w_size != r_size =>
+            {
+                Some(IncompatibilityReason::FixedSizeMismatch {
+                    writer: *w_size,
+                    reader: *r_size,
+                })
+            }
+            // Fields, items and values are matched by the full check, which also resolves the
+            // references and annotations they may be.
+            _ => None,
         }
-
-        false
     }
 }
 
@@ -408,6 +623,14 @@ mod tests {
         .unwrap()
     }
 
+    fn int_linked_list_schema() -> Schema {
+        Schema::parse_str(r#"{"type":"record", "name":"Node", "fields": [{"name": "value", "type": "int"},{"name": "next", "type": ["null", "Node"]}]}"#).unwrap()
+    }
+
+    fn long_linked_list_schema() -> Schema {
+        Schema::parse_str(r#"{"type":"record", "name":"Node", "fields": [{"name": "value", "type": "long"},{"name": "next", "type": ["null", "Node"]}]}"#).unwrap()
+    }
+
     fn union_schema(schemas: Vec<Schema>) -> Schema {
         let schema_string = schemas
             .iter()
@@ -498,6 +721,7 @@ mod tests {
             (a_int_b_dint_record1_schema(), empty_record1_schema()),
             (int_list_record_schema(), long_list_record_schema()),
             (nested_record(), nested_optional_record()),
+            (int_linked_list_schema(), long_linked_list_schema()),
         ];
 
         assert!(!incompatible_schemas
@@ -547,6 +771,8 @@ mod tests {
             (long_list_record_schema(), long_list_record_schema()),
             (long_list_record_schema(), int_list_record_schema()),
             (nested_optional_record(), nested_record()),
+            (int_linked_list_schema(), int_linked_list_schema()),
+            (long_linked_list_schema(), int_linked_list_schema()),
         ];
 
         assert!(compatible_schemas
@@ -736,6 +962,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_nested_record_namespace() {
+        let nested_schema = |inner_namespace: &str| {
+            Schema::parse_str(&format!(
+                r#"
+      {{"type":"record", "name":"Outer", "namespace":"ns", "fields": [
+        {{"name":"inner", "type": {{"type":"record", "name":"Inner", {}"fields": [
+          {{"name":"value", "type":"int"}}
+        ]}}}}
+      ]}}
+"#,
+                inner_namespace
+            ))
+            .unwrap()
+        };
+        // The nested record inherits the namespace of the enclosing one.
+        let inherited_schema = nested_schema("");
+        let explicit_schema = nested_schema(r#""namespace":"ns", "#);
+        let other_schema = nested_schema(r#""namespace":"other", "#);
+
+        assert!(SchemaCompatibility::mutual_read(
+            &inherited_schema,
+            &explicit_schema
+        ));
+        assert!(!SchemaCompatibility::can_read(
+            &inherited_schema,
+            &other_schema
+        ));
+        assert!(!SchemaCompatibility::can_read(
+            &other_schema,
+            &inherited_schema
+        ));
+    }
+
+    #[test]
+    fn test_aliases() {
+        let writer_schema = Schema::parse_str(
+            r#"
+      {"type":"record", "name":"Record", "namespace":"ns", "fields": [
+        {"name":"field1", "type":"int"},
+        {"name":"field2", "type":{"type":"enum", "name":"Enum", "symbols":["A","B"]}},
+        {"name":"field3", "type":{"type":"fixed", "name":"Fixed", "size":2}}
+      ]}
+"#,
+        )
+        .unwrap();
+        let reader_schema = Schema::parse_str(
+            r#"
+      {"type":"record", "name":"Renamed", "namespace":"other", "aliases":["ns.Record"], "fields": [
+        {"name":"renamed1", "type":"long", "aliases":["field1"]},
+        {"name":"renamed2", "type":{"type":"enum", "name":"Renamed", "namespace":"ns", "aliases":["Enum"], "symbols":["A","B"]}, "aliases":["field2"]},
+        {"name":"renamed3", "type":{"type":"fixed", "name":"other.Renamed", "aliases":["ns.Fixed"], "size":2}, "aliases":["field3"]}
+      ]}
+"#,
+        )
+        .unwrap();
+
+        assert!(SchemaCompatibility::can_read(
+            &writer_schema,
+            &reader_schema
+        ));
+        // Aliases are only used on the reader's side.
+        assert!(!SchemaCompatibility::can_read(
+            &reader_schema,
+            &writer_schema
+        ));
+    }
+
     #[test]
     fn test_enum_symbols() {
         let enum_schema1 = Schema::parse_str(
@@ -754,6 +1048,243 @@ mod tests {
         assert!(SchemaCompatibility::can_read(&enum_schema1, &enum_schema2));
     }
 
+    #[test]
+    fn test_enum_default() {
+        let writer_schema =
+            Schema::parse_str(r#"{"type":"enum", "name":"MyEnum", "symbols":["A","B","C"]}"#)
+                .unwrap();
+        let reader_schema = Schema::parse_str(
+            r#"{"type":"enum", "name":"MyEnum", "symbols":["A","B"], "default":"A"}"#,
+        )
+        .unwrap();
+        assert!(SchemaCompatibility::can_read(
+            &writer_schema,
+            &reader_schema
+        ));
+    }
+
+    #[test]
+    fn test_union_of_named_schemas() {
+        let cat = r#"{"type":"record", "name":"Cat", "fields":[{"name":"lives", "type":"int"}]}"#;
+        let dog = r#"{"type":"record", "name":"Dog", "fields":[{"name":"name", "type":"string"}]}"#;
+        let writer_schema = Schema::parse_str(&format!("[{}]", dog)).unwrap();
+        let reader_schema = Schema::parse_str(&format!("[{}, {}]", cat, dog)).unwrap();
+        assert!(SchemaCompatibility::can_read(
+            &writer_schema,
+            &reader_schema
+        ));
+        assert!(!SchemaCompatibility::can_read(
+            &reader_schema,
+            &writer_schema
+        ));
+    }
+
+    #[test]
+    fn test_union_best_match() {
+        let reader_schema = Schema::parse_str(r#"["long", "double"]"#).unwrap();
+        assert!(SchemaCompatibility::can_read(
+            &Schema::Float,
+            &reader_schema
+        ));
+        assert!(SchemaCompatibility::can_read(&Schema::Int, &reader_schema));
+        assert!(!SchemaCompatibility::can_read(
+            &Schema::String,
+            &reader_schema
+        ));
+
+        let best_matches = |schema: &Schema, kind, fullname| match schema {
+            Schema::Union(union_schema) => union_schema
+                .best_matches(kind, fullname, &mut Names::new(schema))
+                .into_iter()
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>(),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            best_matches(&reader_schema, SchemaKind::Float, None),
+            [1, 0]
+        );
+        assert_eq!(best_matches(&reader_schema, SchemaKind::Int, None), [0, 1]);
+
+        let reader_schema = Schema::parse_str(
+            r#"[
+              {"type":"record", "name":"Cat", "fields":[]},
+              {"type":"record", "name":"Dog", "aliases":["Hound"], "fields":[]}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            best_matches(&reader_schema, SchemaKind::Record, Some("Hound")),
+            [1, 0]
+        );
+    }
+
+    #[test]
+    fn test_logical_types() {
+        let date_schema = Schema::parse_str(r#"{"type":"int", "logicalType":"date"}"#).unwrap();
+        assert!(SchemaCompatibility::can_read(&date_schema, &date_schema));
+    }
+
+    #[test]
+    fn test_read_incompatibilities() {
+        let writer_schema = Schema::parse_str(
+            r#"
+      {"type":"record", "name":"Customer", "fields":[
+        {"name":"address", "type":{"type":"record", "name":"Address", "fields":[
+          {"name":"street", "type":"string"}
+        ]}},
+        {"name":"status", "type":{"type":"enum", "name":"Status", "symbols":["ACTIVE", "CLOSED"]}},
+        {"name":"id", "type":{"type":"fixed", "name":"Id", "size":16}},
+        {"name":"tags", "type":{"type":"array", "items":"string"}},
+        {"name":"ref", "type":["null", "long"]}
+      ]}
+"#,
+        )
+        .unwrap();
+        let reader_schema = Schema::parse_str(
+            r#"
+      {"type":"record", "name":"Customer", "fields":[
+        {"name":"address", "type":{"type":"record", "name":"Address", "fields":[
+          {"name":"street", "type":"string"},
+          {"name":"zip", "type":"string"}
+        ]}},
+        {"name":"status", "type":{"type":"enum", "name":"Status", "symbols":["ACTIVE"]}},
+        {"name":"id", "type":{"type":"fixed", "name":"Id", "size":8}},
+        {"name":"tags", "type":{"type":"array", "items":"int"}},
+        {"name":"ref", "type":["null", "int"]}
+      ]}
+"#,
+        )
+        .unwrap();
+
+        let incompatibilities =
+            SchemaCompatibility::read_incompatibilities(&writer_schema, &reader_schema);
+        assert_eq!(
+            incompatibilities
+                .iter()
+                .map(|incompatibility| (incompatibility.path.as_str(), &incompatibility.reason))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "/fields/address/fields/zip",
+                    &IncompatibilityReason::MissingDefault {
+                        field: "zip".to_string()
+                    }
+                ),
+                (
+                    "/fields/status",
+                    &IncompatibilityReason::MissingEnumSymbols {
+                        symbols: vec!["CLOSED".to_string()]
+                    }
+                ),
+                (
+                    "/fields/id",
+                    &IncompatibilityReason::FixedSizeMismatch {
+                        writer: 16,
+                        reader: 8
+                    }
+                ),
+                ("/fields/tags/items", &IncompatibilityReason::TypeMismatch),
+                ("/fields/ref/1", &IncompatibilityReason::MissingUnionBranch),
+            ]
+        );
+        assert_eq!(
+            incompatibilities[3].to_string(),
+            "/fields/tags/items: type mismatch (writer: String, reader: Int)"
+        );
+        assert!(
+            SchemaCompatibility::read_incompatibilities(&reader_schema, &reader_schema).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_read_incompatibilities_in_union_branch() {
+        let writer_schema = Schema::parse_str(
+            r#"["null", {"type":"record", "name":"A", "fields":[{"name":"a", "type":"long"}]}]"#,
+        )
+        .unwrap();
+        let reader_schema = Schema::parse_str(
+            r#"["null", {"type":"record", "name":"B", "aliases":["A"], "fields":[{"name":"a", "type":"int"}]}]"#,
+        )
+        .unwrap();
+
+        let incompatibilities =
+            SchemaCompatibility::read_incompatibilities(&writer_schema, &reader_schema);
+        assert_eq!(incompatibilities.len(), 1);
+        assert_eq!(incompatibilities[0].path, "/1/1/fields/a");
+        assert_eq!(incompatibilities[0].writer_type, SchemaKind::Long);
+        assert_eq!(incompatibilities[0].reader_type, SchemaKind::Int);
+    }
+
+    #[test]
+    fn test_compatibility_levels() {
+        let v1 = Schema::parse_str(
+            r#"{"type":"record", "name":"R", "fields":[{"name":"a", "type":"int"}]}"#,
+        )
+        .unwrap();
+        // Adds a field with a default, which is fully compatible
+        let v2 = Schema::parse_str(
+            r#"{"type":"record", "name":"R", "fields":[{"name":"a", "type":"int"}, {"name":"b", "type":"int", "default":0}]}"#,
+        )
+        .unwrap();
+        // Promotes a field, which is backward but not forward compatible
+        let v3 = Schema::parse_str(
+            r#"{"type":"record", "name":"R", "fields":[{"name":"a", "type":"long"}, {"name":"b", "type":"int", "default":0}]}"#,
+        )
+        .unwrap();
+        let history = [v1, v2];
+
+        let check = |level| SchemaCompatibility::check_compatibility(level, &v3, &history);
+        assert!(check(CompatibilityLevel::None).is_empty());
+        assert!(check(CompatibilityLevel::Backward).is_empty());
+        assert!(check(CompatibilityLevel::BackwardTransitive).is_empty());
+
+        let incompatibilities = check(CompatibilityLevel::Forward);
+        assert_eq!(incompatibilities.len(), 1);
+        assert_eq!(incompatibilities[0].version, 1);
+        assert_eq!(
+            incompatibilities[0].direction,
+            CompatibilityDirection::Forward
+        );
+        assert_eq!(incompatibilities[0].incompatibility.path, "/fields/a");
+
+        let versions = |level| {
+            check(level)
+                .iter()
+                .map(|incompatibility| incompatibility.version)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(versions(CompatibilityLevel::Full), [1]);
+        assert_eq!(versions(CompatibilityLevel::ForwardTransitive), [0, 1]);
+        assert_eq!(versions(CompatibilityLevel::FullTransitive), [0, 1]);
+
+        // v1 cannot read the field b of v2 without a default, v2 can read v1.
+        let v2_without_default = Schema::parse_str(
+            r#"{"type":"record", "name":"R", "fields":[{"name":"a", "type":"int"}, {"name":"b", "type":"int"}]}"#,
+        )
+        .unwrap();
+        let incompatibilities = SchemaCompatibility::check_compatibility(
+            CompatibilityLevel::Backward,
+            &v2_without_default,
+            &history[..1],
+        );
+        assert_eq!(incompatibilities.len(), 1);
+        assert_eq!(
+            incompatibilities[0].to_string(),
+            "Backward incompatibility with version 0: /fields/b: missing default value for field b (writer: Record, reader: Record)"
+        );
+    }
+
+    #[test]
+    fn test_compatibility_level_names() {
+        assert_eq!(
+            "BACKWARD_TRANSITIVE".parse::<CompatibilityLevel>().unwrap(),
+            CompatibilityLevel::BackwardTransitive
+        );
+        assert_eq!(<&str>::from(CompatibilityLevel::Full), "FULL");
+        assert!("SIDEWAYS".parse::<CompatibilityLevel>().is_err());
+    }
+
     // unused
     /*
         fn point_2d_schema() -> Schema {
@@ -0,0 +1,715 @@
+//! Logic for computing the structural differences between two versions of a schema
+use crate::schema::{Name, Names, RecordField, Schema, SchemaKind, UnionSchema};
+use serde_json::Value;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::Hasher,
+    ptr,
+};
+
+/// A change found between an old and a new version of a schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaChange {
+    /// Location of the change within the old schema, as a path similar to a JSON pointer, e.g.
+    /// `/fields/address/fields/zip`, or `None` if something was added. Fields are designated by
+    /// their name, and branches of unions by their index, in the old schema.
+    pub old_path: Option<String>,
+    /// Location of the change within the new schema, or `None` if something was removed. Fields
+    /// are designated by their name, and branches of unions by their index, in the new schema.
+    pub new_path: Option<String>,
+    /// What changed.
+    pub kind: ChangeKind,
+}
+
+/// The kinds of changes between two versions of a schema.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChangeKind {
+    /// A field was added to a record.
+    FieldAdded { name: String, has_default: bool },
+    /// A field was removed from a record.
+    FieldRemoved { name: String, had_default: bool },
+    /// A field of a record was renamed, the old name being one of the aliases of the new field.
+    FieldRenamed { old: String, new: String },
+    /// The default value of a field changed.
+    DefaultChanged {
+        old: Option<Value>,
+        new: Option<Value>,
+    },
+    /// The type changed, and the old type cannot be promoted to the new one.
+    TypeChanged { old: SchemaKind, new: SchemaKind },
+    /// The type changed, and the old type can be promoted to the new one.
+    TypePromoted { old: SchemaKind, new: SchemaKind },
+    /// The name of a named type changed, regardless of its namespace.
+    NameChanged { old: String, new: String },
+    /// The namespace of a named type changed.
+    NamespaceChanged {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// The documentation of a named type or field changed.
+    DocChanged {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// A symbol was added to an enum.
+    EnumSymbolAdded { symbol: String },
+    /// A symbol was removed from an enum.
+    EnumSymbolRemoved { symbol: String },
+    /// The size of a fixed type changed.
+    FixedSizeChanged { old: usize, new: usize },
+    /// The precision of a decimal changed.
+    DecimalPrecisionChanged { old: usize, new: usize },
+    /// The scale of a decimal changed.
+    DecimalScaleChanged { old: usize, new: usize },
+    /// A branch was added to a union.
+    UnionBranchAdded { branch: String },
+    /// A branch was removed from a union.
+    UnionBranchRemoved { branch: String },
+}
+
+/// Compare the `old` and `new` versions of a schema, and return every structural change
+/// between them. The result is empty if the schemas are structurally the same.
+///
+/// Named types are compared where they are defined, and references to them are followed. Fields
+/// are matched by name, or by the aliases of the new field, and union branches by fullname for
+/// named types, or by type otherwise.
+pub fn diff(old: &Schema, new: &Schema) -> Vec<SchemaChange> {
+    let mut differ = Differ {
+        visited: HashSet::new(),
+        old_names: Names::new(old),
+        new_names: Names::new(new),
+        old_path: Vec::new(),
+        new_path: Vec::new(),
+        changes: Vec::new(),
+    };
+    differ.diff(old, new);
+    differ.changes
+}
+
+struct Differ<'s> {
+    visited: HashSet<(u64, u64)>,
+    old_names: Names<'s>,
+    new_names: Names<'s>,
+    old_path: Vec<String>,
+    new_path: Vec<String>,
+    changes: Vec<SchemaChange>,
+}
+
+impl<'s> Differ<'s> {
+    fn diff(&mut self, old: &'s Schema, new: &'s Schema) {
+        // Undefined references are compared as they are, by name.
+        let old = self.old_names.resolve(old).unwrap_or(old);
+        let new = self.new_names.resolve(new).unwrap_or(new);
+
+        // Recursive types are only compared once.
+        if !self.visited.insert((address(old), address(new))) {
+            return;
+        }
+
+        let old_kind = SchemaKind::from(old);
+        let new_kind = SchemaKind::from(new);
+        if old_kind != new_kind {
+            if old_kind.is_promotable_to(new_kind) {
+                self.push(ChangeKind::TypePromoted {
+                    old: old_kind,
+                    new: new_kind,
+                });
+            } else {
+                self.push(ChangeKind::TypeChanged {
+                    old: old_kind,
+                    new: new_kind,
+                });
+            }
+            return;
+        }
+
+        match (old, new) {
+            (
+                Schema::Record {
+                    name: old_name,
+                    doc: old_doc,
+                    fields: old_fields,
+                    lookup: old_lookup,
+                    ..
+                },
+                Schema::Record {
+                    name: new_name,
+                    doc: new_doc,
+                    fields: new_fields,
+                    lookup: new_lookup,
+                    ..
+                },
+            ) => {
+                self.diff_names(old_name, new_name);
+                self.diff_docs(old_doc, new_doc);
+                self.diff_fields(old_fields, old_lookup, new_fields, new_lookup);
+            }
+            (
+                Schema::Enum {
+                    name: old_name,
+                    doc: old_doc,
+                    symbols: old_symbols,
+                    ..
+                },
+                Schema::Enum {
+                    name: new_name,
+                    doc: new_doc,
+                    symbols: new_symbols,
+                    ..
+                },
+            ) => {
+                self.diff_names(old_name, new_name);
+                self.diff_docs(old_doc, new_doc);
+                for symbol in old_symbols.iter().filter(|s| !new_symbols.contains(s)) {
+                    self.push(ChangeKind::EnumSymbolRemoved {
+                        symbol: symbol.clone(),
+                    });
+                }
+                for symbol in new_symbols.iter().filter(|s| !old_symbols.contains(s)) {
+                    self.push(ChangeKind::EnumSymbolAdded {
+                        symbol: symbol.clone(),
+                    });
+                }
+            }
+            (
+                Schema::Fixed {
+                    name: old_name,
+                    doc: old_doc,
+                    size: old_size,
+                    ..
+                },
+                Schema::Fixed {
+                    name: new_name,
+                    doc: new_doc,
+                    size: new_size,
+                    ..
+                },
+            ) => {
+                self.diff_names(old_name, new_name);
+                self.diff_docs(old_doc, new_doc);
+                if old_size != new_size {
+                    self.push(ChangeKind::FixedSizeChanged {
+                        old: *old_size,
+                        new: *new_size,
+                    });
+                }
+            }
+            (
+                Schema::Decimal {
+                    precision: old_precision,
+                    scale: old_scale,
+                    inner: old_inner,
+                },
+                Schema::Decimal {
+                    precision: new_precision,
+                    scale: new_scale,
+                    inner: new_inner,
+                },
+            ) => {
+                if old_precision != new_precision {
+                    self.push(ChangeKind::DecimalPrecisionChanged {
+                        old: *old_precision,
+                        new: *new_precision,
+                    });
+                }
+                if old_scale != new_scale {
+                    self.push(ChangeKind::DecimalScaleChanged {
+                        old: *old_scale,
+                        new: *new_scale,
+                    });
+                }
+                self.diff(old_inner, new_inner);
+            }
+            // A uuid is either a string or a fixed.
+            (Schema::Uuid { inner: old_inner }, Schema::Uuid { inner: new_inner }) => {
+                self.diff(old_inner, new_inner)
+            }
+            (Schema::Array(old_items), Schema::Array(new_items)) => {
+                self.enter(Some("items".to_string()), Some("items".to_string()));
+                self.diff(old_items, new_items);
+                self.leave(true, true);
+            }
+            (Schema::Map(old_values), Schema::Map(new_values)) => {
+                self.enter(Some("values".to_string()), Some("values".to_string()));
+                self.diff(old_values, new_values);
+                self.leave(true, true);
+            }
+            (Schema::Union(old_union), Schema::Union(new_union)) => {
+                self.diff_unions(old_union, new_union)
+            }
+            _ => (),
+        }
+    }
+
+    fn diff_fields(
+        &mut self,
+        old_fields: &'s [RecordField],
+        old_lookup: &HashMap<String, usize>,
+        new_fields: &'s [RecordField],
+        new_lookup: &HashMap<String, usize>,
+    ) {
+        // The old field a new field is matched with, by name or else by one of its aliases.
+        let matching = |new_field: &RecordField| {
+            old_lookup
+                .get(&new_field.name)
+                .or_else(|| {
+                    new_field
+                        .aliases
+                        .iter()
+                        .flatten()
+                        .filter(|alias| !new_lookup.contains_key(*alias))
+                        .find_map(|alias| old_lookup.get(alias))
+                })
+                .map(|&i| &old_fields[i])
+        };
+        let matched = new_fields
+            .iter()
+            .filter_map(matching)
+            .map(|old_field| old_field.name.as_str())
+            .collect::<HashSet<_>>();
+
+        for old_field in old_fields {
+            if !matched.contains(old_field.name.as_str()) {
+                self.enter(Some("fields".to_string()), None);
+                self.enter(Some(old_field.name.clone()), None);
+                self.push(ChangeKind::FieldRemoved {
+                    name: old_field.name.clone(),
+                    had_default: old_field.default.is_some(),
+                });
+                self.leave(true, false);
+                self.leave(true, false);
+            }
+        }
+        for new_field in new_fields {
+            let old_field = match matching(new_field) {
+                Some(old_field) => old_field,
+                None => {
+                    self.enter(None, Some("fields".to_string()));
+                    self.enter(None, Some(new_field.name.clone()));
+                    self.push(ChangeKind::FieldAdded {
+                        name: new_field.name.clone(),
+                        has_default: new_field.default.is_some(),
+                    });
+                    self.leave(false, true);
+                    self.leave(false, true);
+                    continue;
+                }
+            };
+            self.enter(Some("fields".to_string()), Some("fields".to_string()));
+            self.enter(Some(old_field.name.clone()), Some(new_field.name.clone()));
+            if old_field.name != new_field.name {
+                self.push(ChangeKind::FieldRenamed {
+                    old: old_field.name.clone(),
+                    new: new_field.name.clone(),
+                });
+            }
+            self.diff_docs(&old_field.doc, &new_field.doc);
+            if old_field.default != new_field.default {
+                self.push(ChangeKind::DefaultChanged {
+                    old: old_field.default.clone(),
+                    new: new_field.default.clone(),
+                });
+            }
+            self.diff(&old_field.schema, &new_field.schema);
+            self.leave(true, true);
+            self.leave(true, true);
+        }
+    }
+
+    fn diff_unions(&mut self, old_union: &'s UnionSchema, new_union: &'s UnionSchema) {
+        let old_keys = old_union
+            .variants()
+            .iter()
+            .map(|variant| branch_key(variant, &mut self.old_names))
+            .collect::<Vec<_>>();
+        let new_keys = new_union
+            .variants()
+            .iter()
+            .map(|variant| branch_key(variant, &mut self.new_names))
+            .collect::<Vec<_>>();
+
+        for (i, key) in old_keys.iter().enumerate() {
+            if !new_keys.contains(key) {
+                self.enter(Some(i.to_string()), None);
+                self.push(ChangeKind::UnionBranchRemoved {
+                    branch: key.clone(),
+                });
+                self.leave(true, false);
+            }
+        }
+        for (i, key) in new_keys.iter().enumerate() {
+            match old_keys.iter().position(|old_key| old_key == key) {
+                Some(j) => {
+                    self.enter(Some(j.to_string()), Some(i.to_string()));
+                    self.diff(&old_union.variants()[j], &new_union.variants()[i]);
+                    self.leave(true, true);
+                }
+                None => {
+                    self.enter(None, Some(i.to_string()));
+                    self.push(ChangeKind::UnionBranchAdded {
+                        branch: key.clone(),
+                    });
+                    self.leave(false, true);
+                }
+            }
+        }
+    }
+
+    fn diff_names(&mut self, old: &Name, new: &Name) {
+        let old_fullname = old.fullname(None);
+        let new_fullname = new.fullname(None);
+        let (old_namespace, old_name) = split_fullname(&old_fullname);
+        let (new_namespace, new_name) = split_fullname(&new_fullname);
+        if old_name != new_name {
+            self.push(ChangeKind::NameChanged {
+                old: old_name.to_string(),
+                new: new_name.to_string(),
+            });
+        }
+        if old_namespace != new_namespace {
+            self.push(ChangeKind::NamespaceChanged {
+                old: old_namespace.map(String::from),
+                new: new_namespace.map(String::from),
+            });
+        }
+    }
+
+    fn diff_docs(&mut self, old: &Option<String>, new: &Option<String>) {
+        if old != new {
+            self.push(ChangeKind::DocChanged {
+                old: old.clone(),
+                new: new.clone(),
+            });
+        }
+    }
+
+    /// Descend into the given segments of the paths of the old and new schemas, for the sides
+    /// which have them.
+    fn enter(&mut self, old: Option<String>, new: Option<String>) {
+        self.old_path.extend(old);
+        self.new_path.extend(new);
+    }
+
+    /// Undo `enter` for the given sides.
+    fn leave(&mut self, old: bool, new: bool) {
+        if old {
+            self.old_path.pop();
+        }
+        if new {
+            self.new_path.pop();
+        }
+    }
+
+    fn push(&mut self, kind: ChangeKind) {
+        // Additions are not in the old schema, and removals not in the new one.
+        let (in_old, in_new) = match kind {
+            ChangeKind::FieldAdded { .. } | ChangeKind::UnionBranchAdded { .. } => (false, true),
+            ChangeKind::FieldRemoved { .. } | ChangeKind::UnionBranchRemoved { .. } => {
+                (true, false)
+            }
+            _ => (true, true),
+        };
+        let format_path = |path: &[String]| format!("/{}", path.join("/"));
+        self.changes.push(SchemaChange {
+            old_path: if in_old {
+                Some(format_path(&self.old_path))
+            } else {
+                None
+            },
+            new_path: if in_new {
+                Some(format_path(&self.new_path))
+            } else {
+                None
+            },
+            kind,
+        });
+    }
+}
+
+/// The key a union branch is matched by: its fullname for named types, or its type otherwise.
+fn branch_key<'s>(variant: &'s Schema, names: &mut Names<'s>) -> String {
+    match names.resolve(variant).unwrap_or(variant) {
+        Schema::Record { name, .. }
+        | Schema::Enum { name, .. }
+        | Schema::Fixed { name, .. }
+        | Schema::Ref { name } => name.fullname(None),
+        schema => format!("{:?}", SchemaKind::from(schema)),
+    }
+}
+
+/// Split a fullname into its namespace, if any, and its name.
+fn split_fullname(fullname: &str) -> (Option<&str>, &str) {
+    match fullname.rfind('.') {
+        Some(position) => (Some(&fullname[..position]), &fullname[position + 1..]),
+        None => (None, fullname),
+    }
+}
+
+fn address(schema: &Schema) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    ptr::hash(schema, &mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changes(old: &str, new: &str) -> Vec<(Option<String>, Option<String>, ChangeKind)> {
+        diff(
+            &Schema::parse_str(old).unwrap(),
+            &Schema::parse_str(new).unwrap(),
+        )
+        .into_iter()
+        .map(|change| (change.old_path, change.new_path, change.kind))
+        .collect()
+    }
+
+    fn at(path: &str) -> Option<String> {
+        Some(path.to_string())
+    }
+
+    #[test]
+    fn test_same_schema() {
+        let schema = r#"
+        {"type": "record", "name": "Node", "fields": [
+            {"name": "value", "type": "long"},
+            {"name": "next", "type": ["null", "Node"]}
+        ]}
+        "#;
+        assert!(changes(schema, schema).is_empty());
+    }
+
+    #[test]
+    fn test_record_changes() {
+        let old = r#"
+        {"type": "record", "name": "User", "namespace": "com.a", "fields": [
+            {"name": "id", "type": "int"},
+            {"name": "mail", "type": "string"},
+            {"name": "age", "type": "int", "default": 0},
+            {"name": "address", "type": {"type": "record", "name": "Address", "fields": [
+                {"name": "street", "type": "string", "doc": "Street"}
+            ]}}
+        ]}
+        "#;
+        let new = r#"
+        {"type": "record", "name": "User", "namespace": "com.b", "doc": "A user", "fields": [
+            {"name": "id", "type": "long"},
+            {"name": "email", "type": "string", "aliases": ["mail"]},
+            {"name": "address", "type": {"type": "record", "name": "Address", "fields": [
+                {"name": "street", "type": "bytes"},
+                {"name": "zip", "type": "string", "default": ""}
+            ]}},
+            {"name": "name", "type": "string"}
+        ]}
+        "#;
+
+        assert_eq!(
+            changes(old, new),
+            vec![
+                (
+                    at("/"),
+                    at("/"),
+                    ChangeKind::NamespaceChanged {
+                        old: Some("com.a".to_string()),
+                        new: Some("com.b".to_string())
+                    }
+                ),
+                (
+                    at("/"),
+                    at("/"),
+                    ChangeKind::DocChanged {
+                        old: None,
+                        new: Some("A user".to_string())
+                    }
+                ),
+                (
+                    at("/fields/age"),
+                    None,
+                    ChangeKind::FieldRemoved {
+                        name: "age".to_string(),
+                        had_default: true
+                    }
+                ),
+                (
+                    at("/fields/id"),
+                    at("/fields/id"),
+                    ChangeKind::TypePromoted {
+                        old: SchemaKind::Int,
+                        new: SchemaKind::Long
+                    }
+                ),
+                (
+                    at("/fields/mail"),
+                    at("/fields/email"),
+                    ChangeKind::FieldRenamed {
+                        old: "mail".to_string(),
+                        new: "email".to_string()
+                    }
+                ),
+                (
+                    at("/fields/address"),
+                    at("/fields/address"),
+                    ChangeKind::NamespaceChanged {
+                        old: Some("com.a".to_string()),
+                        new: Some("com.b".to_string())
+                    }
+                ),
+                (
+                    at("/fields/address/fields/street"),
+                    at("/fields/address/fields/street"),
+                    ChangeKind::DocChanged {
+                        old: Some("Street".to_string()),
+                        new: None
+                    }
+                ),
+                (
+                    at("/fields/address/fields/street"),
+                    at("/fields/address/fields/street"),
+                    ChangeKind::TypePromoted {
+                        old: SchemaKind::String,
+                        new: SchemaKind::Bytes
+                    }
+                ),
+                (
+                    None,
+                    at("/fields/address/fields/zip"),
+                    ChangeKind::FieldAdded {
+                        name: "zip".to_string(),
+                        has_default: true
+                    }
+                ),
+                (
+                    None,
+                    at("/fields/name"),
+                    ChangeKind::FieldAdded {
+                        name: "name".to_string(),
+                        has_default: false
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enum_fixed_and_union_changes() {
+        let old = r#"
+        {"type": "record", "name": "R", "fields": [
+            {"name": "suit", "type": {"type": "enum", "name": "Suit", "symbols": ["SPADES", "HEARTS"]}},
+            {"name": "hash", "type": {"type": "fixed", "name": "Hash", "size": 16}},
+            {"name": "value", "type": ["null", "string", {"type": "fixed", "name": "Raw", "size": 4}]},
+            {"name": "tags", "type": {"type": "map", "values": "int"}}
+        ]}
+        "#;
+        let new = r#"
+        {"type": "record", "name": "R", "fields": [
+            {"name": "suit", "type": {"type": "enum", "name": "Suit", "symbols": ["HEARTS", "CLUBS"]}},
+            {"name": "hash", "type": {"type": "fixed", "name": "Hash", "size": 32}},
+            {"name": "value", "type": ["null", {"type": "fixed", "name": "Raw", "size": 8}, "long"]},
+            {"name": "tags", "type": {"type": "map", "values": "string"}}
+        ]}
+        "#;
+
+        assert_eq!(
+            changes(old, new),
+            vec![
+                (
+                    at("/fields/suit"),
+                    at("/fields/suit"),
+                    ChangeKind::EnumSymbolRemoved {
+                        symbol: "SPADES".to_string()
+                    }
+                ),
+                (
+                    at("/fields/suit"),
+                    at("/fields/suit"),
+                    ChangeKind::EnumSymbolAdded {
+                        symbol: "CLUBS".to_string()
+                    }
+                ),
+                (
+                    at("/fields/hash"),
+                    at("/fields/hash"),
+                    ChangeKind::FixedSizeChanged { old: 16, new: 32 }
+                ),
+                (
+                    at("/fields/value/1"),
+                    None,
+                    ChangeKind::UnionBranchRemoved {
+                        branch: "String".to_string()
+                    }
+                ),
+                (
+                    at("/fields/value/2"),
+                    at("/fields/value/1"),
+                    ChangeKind::FixedSizeChanged { old: 4, new: 8 }
+                ),
+                (
+                    None,
+                    at("/fields/value/2"),
+                    ChangeKind::UnionBranchAdded {
+                        branch: "Long".to_string()
+                    }
+                ),
+                (
+                    at("/fields/tags/values"),
+                    at("/fields/tags/values"),
+                    ChangeKind::TypeChanged {
+                        old: SchemaKind::Int,
+                        new: SchemaKind::String
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_logical_type_changes() {
+        let old = r#"
+        {"type": "record", "name": "R", "fields": [
+            {"name": "price", "type": {"type": "bytes", "logicalType": "decimal", "precision": 9, "scale": 2}},
+            {"name": "amount", "type": {"type": "bytes", "logicalType": "decimal", "precision": 9, "scale": 2}},
+            {"name": "id", "type": {"type": "string", "logicalType": "uuid"}}
+        ]}
+        "#;
+        let new = r#"
+        {"type": "record", "name": "R", "fields": [
+            {"name": "price", "type": {"type": "bytes", "logicalType": "decimal", "precision": 12, "scale": 4}},
+            {"name": "amount", "type": {"type": {"type": "fixed", "name": "Amount", "size": 8}, "logicalType": "decimal", "precision": 9, "scale": 2}},
+            {"name": "id", "type": {"type": "fixed", "name": "Id", "size": 16, "logicalType": "uuid"}}
+        ]}
+        "#;
+
+        assert_eq!(
+            changes(old, new),
+            vec![
+                (
+                    at("/fields/price"),
+                    at("/fields/price"),
+                    ChangeKind::DecimalPrecisionChanged { old: 9, new: 12 }
+                ),
+                (
+                    at("/fields/price"),
+                    at("/fields/price"),
+                    ChangeKind::DecimalScaleChanged { old: 2, new: 4 }
+                ),
+                (
+                    at("/fields/amount"),
+                    at("/fields/amount"),
+                    ChangeKind::TypeChanged {
+                        old: SchemaKind::Bytes,
+                        new: SchemaKind::Fixed
+                    }
+                ),
+                (
+                    at("/fields/id"),
+                    at("/fields/id"),
+                    ChangeKind::TypeChanged {
+                        old: SchemaKind::String,
+                        new: SchemaKind::Fixed
+                    }
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,532 @@
+//! Logic for linting schemas for patterns which make their evolution risky
+use crate::{
+    schema::{Name, Names, RecordField, RecordFieldOrder, Schema},
+    types::max_prec_for_len,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+lazy_static! {
+    static ref PORTABLE_NAME: Regex = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
+}
+
+/// Reserved words of common target languages of code generation (Java, C#, C++, Python, Go and
+/// Rust), which cannot be used as names in all of them.
+const RESERVED_WORDS: &[&str] = &[
+    "False",
+    "None",
+    "True",
+    "abstract",
+    "as",
+    "assert",
+    "async",
+    "await",
+    "boolean",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "def",
+    "default",
+    "del",
+    "delete",
+    "do",
+    "double",
+    "elif",
+    "else",
+    "enum",
+    "except",
+    "extends",
+    "extern",
+    "false",
+    "final",
+    "finally",
+    "float",
+    "fn",
+    "for",
+    "func",
+    "global",
+    "goto",
+    "if",
+    "impl",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "int",
+    "interface",
+    "is",
+    "lambda",
+    "let",
+    "long",
+    "loop",
+    "match",
+    "mod",
+    "mut",
+    "namespace",
+    "native",
+    "new",
+    "nonlocal",
+    "not",
+    "null",
+    "operator",
+    "package",
+    "pass",
+    "private",
+    "protected",
+    "pub",
+    "public",
+    "raise",
+    "ref",
+    "return",
+    "self",
+    "short",
+    "static",
+    "struct",
+    "super",
+    "switch",
+    "synchronized",
+    "this",
+    "throw",
+    "throws",
+    "trait",
+    "transient",
+    "true",
+    "try",
+    "type",
+    "typeof",
+    "unsafe",
+    "use",
+    "using",
+    "var",
+    "virtual",
+    "void",
+    "volatile",
+    "where",
+    "while",
+    "with",
+    "yield",
+];
+
+/// How risky a pattern flagged by the linter is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// A pattern which is worth knowing about, but rarely a problem.
+    Info,
+    /// A pattern which is likely to make the evolution of the schema harder.
+    Warning,
+    /// A pattern which is invalid, or breaks reading or writing data.
+    Error,
+}
+
+/// The patterns flagged by the linter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LintRule {
+    /// A field has no default value, so that it can be neither added nor removed compatibly.
+    FieldWithoutDefault,
+    /// The default value of a field of a union type does not match the first branch of the
+    /// union, as the specification requires.
+    UnionDefaultMismatch,
+    /// An enum has no default symbol, so that symbols cannot be added to it compatibly.
+    EnumWithoutDefault,
+    /// A named type is in the null namespace.
+    MissingNamespace,
+    /// A field of a type which cannot be sorted (a map) is sorted, its `order` not being `ignore`
+    /// as the specification requires.
+    OrderOnUnsortableType,
+    /// The precision of a decimal cannot be held by its fixed size, or is lower than its scale.
+    DecimalPrecisionMismatch,
+    /// A name is not valid in, or is reserved by, common target languages of code generation.
+    NonPortableName,
+}
+
+impl LintRule {
+    /// The severity of the pattern flagged by this rule.
+    pub fn severity(self) -> Severity {
+        match self {
+            LintRule::FieldWithoutDefault => Severity::Warning,
+            LintRule::UnionDefaultMismatch => Severity::Error,
+            LintRule::EnumWithoutDefault => Severity::Warning,
+            LintRule::MissingNamespace => Severity::Info,
+            LintRule::OrderOnUnsortableType => Severity::Warning,
+            LintRule::DecimalPrecisionMismatch => Severity::Error,
+            LintRule::NonPortableName => Severity::Warning,
+        }
+    }
+}
+
+/// A risky pattern found by the linter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lint {
+    /// Location of the pattern within the schema, as a path similar to a JSON pointer, e.g.
+    /// `/fields/address/fields/zip`. Branches of unions are designated by their index.
+    pub path: String,
+    /// The rule which flagged the pattern.
+    pub rule: LintRule,
+    /// The severity of the rule.
+    pub severity: Severity,
+    /// A description of the pattern found.
+    pub message: String,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} {}: {}", self.severity, self.path, self.message)
+    }
+}
+
+/// Lint the given schema, and return every risky pattern found in it. Named types are linted
+/// where they are defined.
+pub fn lint(schema: &Schema) -> Vec<Lint> {
+    let mut linter = Linter {
+        names: Names::new(schema),
+        visited: HashSet::new(),
+        path: Vec::new(),
+        lints: Vec::new(),
+    };
+    linter.lint(schema);
+    linter.lints
+}
+
+struct Linter<'s> {
+    names: Names<'s>,
+    visited: HashSet<String>,
+    path: Vec<String>,
+    lints: Vec<Lint>,
+}
+
+impl<'s> Linter<'s> {
+    fn lint(&mut self, schema: &'s Schema) {
+        match schema {
+            Schema::Record { name, fields, .. } if self.lint_name(name) => {
+                self.lint_fields(fields);
+            }
+            Schema::Enum {
+                name,
+                symbols,
+                default,
+                ..
+            } if self.lint_name(name) => {
+                if default.is_none() {
+                    self.push(
+                        LintRule::EnumWithoutDefault,
+                        format!("enum {} has no default symbol", name.fullname(None)),
+                    );
+                }
+                for symbol in symbols {
+                    self.lint_portable(symbol, "enum symbol");
+                }
+            }
+            Schema::Fixed { name, .. } => {
+                self.lint_name(name);
+            }
+            Schema::Decimal {
+                precision,
+                scale,
+                inner,
+            } => {
+                if scale > precision {
+                    self.push(
+                        LintRule::DecimalPrecisionMismatch,
+                        format!(
+                            "decimal scale {} is greater than its precision {}",
+                            scale, precision
+                        ),
+                    );
+                }
+                if let Ok(Schema::Fixed { size, .. }) = self.names.resolve_fixed(inner) {
+                    let size = *size;
+                    if max_prec_for_len(size).map_or(true, |max| max < *precision) {
+                        self.push(
+                            LintRule::DecimalPrecisionMismatch,
+                            format!(
+                                "decimal precision {} cannot be held by {} bytes",
+                                precision, size
+                            ),
+                        );
+                    }
+                }
+                self.lint(inner);
+            }
+            Schema::Array(items) => {
+                self.path.push("items".to_string());
+                self.lint(items);
+                self.path.pop();
+            }
+            Schema::Map(values) => {
+                self.path.push("values".to_string());
+                self.lint(values);
+                self.path.pop();
+            }
+            Schema::Union(union) => {
+                for (i, variant) in union.variants().iter().enumerate() {
+                    self.path.push(i.to_string());
+                    self.lint(variant);
+                    self.path.pop();
+                }
+            }
+            Schema::Custom { inner, .. } | Schema::Attributed { inner, .. } => self.lint(inner),
+            _ => (),
+        }
+    }
+
+    /// Lint the name of a named type, and return whether it is linted for the first time.
+    fn lint_name(&mut self, name: &Name) -> bool {
+        let fullname = name.fullname(None);
+        if !self.visited.insert(fullname.clone()) {
+            return false;
+        }
+        let mut parts = fullname.split('.').collect::<Vec<_>>();
+        let name = parts.pop().unwrap_or_default();
+        if parts.is_empty() {
+            self.push(
+                LintRule::MissingNamespace,
+                format!("{} is in the null namespace", name),
+            );
+        }
+        for part in parts {
+            self.lint_portable(part, "namespace part");
+        }
+        self.lint_portable(name, "name");
+        true
+    }
+
+    fn lint_fields(&mut self, fields: &'s [RecordField]) {
+        // Some languages are not case sensitive, or change the case of names.
+        let mut lowercase_names = HashMap::new();
+        for field in fields {
+            self.path.push("fields".to_string());
+            self.path.push(field.name.clone());
+
+            self.lint_portable(&field.name, "field name");
+            if let Some(other) = lowercase_names.insert(field.name.to_lowercase(), &field.name) {
+                self.push(
+                    LintRule::NonPortableName,
+                    format!(
+                        "field name {} only differs from {} by case",
+                        field.name, other
+                    ),
+                );
+            }
+
+            let schema = self.names.resolve(&field.schema).unwrap_or(&field.schema);
+            match field.default {
+                None => self.push(
+                    LintRule::FieldWithoutDefault,
+                    format!("field {} has no default value", field.name),
+                ),
+                Some(ref default) => {
+                    if let Schema::Union(union) = schema {
+                        let first = union
+                            .variants()
+                            .first()
+                            .map(|first| self.names.resolve(first).unwrap_or(first));
+                        if !matches!(first, Some(first) if default_matches(first, default)) {
+                            self.push(
+                                LintRule::UnionDefaultMismatch,
+                                format!(
+                                    "default value {} of field {} does not match the first branch of its union",
+                                    default, field.name
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+
+            let unsortable = match schema {
+                Schema::Map(_) => true,
+                Schema::Union(union) => union
+                    .variants()
+                    .iter()
+                    .any(|variant| matches!(self.names.resolve(variant), Ok(Schema::Map(_)))),
+                _ => false,
+            };
+            if unsortable && field.order != RecordFieldOrder::Ignore {
+                let order: &str = (&field.order).into();
+                self.push(
+                    LintRule::OrderOnUnsortableType,
+                    format!(
+                        "field {} is sorted in {} order, but maps cannot be sorted",
+                        field.name, order
+                    ),
+                );
+            }
+
+            self.lint(&field.schema);
+            self.path.pop();
+            self.path.pop();
+        }
+    }
+
+    fn lint_portable(&mut self, name: &str, what: &str) {
+        if !PORTABLE_NAME.is_match(name) {
+            self.push(
+                LintRule::NonPortableName,
+                format!("{} {} is not a valid name in every language", what, name),
+            );
+        } else if RESERVED_WORDS.contains(&name) {
+            self.push(
+                LintRule::NonPortableName,
+                format!("{} {} is reserved in some languages", what, name),
+            );
+        }
+    }
+
+    fn push(&mut self, rule: LintRule, message: String) {
+        self.lints.push(Lint {
+            path: format!("/{}", self.path.join("/")),
+            rule,
+            severity: rule.severity(),
+            message,
+        });
+    }
+}
+
+/// Return whether a JSON default value is of the JSON type the given schema expects.
+fn default_matches(schema: &Schema, default: &Value) -> bool {
+    match (schema, default) {
+        (Schema::Null, Value::Null) => true,
+        (Schema::Boolean, Value::Bool(_)) => true,
+        (Schema::Int, Value::Number(n))
+        | (Schema::Long, Value::Number(n))
+        | (Schema::Date, Value::Number(n))
+        | (Schema::TimeMillis, Value::Number(n))
+        | (Schema::TimeMicros, Value::Number(n))
+        | (Schema::TimestampMillis, Value::Number(n))
+        | (Schema::TimestampMicros, Value::Number(n))
+        | (Schema::LocalTimestampMillis, Value::Number(n))
+        | (Schema::LocalTimestampMicros, Value::Number(n))
+        | (Schema::TimestampNanos, Value::Number(n))
+        | (Schema::LocalTimestampNanos, Value::Number(n)) => n.is_i64() || n.is_u64(),
+        (Schema::Float, Value::Number(_)) | (Schema::Double, Value::Number(_)) => true,
+        (Schema::Bytes, Value::String(_))
+        | (Schema::String, Value::String(_))
+        | (Schema::Uuid { .. }, Value::String(_))
+        | (Schema::Fixed { .. }, Value::String(_))
+        | (Schema::Decimal { .. }, Value::String(_))
+        | (Schema::BigDecimal, Value::String(_))
+        | (Schema::Duration, Value::String(_)) => true,
+        (Schema::Enum { symbols, .. }, Value::String(s)) => symbols.contains(s),
+        (Schema::Array(_), Value::Array(_)) => true,
+        (Schema::Map(_), Value::Object(_)) | (Schema::Record { .. }, Value::Object(_)) => true,
+        (Schema::Custom { inner, .. }, default) => default_matches(inner, default),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lints(schema: &str) -> Vec<(String, LintRule)> {
+        lint(&Schema::parse_str(schema).unwrap())
+            .into_iter()
+            .map(|lint| (lint.path, lint.rule))
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_schema() {
+        let schema = r#"
+        {"type": "record", "name": "Node", "namespace": "com.example", "fields": [
+            {"name": "value", "type": "long", "default": 0},
+            {"name": "next", "type": ["null", "Node"], "default": null},
+            {"name": "color", "type": {"type": "enum", "name": "Color", "symbols": ["RED", "BLUE"], "default": "RED"}, "default": "RED"},
+            {"name": "amount", "type": {"type": {"type": "fixed", "name": "Amount", "size": 8}, "logicalType": "decimal", "precision": 18, "scale": 2}, "default": "\u0000\u0000\u0000\u0000\u0000\u0000\u0000\u0000"}
+        ]}
+        "#;
+        assert!(lints(schema).is_empty());
+    }
+
+    #[test]
+    fn test_record_lints() {
+        let schema = r#"
+        {"type": "record", "name": "User", "fields": [
+            {"name": "id", "type": "int"},
+            {"name": "Id", "type": "int", "default": 0},
+            {"name": "type", "type": ["null", "string"], "default": null},
+            {"name": "tags", "type": {"type": "map", "values": "string"}, "default": {}, "order": "descending"},
+            {"name": "color", "type": {"type": "enum", "name": "com.example.Color", "symbols": ["RED"]}, "default": "RED"},
+            {"name": "amount", "type": {"type": {"type": "fixed", "name": "com.example.Amount", "size": 2}, "logicalType": "decimal", "precision": 5, "scale": 2}, "default": "\u0000\u0000"}
+        ]}
+        "#;
+        assert_eq!(
+            lints(schema),
+            vec![
+                ("/".to_string(), LintRule::MissingNamespace),
+                ("/fields/id".to_string(), LintRule::FieldWithoutDefault),
+                ("/fields/Id".to_string(), LintRule::NonPortableName),
+                ("/fields/type".to_string(), LintRule::NonPortableName),
+                ("/fields/tags".to_string(), LintRule::OrderOnUnsortableType),
+                ("/fields/color".to_string(), LintRule::EnumWithoutDefault),
+                (
+                    "/fields/amount".to_string(),
+                    LintRule::DecimalPrecisionMismatch
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_order_on_unsortable_type() {
+        let schema = r#"
+        {"type": "record", "name": "com.example.User", "fields": [
+            {"name": "a", "type": {"type": "map", "values": "string"}, "default": {}, "order": "ascending"},
+            {"name": "b", "type": {"type": "map", "values": "string"}, "default": {}},
+            {"name": "c", "type": ["null", {"type": "map", "values": "string"}], "default": null, "order": "descending"},
+            {"name": "d", "type": {"type": "map", "values": "string"}, "default": {}, "order": "ignore"}
+        ]}
+        "#;
+        // Fields are sorted in ascending order unless told otherwise.
+        assert_eq!(
+            lints(schema),
+            vec![
+                ("/fields/a".to_string(), LintRule::OrderOnUnsortableType),
+                ("/fields/b".to_string(), LintRule::OrderOnUnsortableType),
+                ("/fields/c".to_string(), LintRule::OrderOnUnsortableType),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_union_default_mismatch() {
+        // Invalid defaults are rejected by the parser, so only schemas built by hand have them.
+        let mut schema = Schema::parse_str(
+            r#"{"type": "record", "name": "com.example.User", "fields": [
+                {"name": "name", "type": ["null", "string"], "default": null}
+            ]}"#,
+        )
+        .unwrap();
+        if let Schema::Record { ref mut fields, .. } = schema {
+            fields[0].default = Some(Value::String("Joe".to_string()));
+        }
+        let lints = lint(&schema);
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].path, "/fields/name");
+        assert_eq!(lints[0].rule, LintRule::UnionDefaultMismatch);
+    }
+
+    #[test]
+    fn test_severity() {
+        let lints =
+            lint(&Schema::parse_str(r#"{"type": "fixed", "name": "switch", "size": 1}"#).unwrap());
+        assert_eq!(lints.len(), 2);
+        assert_eq!(lints[0].severity, Severity::Info);
+        assert_eq!(lints[1].severity, Severity::Warning);
+        assert_eq!(
+            lints[1].to_string(),
+            "Warning /: name switch is reserved in some languages"
+        );
+        assert!(Severity::Error > Severity::Warning);
+    }
+}
@@ -0,0 +1,352 @@
+//! Logic for loading collections of schemas which refer to each other from `.avsc` files
+use crate::{
+    schema::{locate_error, parse_defaults, validate, Name, Parser, ParsingMode, Schema},
+    AvroResult, Error,
+};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Loads named schemas from `.avsc` files, directories of them and already parsed schemas.
+///
+/// The schemas can refer to the named types defined by each other by fullname, whatever the
+/// file they are defined in, be they the type of the file or nested within it. References are resolved when the schemas are loaded, and cyclic
+/// references between files are reported as `Error::SchemaCycle`. Errors in a file are reported
+/// as `Error::LoadSchemaFile`, along with its path.
+///
+/// ```no_run
+/// use avro_rs::schema_loader::SchemaLoader;
+///
+/// let mut loader = SchemaLoader::new();
+/// loader.add_path("schemas/").unwrap();
+/// let schemas = loader.load().unwrap();
+/// let user = &schemas["com.example.User"];
+/// ```
+#[derive(Default)]
+pub struct SchemaLoader {
+    mode: ParsingMode,
+    files: HashMap<String, SchemaFile>,
+    schemas: HashMap<String, Schema>,
+}
+
+/// A JSON schema read from a file.
+struct SchemaFile {
+    path: PathBuf,
+    input: String,
+    value: Value,
+}
+
+impl SchemaLoader {
+    /// Create a loader without any schema, which parses schemas in `ParsingMode::Strict`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the `ParsingMode` in which the files are parsed.
+    pub fn set_parsing_mode(&mut self, mode: ParsingMode) {
+        self.mode = mode;
+    }
+
+    /// Add the schema of a `.avsc` file, or the schemas of the `.avsc` files found in a directory
+    /// and its subdirectories.
+    pub fn add_path<P: AsRef<Path>>(&mut self, path: P) -> AvroResult<()> {
+        let path = path.as_ref();
+        if path.is_dir() {
+            let mut entries = fs::read_dir(path)
+                .and_then(|entries| {
+                    entries
+                        .map(|entry| entry.map(|entry| entry.path()))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .map_err(|e| Error::ReadSchemaFile(e, path.to_path_buf()))?;
+            // Sorted, for the errors not to depend on the order of the entries.
+            entries.sort();
+            for entry in entries {
+                if entry.is_dir() || entry.extension() == Some("avsc".as_ref()) {
+                    self.add_path(entry)?;
+                }
+            }
+            Ok(())
+        } else {
+            let input = fs::read_to_string(path)
+                .map_err(|e| Error::ReadSchemaFile(e, path.to_path_buf()))?;
+            self.add_file(path, input)
+                .map_err(|e| Error::LoadSchemaFile(Box::new(e), path.to_path_buf()))
+        }
+    }
+
+    fn add_file(&mut self, path: &Path, input: String) -> AvroResult<()> {
+        let value: Value = serde_json::from_str(&input).map_err(Error::ParseSchemaJson)?;
+        let fullname = match value {
+            Value::Object(ref complex) => Name::parse(complex)?.fullname(None),
+            _ => return Err(Error::GetNameField),
+        };
+        if self.mode == ParsingMode::Strict {
+            validate(&value).map_err(|e| locate_error(e, &input))?;
+        }
+        self.check_collision(&fullname)?;
+        self.files.insert(
+            fullname,
+            SchemaFile {
+                path: path.to_path_buf(),
+                input,
+                value,
+            },
+        );
+        Ok(())
+    }
+
+    /// Add an already parsed named schema, which the files can refer to.
+    pub fn add_schema(&mut self, schema: Schema) -> AvroResult<()> {
+        let fullname = match schema {
+            Schema::Record { ref name, .. }
+            | Schema::Enum { ref name, .. }
+            | Schema::Fixed { ref name, .. } => name.fullname(None),
+            _ => return Err(Error::GetNameField),
+        };
+        self.check_collision(&fullname)?;
+        self.schemas.insert(fullname, schema);
+        Ok(())
+    }
+
+    fn check_collision(&self, fullname: &str) -> AvroResult<()> {
+        if self.files.contains_key(fullname) || self.schemas.contains_key(fullname) {
+            Err(Error::NameCollision(fullname.to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Parse the schemas of the files, resolving their references to each other, and return
+    /// every schema added, keyed by fullname.
+    pub fn load(self) -> AvroResult<BTreeMap<String, Schema>> {
+        let mode = self.mode;
+        let mut files = self.files;
+        let inputs = files
+            .iter()
+            .map(|(fullname, file)| (fullname.clone(), file.value.clone()))
+            .collect();
+        let mut parser = Parser::with_inputs(inputs, self.schemas);
+        if let Err(e) = parser.parse_list() {
+            return Err(
+                match parser.failed_input().and_then(|name| files.remove(name)) {
+                    Some(file) => {
+                        Error::LoadSchemaFile(Box::new(locate_error(e, &file.input)), file.path)
+                    }
+                    None => e,
+                },
+            );
+        }
+
+        let (parsed, record_paths) = parser.into_parsed();
+        parsed
+            .into_iter()
+            .map(|(fullname, schema)| match files.remove(&fullname) {
+                Some(file) => parse_defaults(schema, mode, &record_paths)
+                    .map(|schema| (fullname, schema))
+                    .map_err(|e| {
+                        Error::LoadSchemaFile(Box::new(locate_error(e, &file.input)), file.path)
+                    }),
+                None => Ok((fullname, schema)),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// A temporary directory of schema files, removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str, files: &[(&str, &str)]) -> Self {
+            let dir = env::temp_dir().join(format!("avro-rs-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            for (path, content) in files {
+                let path = dir.join(path);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, content).unwrap();
+            }
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_load_directory() {
+        let dir = TempDir::new(
+            "load",
+            &[
+                (
+                    "user.avsc",
+                    r#"{"type": "record", "name": "User", "namespace": "com.example", "fields": [
+                        {"name": "address", "type": "Address"},
+                        {"name": "id", "type": "com.example.common.Id"}
+                    ]}"#,
+                ),
+                (
+                    "nested/address.avsc",
+                    r#"{"type": "record", "name": "com.example.Address", "fields": [
+                        {"name": "country", "type": "com.example.common.Country"}
+                    ]}"#,
+                ),
+                (
+                    "nested/country.avsc",
+                    r#"{"type": "enum", "name": "com.example.common.Country", "symbols": ["FR"]}"#,
+                ),
+                ("README.md", "not a schema"),
+            ],
+        );
+        let id =
+            Schema::parse_str(r#"{"type": "fixed", "name": "com.example.common.Id", "size": 16}"#)
+                .unwrap();
+
+        let mut loader = SchemaLoader::new();
+        loader.add_path(&dir.0).unwrap();
+        loader.add_schema(id.clone()).unwrap();
+        let schemas = loader.load().unwrap();
+
+        assert_eq!(
+            schemas.keys().collect::<Vec<_>>(),
+            vec![
+                "com.example.Address",
+                "com.example.User",
+                "com.example.common.Country",
+                "com.example.common.Id",
+            ]
+        );
+        assert_eq!(schemas["com.example.common.Id"], id);
+        let expected = Schema::parse_list(&[
+            r#"{"type": "enum", "name": "com.example.common.Country", "symbols": ["FR"]}"#,
+            r#"{"type": "fixed", "name": "com.example.common.Id", "size": 16}"#,
+            r#"{"type": "record", "name": "com.example.Address", "fields": [
+                {"name": "country", "type": "com.example.common.Country"}
+            ]}"#,
+            r#"{"type": "record", "name": "User", "namespace": "com.example", "fields": [
+                {"name": "address", "type": "Address"},
+                {"name": "id", "type": "com.example.common.Id"}
+            ]}"#,
+        ])
+        .unwrap();
+        assert_eq!(schemas["com.example.User"], expected[3]);
+    }
+
+    #[test]
+    fn test_load_nested_reference() {
+        let dir = TempDir::new(
+            "nested",
+            &[
+                (
+                    "user.avsc",
+                    r#"{"type": "record", "name": "User", "namespace": "com.ex", "fields": [
+                        {"name": "address", "type": {"type": "record", "name": "Address", "fields": [
+                            {"name": "street", "type": "string"}
+                        ]}}
+                    ]}"#,
+                ),
+                (
+                    "order.avsc",
+                    r#"{"type": "record", "name": "com.ex.Order", "fields": [
+                        {"name": "address", "type": "com.ex.Address"},
+                        {"name": "id", "type": "com.ex.common.Id"}
+                    ]}"#,
+                ),
+            ],
+        );
+        // The fixed is defined within an already parsed schema.
+        let account = Schema::parse_str(
+            r#"{"type": "record", "name": "com.ex.common.Account", "fields": [
+                {"name": "id", "type": {"type": "fixed", "name": "Id", "size": 16}}
+            ]}"#,
+        )
+        .unwrap();
+
+        let mut loader = SchemaLoader::new();
+        loader.add_path(&dir.0).unwrap();
+        loader.add_schema(account).unwrap();
+        let schemas = loader.load().unwrap();
+
+        let expected = Schema::parse_str(
+            r#"{"type": "record", "name": "com.ex.Order", "fields": [
+                {"name": "address", "type": {"type": "record", "name": "Address", "fields": [
+                    {"name": "street", "type": "string"}
+                ]}},
+                {"name": "id", "type": {"type": "fixed", "name": "com.ex.common.Id", "size": 16}}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(schemas["com.ex.Order"], expected);
+        assert!(!schemas.contains_key("com.ex.Address"));
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let dir = TempDir::new(
+            "errors",
+            &[
+                (
+                    "a.avsc",
+                    r#"{"type": "record", "name": "A", "fields": [{"name": "b", "type": "B"}]}"#,
+                ),
+                (
+                    "b.avsc",
+                    r#"{"type": "record", "name": "B", "fields": [{"name": "a", "type": "A"}]}"#,
+                ),
+                (
+                    "c.avsc",
+                    r#"{"type": "record", "name": "C", "fields": [{"name": "x", "type": "X"}]}"#,
+                ),
+                ("d.avsc", r#"{"type": "fixed", "name": "A", "size": 1}"#),
+            ],
+        );
+
+        let mut loader = SchemaLoader::new();
+        loader.add_path(dir.0.join("a.avsc")).unwrap();
+        match loader.add_path(dir.0.join("d.avsc")) {
+            Err(Error::LoadSchemaFile(e, path)) => {
+                assert!(matches!(*e, Error::NameCollision(ref name) if name == "A"));
+                assert_eq!(path, dir.0.join("d.avsc"));
+            }
+            other => panic!("Expected a name collision, got {:?}", other),
+        }
+        loader.add_path(dir.0.join("b.avsc")).unwrap();
+        match loader.load() {
+            Err(Error::LoadSchemaFile(e, _)) => {
+                assert!(matches!(
+                    *e,
+                    Error::ParseSchemaAt { ref source, .. }
+                        if matches!(**source, Error::SchemaCycle(ref cycle) if cycle.len() == 3)
+                ))
+            }
+            other => panic!("Expected a cycle, got {:?}", other),
+        }
+
+        let mut loader = SchemaLoader::new();
+        loader.add_path(dir.0.join("c.avsc")).unwrap();
+        match loader.load() {
+            Err(Error::LoadSchemaFile(e, path)) => {
+                assert_eq!(path, dir.0.join("c.avsc"));
+                let location = e.schema_location().unwrap();
+                assert_eq!(location.path, "fields[0].type");
+                assert_eq!((location.line, location.column), (Some(1), Some(66)));
+            }
+            other => panic!("Expected an unknown type, got {:?}", other),
+        }
+
+        assert!(matches!(
+            SchemaLoader::new().add_path(dir.0.join("missing.avsc")),
+            Err(Error::ReadSchemaFile(_, _))
+        ));
+    }
+}
@@ -217,7 +217,7 @@ impl<'b> ser::Serializer for &'b mut Serializer {
             ),
             (
                 "value".to_owned(),
-                Value::Union(Box::new(value.serialize(self)?)),
+                Value::Union(None, Box::new(value.serialize(self)?)),
             ),
         ]))
     }
@@ -329,9 +329,10 @@ impl<'a> ser::SerializeSeq for SeqVariantSerializer<'a> {
     where
         T: Serialize,
     {
-        self.items.push(Value::Union(Box::new(
-            value.serialize(&mut Serializer::default())?,
-        )));
+        self.items.push(Value::Union(
+            None,
+            Box::new(value.serialize(&mut Serializer::default())?),
+        ));
         Ok(())
     }
 
@@ -452,7 +453,7 @@ impl<'a> ser::SerializeStructVariant for StructVariantSerializer<'a> {
             ),
             (
                 "value".to_owned(),
-                Value::Union(Box::new(Value::Record(self.fields))),
+                Value::Union(None, Box::new(Value::Record(self.fields))),
             ),
         ]))
     }
@@ -690,6 +691,32 @@ mod tests {
         assert_eq!(to_value(test_inner).unwrap(), expected_inner);
     }
 
+    #[test]
+    fn test_to_value_timestamp_nanos() {
+        #[derive(Serialize)]
+        struct Event {
+            at: i64,
+            local_at: i64,
+        }
+
+        // Timestamps are serialized as longs, which are resolved to the schema of the writer.
+        let schema = crate::Schema::parse_str(
+            r#"{"type": "record", "name": "Event", "fields": [
+                {"name": "at", "type": {"type": "long", "logicalType": "timestamp-nanos"}},
+                {"name": "local_at", "type": {"type": "long", "logicalType": "local-timestamp-nanos"}}
+            ]}"#,
+        )
+        .unwrap();
+        let value = to_value(Event { at: 1, local_at: 2 }).unwrap();
+        assert_eq!(
+            value.resolve(&schema).unwrap(),
+            Value::Record(vec![
+                ("at".to_owned(), Value::TimestampNanos(1)),
+                ("local_at".to_owned(), Value::LocalTimestampNanos(2)),
+            ])
+        );
+    }
+
     #[test]
     fn test_to_value_unit_enum() {
         let test = TestUnitExternalEnum {
@@ -759,7 +786,7 @@ mod tests {
                 ("type".to_owned(), Value::Enum(0, "Double".to_owned())),
                 (
                     "value".to_owned(),
-                    Value::Union(Box::new(Value::Double(64.0))),
+                    Value::Union(None, Box::new(Value::Double(64.0))),
                 ),
             ]),
         )]);
@@ -819,10 +846,13 @@ mod tests {
                 ("type".to_owned(), Value::Enum(0, "Val1".to_owned())),
                 (
                     "value".to_owned(),
-                    Value::Union(Box::new(Value::Record(vec![
-                        ("x".to_owned(), Value::Float(1.0)),
-                        ("y".to_owned(), Value::Float(2.0)),
-                    ]))),
+                    Value::Union(
+                        None,
+                        Box::new(Value::Record(vec![
+                            ("x".to_owned(), Value::Float(1.0)),
+                            ("y".to_owned(), Value::Float(2.0)),
+                        ])),
+                    ),
                 ),
             ]),
         )]);
@@ -929,9 +959,9 @@ mod tests {
                 (
                     "value".to_owned(),
                     Value::Array(vec![
-                        Value::Union(Box::new(Value::Float(1.0))),
-                        Value::Union(Box::new(Value::Float(2.0))),
-                        Value::Union(Box::new(Value::Float(3.0))),
+                        Value::Union(None, Box::new(Value::Float(1.0))),
+                        Value::Union(None, Box::new(Value::Float(2.0))),
+                        Value::Union(None, Box::new(Value::Float(3.0))),
                     ]),
                 ),
             ]),
@@ -1,20 +1,55 @@
 //! Logic handling the intermediate representation of Avro values.
 use crate::{
-    decimal::Decimal,
+    decimal::{BigDecimal, Decimal},
     duration::Duration,
-    schema::{Precision, RecordField, Scale, Schema, SchemaKind, UnionSchema},
+    schema::{Names, Precision, RecordField, Scale, Schema, SchemaKind, UnionSchema},
     AvroResult, Error,
 };
 use serde_json::{Number, Value as JsonValue};
-use std::{collections::HashMap, convert::TryFrom, hash::BuildHasher, str::FromStr, u8};
+use std::{
+    collections::HashMap,
+    convert::{TryFrom, TryInto},
+    hash::BuildHasher,
+    str::FromStr,
+    u8,
+};
 use uuid::Uuid;
 
 /// Compute the maximum decimal value precision of a byte array of length `len` could hold.
-fn max_prec_for_len(len: usize) -> Result<usize, Error> {
+pub(crate) fn max_prec_for_len(len: usize) -> Result<usize, Error> {
     let len = i32::try_from(len).map_err(|e| Error::ConvertLengthToI32(e, len))?;
     Ok((2.0_f64.powi(8 * len - 1) - 1.0).log10().floor() as usize)
 }
 
+/// The branch of a union schema that a `Value::Union` holds a value of.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnionBranch {
+    /// Position of the branch within the variants of the union.
+    pub index: usize,
+    /// Fullname of the branch, if it is a named type (or a reference to one).
+    pub name: Option<String>,
+}
+
+/// How lenient schema resolution is about the conversions between values of different types.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResolutionMode {
+    /// Only the promotions of the specification are allowed: `int` to `long`, `float` or
+    /// `double`, `long` to `float` or `double`, `float` to `double`, and `string` and `bytes`
+    /// both ways.
+    Strict,
+    /// Lossy conversions are also allowed: `long` to `int` and `double` to `float` by
+    /// truncation, and arrays of ints to `bytes`.
+    Lenient,
+}
+
+// Deriving `Default` with a `#[default]` variant requires Rust 1.62.
+#[allow(clippy::derivable_impls)]
+impl Default for ResolutionMode {
+    fn default() -> Self {
+        ResolutionMode::Strict
+    }
+}
+
 /// A valid Avro value.
 ///
 /// More information about Avro values can be found in the [Avro
@@ -49,7 +84,11 @@ pub enum Value {
     /// reading values.
     Enum(i32, String),
     /// An `union` Avro value.
-    Union(Box<Value>),
+    ///
+    /// The branch of the union schema the value belongs to is known for decoded and resolved
+    /// values, and can be given when building a value (see `UnionSchema::branch`). When it is
+    /// `None`, the branch is found by matching the value against the variants of the union.
+    Union(Option<UnionBranch>, Box<Value>),
     /// An `array` Avro value.
     Array(Vec<Value>),
     /// A `map` Avro value.
@@ -68,6 +107,8 @@ pub enum Value {
     Date(i32),
     /// An Avro Decimal value. Bytes are in big-endian order, per the Avro spec.
     Decimal(Decimal),
+    /// An Avro BigDecimal value, whose scale is serialized along with its unscaled value.
+    BigDecimal(BigDecimal),
     /// Time in milliseconds.
     TimeMillis(i32),
     /// Time in microseconds.
@@ -76,11 +117,22 @@ pub enum Value {
     TimestampMillis(i64),
     /// Timestamp in microseconds.
     TimestampMicros(i64),
+    /// Local timestamp in milliseconds.
+    LocalTimestampMillis(i64),
+    /// Local timestamp in microseconds.
+    LocalTimestampMicros(i64),
+    /// Timestamp in nanoseconds.
+    TimestampNanos(i64),
+    /// Local timestamp in nanoseconds.
+    LocalTimestampNanos(i64),
     /// Avro Duration. An amount of time defined by months, days and milliseconds.
     Duration(Duration),
     /// Universally unique identifier.
     /// Universally unique identifier.
     Uuid(Uuid),
+    /// A value of a custom logical type, given by its name, in the richer representation of the
+    /// logical type (see `logical_type::LogicalType`).
+    Custom(String, Box<Value>),
 }
 /// Any structure implementing the [ToAvro](trait.ToAvro.html) trait will be usable
 /// from a [Writer](../writer/struct.Writer.html).
@@ -119,6 +171,7 @@ to_value!(String, Value::String);
 to_value!(Vec<u8>, Value::Bytes);
 to_value!(uuid::Uuid, Value::Uuid);
 to_value!(Decimal, Value::Decimal);
+to_value!(BigDecimal, Value::BigDecimal);
 to_value!(Duration, Value::Duration);
 
 impl From<()> for Value {
@@ -152,7 +205,7 @@ where
     T: Into<Self>,
 {
     fn from(value: Option<T>) -> Self {
-        Self::Union(Box::new(value.map_or_else(|| Self::Null, Into::into)))
+        Self::Union(None, Box::new(value.map_or_else(|| Self::Null, Into::into)))
     }
 }
 
@@ -269,7 +322,7 @@ impl std::convert::TryFrom<Value> for JsonValue {
                 Ok(Self::Array(items.into_iter().map(|v| v.into()).collect()))
             }
             Value::Enum(_i, s) => Ok(Self::String(s)),
-            Value::Union(b) => Self::try_from(*b),
+            Value::Union(_, b) => Self::try_from(*b),
             Value::Array(items) => items
                 .into_iter()
                 .map(Self::try_from)
@@ -288,14 +341,22 @@ impl std::convert::TryFrom<Value> for JsonValue {
             Value::Date(d) => Ok(Self::Number(d.into())),
             Value::Decimal(ref d) => <Vec<u8>>::try_from(d)
                 .map(|vec| Self::Array(vec.into_iter().map(|v| v.into()).collect())),
+            Value::BigDecimal(ref d) => Ok(Self::Array(
+                <Vec<u8>>::from(d).into_iter().map(|v| v.into()).collect(),
+            )),
             Value::TimeMillis(t) => Ok(Self::Number(t.into())),
             Value::TimeMicros(t) => Ok(Self::Number(t.into())),
             Value::TimestampMillis(t) => Ok(Self::Number(t.into())),
             Value::TimestampMicros(t) => Ok(Self::Number(t.into())),
+            Value::LocalTimestampMillis(t) => Ok(Self::Number(t.into())),
+            Value::LocalTimestampMicros(t) => Ok(Self::Number(t.into())),
+            Value::TimestampNanos(t) => Ok(Self::Number(t.into())),
+            Value::LocalTimestampNanos(t) => Ok(Self::Number(t.into())),
             Value::Duration(d) => Ok(Self::Array(
                 <[u8; 12]>::from(d).iter().map(|&v| v.into()).collect(),
             )),
             Value::Uuid(uuid) => Ok(Self::String(uuid.to_hyphenated().to_string())),
+            Value::Custom(_, value) => Self::try_from(*value),
         }
     }
 }
@@ -306,6 +367,19 @@ impl Value {
     /// See the [Avro specification](https://avro.apache.org/docs/current/spec.html)
     /// for the full set of rules of schema validation.
     pub fn validate(&self, schema: &Schema) -> bool {
+        self.validate_with_names(schema, &mut Names::new(schema))
+    }
+
+    /// Same as `validate`, resolving references to named types through `names`.
+    pub(crate) fn validate_with_names<'s>(
+        &self,
+        schema: &'s Schema,
+        names: &mut Names<'s>,
+    ) -> bool {
+        let schema = match names.resolve(schema) {
+            Ok(schema) => schema,
+            Err(_) => return false,
+        };
         match (self, schema) {
             (&Value::Null, &Schema::Null) => true,
             (&Value::Boolean(_), &Schema::Boolean) => true,
@@ -318,18 +392,35 @@ impl Value {
             (&Value::Long(_), &Schema::TimestampMicros) => true,
             (&Value::TimestampMicros(_), &Schema::TimestampMicros) => true,
             (&Value::TimestampMillis(_), &Schema::TimestampMillis) => true,
+            (&Value::Long(_), &Schema::LocalTimestampMillis) => true,
+            (&Value::Long(_), &Schema::LocalTimestampMicros) => true,
+            (&Value::LocalTimestampMillis(_), &Schema::LocalTimestampMillis) => true,
+            (&Value::LocalTimestampMicros(_), &Schema::LocalTimestampMicros) => true,
+            (&Value::Long(_), &Schema::TimestampNanos) => true,
+            (&Value::Long(_), &Schema::LocalTimestampNanos) => true,
+            (&Value::TimestampNanos(_), &Schema::TimestampNanos) => true,
+            (&Value::LocalTimestampNanos(_), &Schema::LocalTimestampNanos) => true,
             (&Value::TimeMicros(_), &Schema::TimeMicros) => true,
             (&Value::TimeMillis(_), &Schema::TimeMillis) => true,
             (&Value::Date(_), &Schema::Date) => true,
             (&Value::Decimal(_), &Schema::Decimal { .. }) => true,
+            (&Value::BigDecimal(_), &Schema::BigDecimal) => true,
+            (Value::Bytes(b), Schema::BigDecimal) => BigDecimal::try_from(&b[..]).is_ok(),
+            (Value::String(s), Schema::BigDecimal) => BigDecimal::from_str(s).is_ok(),
             (&Value::Duration(_), &Schema::Duration) => true,
-            (&Value::Uuid(_), &Schema::Uuid) => true,
+            (&Value::Uuid(_), &Schema::Uuid { .. }) => true,
             (&Value::Float(_), &Schema::Float) => true,
             (&Value::Double(_), &Schema::Double) => true,
             (&Value::Bytes(_), &Schema::Bytes) => true,
             (&Value::Bytes(_), &Schema::Decimal { .. }) => true,
             (&Value::String(_), &Schema::String) => true,
-            (&Value::String(_), &Schema::Uuid) => true,
+            (Value::String(s), Schema::Uuid { inner }) => match names.resolve_fixed(inner) {
+                Ok(Schema::Fixed { .. }) => Uuid::from_str(s).is_ok(),
+                _ => true,
+            },
+            (Value::Fixed(n, _), Schema::Uuid { inner }) => {
+                matches!(names.resolve_fixed(inner), Ok(Schema::Fixed { size, .. }) if size == n)
+            }
             (&Value::Fixed(n, _), &Schema::Fixed { size, .. }) => n == size,
             (&Value::Bytes(ref b), &Schema::Fixed { size, .. }) => b.len() == size,
             (&Value::Fixed(n, _), &Schema::Duration) => n == 12,
@@ -340,33 +431,49 @@ impl Value {
                 .get(i as usize)
                 .map(|ref symbol| symbol == &s)
                 .unwrap_or(false),
-            // (&Value::Union(None), &Schema::Union(_)) => true,
-            (&Value::Union(ref value), &Schema::Union(ref inner)) => {
-                inner.find_schema(value).is_some()
+            (&Value::Union(None, ref value), &Schema::Union(ref inner)) => {
+                inner.find_schema_with_names(value, names).is_some()
             }
-            (&Value::Array(ref items), &Schema::Array(ref inner)) => {
-                items.iter().all(|item| item.validate(inner))
-            }
-            (&Value::Map(ref items), &Schema::Map(ref inner)) => {
-                items.iter().all(|(_, value)| value.validate(inner))
+            (&Value::Union(Some(ref branch), ref value), &Schema::Union(ref inner)) => {
+                match inner.find_branch(branch) {
+                    Some(schema) => value.validate_with_names(schema, names),
+                    None => false,
+                }
             }
+            (&Value::Array(ref items), &Schema::Array(ref inner)) => items
+                .iter()
+                .all(|item| item.validate_with_names(inner, names)),
+            (&Value::Map(ref items), &Schema::Map(ref inner)) => items
+                .iter()
+                .all(|(_, value)| value.validate_with_names(inner, names)),
             (&Value::Record(ref record_fields), &Schema::Record { ref fields, .. }) => {
                 fields.len() == record_fields.len()
                     && fields.iter().zip(record_fields.iter()).all(
                         |(field, &(ref name, ref value))| {
-                            field.name == *name && value.validate(&field.schema)
+                            field.name == *name && value.validate_with_names(&field.schema, names)
                         },
                     )
             }
             (&Value::Map(ref items), &Schema::Record { ref fields, .. }) => {
                 fields.iter().all(|field| {
                     if let Some(item) = items.get(&field.name) {
-                        item.validate(&field.schema)
+                        item.validate_with_names(&field.schema, names)
                     } else {
                         false
                     }
                 })
             }
+            (
+                value,
+                Schema::Custom {
+                    logical_type,
+                    inner,
+                },
+            ) => match logical_type.to_base(value, |value| value.validate_with_names(inner, names))
+            {
+                Ok(base) => base.validate_with_names(inner, names) && logical_type.validate(&base),
+                Err(_) => false,
+            },
             _ => false,
         }
     }
@@ -377,14 +484,32 @@ impl Value {
     /// See [Schema Resolution](https://avro.apache.org/docs/current/spec.html#Schema+Resolution)
     /// in the Avro specification for the full set of rules of schema
     /// resolution.
-    pub fn resolve(mut self, schema: &Schema) -> AvroResult<Self> {
+    ///
+    /// Only the promotions of the specification are allowed, see `ResolutionMode::Strict`.
+    pub fn resolve(self, schema: &Schema) -> AvroResult<Self> {
+        self.resolve_with_mode(schema, ResolutionMode::Strict)
+    }
+
+    /// Same as `resolve`, with the given `ResolutionMode`.
+    pub fn resolve_with_mode(self, schema: &Schema, mode: ResolutionMode) -> AvroResult<Self> {
+        self.resolve_with_names(schema, &mut Names::new(schema), mode)
+    }
+
+    /// Same as `resolve_with_mode`, resolving references to named types through `names`.
+    pub(crate) fn resolve_with_names<'s>(
+        mut self,
+        schema: &'s Schema,
+        names: &mut Names<'s>,
+        mode: ResolutionMode,
+    ) -> AvroResult<Self> {
+        let schema = names.resolve(schema)?;
         // Check if this schema is a union, and if the reader schema is not.
         if SchemaKind::from(&self) == SchemaKind::Union
             && SchemaKind::from(schema) != SchemaKind::Union
         {
             // Pull out the Union, and attempt to resolve against it.
             let v = match self {
-                Value::Union(b) => *b,
+                Value::Union(_, b) => *b,
                 _ => unreachable!(),
             };
             self = v;
@@ -392,30 +517,51 @@ impl Value {
         match *schema {
             Schema::Null => self.resolve_null(),
             Schema::Boolean => self.resolve_boolean(),
-            Schema::Int => self.resolve_int(),
+            Schema::Int => self.resolve_int(mode),
             Schema::Long => self.resolve_long(),
-            Schema::Float => self.resolve_float(),
+            Schema::Float => self.resolve_float(mode),
             Schema::Double => self.resolve_double(),
-            Schema::Bytes => self.resolve_bytes(),
+            Schema::Bytes => self.resolve_bytes(mode),
             Schema::String => self.resolve_string(),
             Schema::Fixed { size, .. } => self.resolve_fixed(size),
-            Schema::Union(ref inner) => self.resolve_union(inner),
-            Schema::Enum { ref symbols, .. } => self.resolve_enum(symbols),
-            Schema::Array(ref inner) => self.resolve_array(inner),
-            Schema::Map(ref inner) => self.resolve_map(inner),
-            Schema::Record { ref fields, .. } => self.resolve_record(fields),
+            Schema::Union(ref inner) => self.resolve_union(inner, names, mode),
+            Schema::Enum {
+                ref symbols,
+                ref default,
+                ..
+            } => self.resolve_enum(symbols, default),
+            Schema::Array(ref inner) => self.resolve_array(inner, names, mode),
+            Schema::Map(ref inner) => self.resolve_map(inner, names, mode),
+            Schema::Record { ref fields, .. } => self.resolve_record(fields, names, mode),
             Schema::Decimal {
                 scale,
                 precision,
                 ref inner,
-            } => self.resolve_decimal(precision, scale, inner),
+            } => self.resolve_decimal(precision, scale, names.resolve_fixed(inner)?),
+            Schema::BigDecimal => self.resolve_big_decimal(),
             Schema::Date => self.resolve_date(),
             Schema::TimeMillis => self.resolve_time_millis(),
             Schema::TimeMicros => self.resolve_time_micros(),
             Schema::TimestampMillis => self.resolve_timestamp_millis(),
             Schema::TimestampMicros => self.resolve_timestamp_micros(),
+            Schema::LocalTimestampMillis => self.resolve_local_timestamp_millis(),
+            Schema::LocalTimestampMicros => self.resolve_local_timestamp_micros(),
+            Schema::TimestampNanos => self.resolve_timestamp_nanos(),
+            Schema::LocalTimestampNanos => self.resolve_local_timestamp_nanos(),
             Schema::Duration => self.resolve_duration(),
-            Schema::Uuid => self.resolve_uuid(),
+            Schema::Uuid { .. } => self.resolve_uuid(),
+            Schema::Custom {
+                ref logical_type,
+                ref inner,
+            } => {
+                // The value is of the logical type already if the writer's schema is annotated
+                // with it as well.
+                let base = logical_type.to_base(&self, |_| true)?;
+                logical_type.to_custom(base.resolve_with_names(inner, names, mode)?)
+            }
+            Schema::Ref { .. } | Schema::Attributed { .. } => {
+                unreachable!("references and attributes are resolved above")
+            }
         }
     }
 
@@ -425,6 +571,9 @@ impl Value {
             Value::String(ref string) => {
                 Value::Uuid(Uuid::from_str(string).map_err(Error::ConvertStrToUuid)?)
             }
+            Value::Fixed(16, ref bytes) => {
+                Value::Uuid(Uuid::from_slice(bytes).map_err(Error::ConvertSliceToUuid)?)
+            }
             other => return Err(Error::GetUuid(other.into())),
         })
     }
@@ -491,6 +640,15 @@ impl Value {
         }
     }
 
+    fn resolve_big_decimal(self) -> Result<Self, Error> {
+        match self {
+            Value::BigDecimal(decimal) => Ok(Value::BigDecimal(decimal)),
+            Value::Bytes(bytes) => BigDecimal::try_from(&bytes[..]).map(Value::BigDecimal),
+            Value::String(s) => BigDecimal::from_str(&s).map(Value::BigDecimal),
+            other => Err(Error::ResolveBigDecimal(other.into())),
+        }
+    }
+
     fn resolve_date(self) -> Result<Self, Error> {
         match self {
             Value::Date(d) | Value::Int(d) => Ok(Value::Date(d)),
@@ -529,6 +687,42 @@ impl Value {
         }
     }
 
+    fn resolve_local_timestamp_millis(self) -> Result<Self, Error> {
+        match self {
+            Value::LocalTimestampMillis(ts) | Value::Long(ts) => {
+                Ok(Value::LocalTimestampMillis(ts))
+            }
+            Value::Int(ts) => Ok(Value::LocalTimestampMillis(i64::from(ts))),
+            other => Err(Error::GetLocalTimestampMillis(other.into())),
+        }
+    }
+
+    fn resolve_local_timestamp_micros(self) -> Result<Self, Error> {
+        match self {
+            Value::LocalTimestampMicros(ts) | Value::Long(ts) => {
+                Ok(Value::LocalTimestampMicros(ts))
+            }
+            Value::Int(ts) => Ok(Value::LocalTimestampMicros(i64::from(ts))),
+            other => Err(Error::GetLocalTimestampMicros(other.into())),
+        }
+    }
+
+    fn resolve_timestamp_nanos(self) -> Result<Self, Error> {
+        match self {
+            Value::TimestampNanos(ts) | Value::Long(ts) => Ok(Value::TimestampNanos(ts)),
+            Value::Int(ts) => Ok(Value::TimestampNanos(i64::from(ts))),
+            other => Err(Error::GetTimestampNanos(other.into())),
+        }
+    }
+
+    fn resolve_local_timestamp_nanos(self) -> Result<Self, Error> {
+        match self {
+            Value::LocalTimestampNanos(ts) | Value::Long(ts) => Ok(Value::LocalTimestampNanos(ts)),
+            Value::Int(ts) => Ok(Value::LocalTimestampNanos(i64::from(ts))),
+            other => Err(Error::GetLocalTimestampNanos(other.into())),
+        }
+    }
+
     fn resolve_null(self) -> Result<Self, Error> {
         match self {
             Value::Null => Ok(Value::Null),
@@ -543,10 +737,14 @@ impl Value {
         }
     }
 
-    fn resolve_int(self) -> Result<Self, Error> {
+    fn resolve_int(self, mode: ResolutionMode) -> Result<Self, Error> {
         match self {
             Value::Int(n) => Ok(Value::Int(n)),
-            Value::Long(n) => Ok(Value::Int(n as i32)),
+            Value::Long(n) if mode == ResolutionMode::Lenient => Ok(Value::Int(n as i32)),
+            Value::Long(_) => Err(Error::ResolvePromotion {
+                from: ValueKind::Long,
+                to: SchemaKind::Int,
+            }),
             other => Err(Error::GetInt(other.into())),
         }
     }
@@ -559,12 +757,16 @@ impl Value {
         }
     }
 
-    fn resolve_float(self) -> Result<Self, Error> {
+    fn resolve_float(self, mode: ResolutionMode) -> Result<Self, Error> {
         match self {
             Value::Int(n) => Ok(Value::Float(n as f32)),
             Value::Long(n) => Ok(Value::Float(n as f32)),
             Value::Float(x) => Ok(Value::Float(x)),
-            Value::Double(x) => Ok(Value::Float(x as f32)),
+            Value::Double(x) if mode == ResolutionMode::Lenient => Ok(Value::Float(x as f32)),
+            Value::Double(_) => Err(Error::ResolvePromotion {
+                from: ValueKind::Double,
+                to: SchemaKind::Float,
+            }),
             other => Err(Error::GetFloat(other.into())),
         }
     }
@@ -579,10 +781,14 @@ impl Value {
         }
     }
 
-    fn resolve_bytes(self) -> Result<Self, Error> {
+    fn resolve_bytes(self, mode: ResolutionMode) -> Result<Self, Error> {
         match self {
             Value::Bytes(bytes) => Ok(Value::Bytes(bytes)),
             Value::String(s) => Ok(Value::Bytes(s.into_bytes())),
+            Value::Array(_) if mode == ResolutionMode::Strict => Err(Error::ResolvePromotion {
+                from: ValueKind::Array,
+                to: SchemaKind::Bytes,
+            }),
             Value::Array(items) => Ok(Value::Bytes(
                 items
                     .into_iter()
@@ -616,10 +822,20 @@ impl Value {
         }
     }
 
-    fn resolve_enum(self, symbols: &[String]) -> Result<Self, Error> {
+    fn resolve_enum(self, symbols: &[String], default: &Option<String>) -> Result<Self, Error> {
         let validate_symbol = |symbol: String, symbols: &[String]| {
             if let Some(index) = symbols.iter().position(|item| item == &symbol) {
                 Ok(Value::Enum(index as i32, symbol))
+            } else if let Some(ref default) = default {
+                // The default is used for the symbols the reader does not know about.
+                let index = symbols
+                    .iter()
+                    .position(|item| item == default)
+                    .ok_or_else(|| Error::GetEnumDefault {
+                        symbol: default.clone(),
+                        symbols: symbols.into(),
+                    })?;
+                Ok(Value::Enum(index as i32, default.clone()))
             } else {
                 Err(Error::GetEnumDefault {
                     symbol,
@@ -646,24 +862,59 @@ impl Value {
         }
     }
 
-    fn resolve_union(self, schema: &UnionSchema) -> Result<Self, Error> {
-        let v = match self {
+    fn resolve_union<'s>(
+        self,
+        schema: &'s UnionSchema,
+        names: &mut Names<'s>,
+        mode: ResolutionMode,
+    ) -> Result<Self, Error> {
+        let (branch, v) = match self {
             // Both are unions case.
-            Value::Union(v) => *v,
+            Value::Union(branch, v) => (branch, *v),
             // Reader is a union, but writer is not.
-            v => v,
+            v => (None, v),
+        };
+        // The value is read as the best match for it among the reader's variants. A named
+        // branch of the writer's union is best read as the reader's variant of the same name;
+        // the index of the branch is not used, since it is relative to the writer's union.
+        let name = branch.and_then(|branch| branch.name);
+        let candidates = schema.ranked_matches(SchemaKind::from(&v), name.as_deref(), names);
+        // The value is resolved as the variant of its name if there is one, or else as the first
+        // variant it is already valid for, or else as the best variant of the same kind or one
+        // it can be promoted to, whose error is returned if it cannot be read as such.
+        let found = match candidates.first() {
+            Some(&(0, index, inner)) if name.is_some() => Some((index, inner)),
+            _ => match candidates
+                .iter()
+                .find(|&&(_, _, inner)| v.validate_with_names(inner, names))
+            {
+                Some(&(_, index, inner)) => Some((index, inner)),
+                None => candidates
+                    .first()
+                    .filter(|&&(rank, _, _)| rank < 3)
+                    .map(|&(_, index, inner)| (index, inner)),
+            },
         };
-        // Find the first match in the reader schema.
-        let (_, inner) = schema.find_schema(&v).ok_or(Error::FindUnionVariant)?;
-        Ok(Value::Union(Box::new(v.resolve(inner)?)))
+        match found {
+            Some((index, inner)) => Ok(Value::Union(
+                schema.branch(index),
+                Box::new(v.resolve_with_names(inner, names, mode)?),
+            )),
+            None => Err(Error::FindUnionVariant),
+        }
     }
 
-    fn resolve_array(self, schema: &Schema) -> Result<Self, Error> {
+    fn resolve_array<'s>(
+        self,
+        schema: &'s Schema,
+        names: &mut Names<'s>,
+        mode: ResolutionMode,
+    ) -> Result<Self, Error> {
         match self {
             Value::Array(items) => Ok(Value::Array(
                 items
                     .into_iter()
-                    .map(|item| item.resolve(schema))
+                    .map(|item| item.resolve_with_names(schema, names, mode))
                     .collect::<Result<_, _>>()?,
             )),
             other => Err(Error::GetArray {
@@ -673,12 +924,21 @@ impl Value {
         }
     }
 
-    fn resolve_map(self, schema: &Schema) -> Result<Self, Error> {
+    fn resolve_map<'s>(
+        self,
+        schema: &'s Schema,
+        names: &mut Names<'s>,
+        mode: ResolutionMode,
+    ) -> Result<Self, Error> {
         match self {
             Value::Map(items) => Ok(Value::Map(
                 items
                     .into_iter()
-                    .map(|(key, value)| value.resolve(schema).map(|value| (key, value)))
+                    .map(|(key, value)| {
+                        value
+                            .resolve_with_names(schema, names, mode)
+                            .map(|value| (key, value))
+                    })
                     .collect::<Result<_, _>>()?,
             )),
             other => Err(Error::GetMap {
@@ -688,7 +948,12 @@ impl Value {
         }
     }
 
-    fn resolve_record(self, fields: &[RecordField]) -> Result<Self, Error> {
+    fn resolve_record<'s>(
+        self,
+        fields: &'s [RecordField],
+        names: &mut Names<'s>,
+        mode: ResolutionMode,
+    ) -> Result<Self, Error> {
         let mut items = match self {
             Value::Map(items) => Ok(items),
             Value::Record(fields) => Ok(fields.into_iter().collect::<HashMap<_, _>>()),
@@ -704,33 +969,30 @@ impl Value {
         let new_fields = fields
             .iter()
             .map(|field| {
-                let value = match items.remove(&field.name) {
+                // The writer's field may have been renamed, in which case the reader's field
+                // lists its former name among its aliases.
+                let value = match items.remove(&field.name).or_else(|| {
+                    field
+                        .aliases
+                        .iter()
+                        .flatten()
+                        .find_map(|alias| items.remove(alias))
+                }) {
                     Some(value) => value,
-                    None => match field.default {
-                        Some(ref value) => match field.schema {
-                            Schema::Enum { ref symbols, .. } => {
-                                Value::from(value.clone()).resolve_enum(symbols)?
-                            }
-                            Schema::Union(ref union_schema) => {
-                                let first = &union_schema.variants()[0];
-                                // NOTE: this match exists only to optimize null defaults for large
-                                // backward-compatible schemas with many nullable fields
-                                match first {
-                                    Schema::Null => Value::Union(Box::new(Value::Null)),
-                                    _ => Value::Union(Box::new(
-                                        Value::from(value.clone()).resolve(first)?,
-                                    )),
-                                }
-                            }
-                            _ => Value::from(value.clone()),
-                        },
-                        None => {
+                    // Defaults are parsed along with the schema, unless the field was built by
+                    // hand.
+                    None => match (&field.default_value, &field.default) {
+                        (Some(value), _) => value.clone(),
+                        (None, Some(default)) => {
+                            parse_default(&field.name, default, &field.schema, names)?
+                        }
+                        (None, None) => {
                             return Err(Error::GetField(field.name.clone()));
                         }
                     },
                 };
                 value
-                    .resolve(&field.schema)
+                    .resolve_with_names(&field.schema, names, mode)
                     .map(|value| (field.name.clone(), value))
             })
             .collect::<Result<Vec<_>, _>>()?;
@@ -739,7 +1001,7 @@ impl Value {
     }
 
     fn try_u8(self) -> AvroResult<u8> {
-        let int = self.resolve(&Schema::Int)?;
+        let int = self.resolve_with_mode(&Schema::Int, ResolutionMode::Lenient)?;
         if let Value::Int(n) = int {
             if n >= 0 && n <= i32::from(u8::MAX) {
                 return Ok(n as u8);
@@ -750,6 +1012,139 @@ impl Value {
     }
 }
 
+/// Parse the JSON `default` of the record field `field` into a `Value` of the field's `schema`.
+///
+/// Per the specification, bytes and fixed defaults are strings whose code points are the values
+/// of the bytes (ISO-8859-1), records and maps are objects, and union defaults are values of the
+/// first branch of the union.
+pub(crate) fn parse_default<'s>(
+    field: &str,
+    default: &JsonValue,
+    schema: &'s Schema,
+    names: &mut Names<'s>,
+) -> AvroResult<Value> {
+    let schema = names.resolve(schema)?;
+    let invalid = || Error::GetDefaultValue {
+        field: field.to_string(),
+        value: default.clone(),
+        kind: schema.into(),
+    };
+    let int = || {
+        default
+            .as_i64()
+            .and_then(|n| i32::try_from(n).ok())
+            .ok_or_else(invalid)
+    };
+    let long = || default.as_i64().ok_or_else(invalid);
+    let bytes = || {
+        default
+            .as_str()
+            .ok_or_else(invalid)?
+            .chars()
+            .map(|c| u8::try_from(u32::from(c)).map_err(|_| invalid()))
+            .collect::<AvroResult<Vec<u8>>>()
+    };
+    let fixed = |size: usize| {
+        let bytes = bytes()?;
+        if bytes.len() == size {
+            Ok(bytes)
+        } else {
+            Err(invalid())
+        }
+    };
+
+    Ok(match *schema {
+        Schema::Null if default.is_null() => Value::Null,
+        Schema::Boolean => Value::Boolean(default.as_bool().ok_or_else(invalid)?),
+        Schema::Int => Value::Int(int()?),
+        Schema::Long => Value::Long(long()?),
+        Schema::Float => Value::Float(default.as_f64().ok_or_else(invalid)? as f32),
+        Schema::Double => Value::Double(default.as_f64().ok_or_else(invalid)?),
+        Schema::Bytes => Value::Bytes(bytes()?),
+        Schema::String => Value::String(default.as_str().ok_or_else(invalid)?.to_string()),
+        Schema::Fixed { size, .. } => Value::Fixed(size, fixed(size)?),
+        Schema::Enum { ref symbols, .. } => {
+            let symbol = default.as_str().ok_or_else(invalid)?;
+            let index = symbols
+                .iter()
+                .position(|s| s == symbol)
+                .ok_or_else(invalid)?;
+            Value::Enum(index as i32, symbol.to_string())
+        }
+        Schema::Array(ref items) => Value::Array(
+            default
+                .as_array()
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|item| parse_default(field, item, items, names))
+                .collect::<AvroResult<_>>()?,
+        ),
+        Schema::Map(ref values) => Value::Map(
+            default
+                .as_object()
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|(key, value)| {
+                    parse_default(field, value, values, names).map(|value| (key.clone(), value))
+                })
+                .collect::<AvroResult<_>>()?,
+        ),
+        Schema::Record { ref fields, .. } => {
+            let object = default.as_object().ok_or_else(invalid)?;
+            Value::Record(
+                fields
+                    .iter()
+                    .map(|f| {
+                        let value = match object.get(&f.name).or(f.default.as_ref()) {
+                            Some(value) => parse_default(field, value, &f.schema, names)?,
+                            None => return Err(invalid()),
+                        };
+                        Ok((f.name.clone(), value))
+                    })
+                    .collect::<AvroResult<_>>()?,
+            )
+        }
+        Schema::Union(ref union_schema) => {
+            let first = union_schema.variants().first().ok_or_else(invalid)?;
+            Value::Union(
+                union_schema.branch(0),
+                Box::new(parse_default(field, default, first, names)?),
+            )
+        }
+        Schema::Decimal { ref inner, .. } => match *names.resolve_fixed(inner)? {
+            Schema::Fixed { size, .. } => Value::Decimal(Decimal::from(fixed(size)?)),
+            _ => Value::Decimal(Decimal::from(bytes()?)),
+        },
+        Schema::BigDecimal => {
+            Value::BigDecimal(BigDecimal::try_from(&bytes()?[..]).map_err(|_| invalid())?)
+        }
+        Schema::Uuid { ref inner } => Value::Uuid(match *names.resolve_fixed(inner)? {
+            Schema::Fixed { size, .. } => Uuid::from_slice(&fixed(size)?).map_err(|_| invalid())?,
+            _ => Uuid::from_str(default.as_str().ok_or_else(invalid)?).map_err(|_| invalid())?,
+        }),
+        Schema::Date => Value::Date(int()?),
+        Schema::TimeMillis => Value::TimeMillis(int()?),
+        Schema::TimeMicros => Value::TimeMicros(long()?),
+        Schema::TimestampMillis => Value::TimestampMillis(long()?),
+        Schema::TimestampMicros => Value::TimestampMicros(long()?),
+        Schema::LocalTimestampMillis => Value::LocalTimestampMillis(long()?),
+        Schema::LocalTimestampMicros => Value::LocalTimestampMicros(long()?),
+        Schema::TimestampNanos => Value::TimestampNanos(long()?),
+        Schema::LocalTimestampNanos => Value::LocalTimestampNanos(long()?),
+        Schema::Duration => {
+            let bytes: [u8; 12] = fixed(12)?.try_into().map_err(|_| invalid())?;
+            Value::Duration(Duration::from(bytes))
+        }
+        Schema::Custom {
+            ref logical_type,
+            ref inner,
+        } => logical_type
+            .to_custom(parse_default(field, default, inner, names)?)
+            .map_err(|_| invalid())?,
+        _ => return Err(invalid()),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -767,22 +1162,22 @@ mod tests {
             (Value::Int(42), Schema::Int, true),
             (Value::Int(42), Schema::Boolean, false),
             (
-                Value::Union(Box::new(Value::Null)),
+                Value::Union(None, Box::new(Value::Null)),
                 Schema::Union(UnionSchema::new(vec![Schema::Null, Schema::Int]).unwrap()),
                 true,
             ),
             (
-                Value::Union(Box::new(Value::Int(42))),
+                Value::Union(None, Box::new(Value::Int(42))),
                 Schema::Union(UnionSchema::new(vec![Schema::Null, Schema::Int]).unwrap()),
                 true,
             ),
             (
-                Value::Union(Box::new(Value::Null)),
+                Value::Union(None, Box::new(Value::Null)),
                 Schema::Union(UnionSchema::new(vec![Schema::Double, Schema::Int]).unwrap()),
                 false,
             ),
             (
-                Value::Union(Box::new(Value::Int(42))),
+                Value::Union(None, Box::new(Value::Int(42))),
                 Schema::Union(
                     UnionSchema::new(vec![
                         Schema::Null,
@@ -795,7 +1190,7 @@ mod tests {
                 true,
             ),
             (
-                Value::Union(Box::new(Value::Long(42i64))),
+                Value::Union(None, Box::new(Value::Long(42i64))),
                 Schema::Union(
                     UnionSchema::new(vec![Schema::Null, Schema::TimestampMillis]).unwrap(),
                 ),
@@ -824,6 +1219,8 @@ mod tests {
         let schema = Schema::Fixed {
             size: 4,
             name: Name::new("some_fixed"),
+            doc: None,
+            attributes: Default::default(),
         };
 
         assert!(Value::Fixed(4, vec![0, 0, 0, 0]).validate(&schema));
@@ -843,6 +1240,8 @@ mod tests {
                 "diamonds".to_string(),
                 "clubs".to_string(),
             ],
+            default: None,
+            attributes: Default::default(),
         };
 
         assert!(Value::Enum(0, "spades".to_string()).validate(&schema));
@@ -860,6 +1259,8 @@ mod tests {
                 "clubs".to_string(),
                 "spades".to_string(),
             ],
+            default: None,
+            attributes: Default::default(),
         };
 
         assert!(!Value::Enum(0, "spades".to_string()).validate(&other_schema));
@@ -883,20 +1284,29 @@ mod tests {
                     name: "a".to_string(),
                     doc: None,
                     default: None,
+                    default_value: None,
                     schema: Schema::Long,
                     order: RecordFieldOrder::Ascending,
                     position: 0,
+                    aliases: None,
+                    attributes: Default::default(),
+                    type_attributes: Default::default(),
                 },
                 RecordField {
                     name: "b".to_string(),
                     doc: None,
                     default: None,
+                    default_value: None,
                     schema: Schema::String,
                     order: RecordFieldOrder::Ascending,
                     position: 1,
+                    aliases: None,
+                    attributes: Default::default(),
+                    type_attributes: Default::default(),
                 },
             ],
             lookup: HashMap::new(),
+            attributes: Default::default(),
         };
 
         assert!(Value::Record(vec![
@@ -942,20 +1352,26 @@ mod tests {
 
         let union_schema = Schema::Union(UnionSchema::new(vec![Schema::Null, schema]).unwrap());
 
-        assert!(Value::Union(Box::new(Value::Record(vec![
-            ("a".to_string(), Value::Long(42i64)),
-            ("b".to_string(), Value::String("foo".to_string())),
-        ])))
-        .validate(&union_schema));
-
-        assert!(Value::Union(Box::new(Value::Map(
-            vec![
+        assert!(Value::Union(
+            None,
+            Box::new(Value::Record(vec![
                 ("a".to_string(), Value::Long(42i64)),
                 ("b".to_string(), Value::String("foo".to_string())),
-            ]
-            .into_iter()
-            .collect()
-        )))
+            ]))
+        )
+        .validate(&union_schema));
+
+        assert!(Value::Union(
+            None,
+            Box::new(Value::Map(
+                vec![
+                    ("a".to_string(), Value::Long(42i64)),
+                    ("b".to_string(), Value::String("foo".to_string())),
+                ]
+                .into_iter()
+                .collect()
+            ))
+        )
         .validate(&union_schema));
     }
 
@@ -963,7 +1379,9 @@ mod tests {
     fn resolve_bytes_ok() {
         let value = Value::Array(vec![Value::Int(0), Value::Int(42)]);
         assert_eq!(
-            value.resolve(&Schema::Bytes).unwrap(),
+            value
+                .resolve_with_mode(&Schema::Bytes, ResolutionMode::Lenient)
+                .unwrap(),
             Value::Bytes(vec![0u8, 42u8])
         );
     }
@@ -971,7 +1389,48 @@ mod tests {
     #[test]
     fn resolve_bytes_failure() {
         let value = Value::Array(vec![Value::Int(2000), Value::Int(-42)]);
-        assert!(value.resolve(&Schema::Bytes).is_err());
+        assert!(value
+            .resolve_with_mode(&Schema::Bytes, ResolutionMode::Lenient)
+            .is_err());
+    }
+
+    #[test]
+    fn resolve_strict_promotions() {
+        assert_eq!(
+            Value::Int(1).resolve(&Schema::Double).unwrap(),
+            Value::Double(1.0)
+        );
+        assert_eq!(
+            Value::String("a".to_string())
+                .resolve(&Schema::Bytes)
+                .unwrap(),
+            Value::Bytes(vec![b'a'])
+        );
+
+        match Value::Long(i64::MAX).resolve(&Schema::Int) {
+            Err(Error::ResolvePromotion { from, to }) => {
+                assert_eq!(from, ValueKind::Long);
+                assert_eq!(to, SchemaKind::Int);
+            }
+            other => panic!("Expected Error::ResolvePromotion, got {:?}", other),
+        }
+        assert!(Value::Double(1.5).resolve(&Schema::Float).is_err());
+        assert!(Value::Array(vec![Value::Int(0)])
+            .resolve(&Schema::Bytes)
+            .is_err());
+
+        assert_eq!(
+            Value::Long(1)
+                .resolve_with_mode(&Schema::Int, ResolutionMode::Lenient)
+                .unwrap(),
+            Value::Int(1)
+        );
+        assert_eq!(
+            Value::Double(1.5)
+                .resolve_with_mode(&Schema::Float, ResolutionMode::Lenient)
+                .unwrap(),
+            Value::Float(1.5)
+        );
     }
 
     #[test]
@@ -1000,6 +1459,96 @@ mod tests {
             .is_err());
     }
 
+    #[test]
+    fn validate_union_branch() {
+        let schema = Schema::parse_str(
+            r#"[{"type": "fixed", "name": "Md5", "size": 16}, {"type": "fixed", "name": "Uid", "size": 16}]"#,
+        )
+        .unwrap();
+        let value = |index, name: &str| {
+            Value::Union(
+                Some(UnionBranch {
+                    index,
+                    name: Some(name.to_string()),
+                }),
+                Box::new(Value::Fixed(16, vec![0; 16])),
+            )
+        };
+
+        assert!(value(1, "Uid").validate(&schema));
+        assert!(!value(1, "Md5").validate(&schema));
+        assert!(!value(2, "Uid").validate(&schema));
+    }
+
+    #[test]
+    fn resolve_union_branch_by_name() {
+        let writer_schema = Schema::parse_str(
+            r#"[{"type": "fixed", "name": "Md5", "size": 16}, {"type": "fixed", "name": "Uid", "size": 16}]"#,
+        )
+        .unwrap();
+        let reader_schema = Schema::parse_str(
+            r#"[{"type": "fixed", "name": "Id", "aliases": ["Uid"], "size": 16}, {"type": "fixed", "name": "Md5", "size": 16}]"#,
+        )
+        .unwrap();
+        let branch = |schema: &Schema, index| match schema {
+            Schema::Union(ref union_schema) => union_schema.branch(index),
+            _ => unreachable!(),
+        };
+        let fixed = || Box::new(Value::Fixed(16, vec![0; 16]));
+
+        assert_eq!(
+            Value::Union(branch(&writer_schema, 0), fixed())
+                .resolve(&reader_schema)
+                .unwrap(),
+            Value::Union(branch(&reader_schema, 1), fixed())
+        );
+        assert_eq!(
+            Value::Union(branch(&writer_schema, 1), fixed())
+                .resolve(&reader_schema)
+                .unwrap(),
+            Value::Union(branch(&reader_schema, 0), fixed())
+        );
+    }
+
+    #[test]
+    fn resolve_union_inner_error() {
+        let schema = Schema::parse_str(
+            r#"["null", {"type": "record", "name": "Point", "fields": [{"name": "x", "type": "int"}]}]"#,
+        )
+        .unwrap();
+
+        let point = Value::Record(vec![("x".to_string(), Value::String("1".to_string()))]);
+        match point.resolve(&schema) {
+            Err(Error::GetInt(ValueKind::String)) => (),
+            other => panic!("Expected Error::GetInt, got {:?}", other),
+        }
+        match Value::Boolean(true).resolve(&schema) {
+            Err(Error::FindUnionVariant) => (),
+            other => panic!("Expected Error::FindUnionVariant, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn resolve_enum_default() {
+        let schema = Schema::parse_str(
+            r#"{"type": "enum", "name": "Suit", "symbols": ["spades", "hearts"], "default": "hearts"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Value::Enum(2, "clubs".to_string())
+                .resolve(&schema)
+                .unwrap(),
+            Value::Enum(1, "hearts".to_string())
+        );
+        assert_eq!(
+            Value::String("spades".to_string())
+                .resolve(&schema)
+                .unwrap(),
+            Value::Enum(0, "spades".to_string())
+        );
+    }
+
     #[test]
     fn resolve_decimal_invalid_precision_for_length() {
         let value = Value::Decimal(Decimal::from((1u8..=8u8).rev().collect::<Vec<_>>()));
@@ -1022,13 +1571,42 @@ mod tests {
                 scale: 1,
                 inner: Box::new(Schema::Fixed {
                     name: Name::new("decimal"),
-                    size: 20
+                    size: 20,
+                    doc: None,
+                    attributes: Default::default(),
                 })
             })
             .is_ok());
         assert!(value.resolve(&Schema::String).is_err());
     }
 
+    #[test]
+    fn resolve_big_decimal() {
+        let value = Value::BigDecimal(BigDecimal::new(Decimal::from(vec![1, 2]), 3));
+        assert_eq!(
+            value.clone().resolve(&Schema::BigDecimal).unwrap(),
+            value.clone()
+        );
+        assert_eq!(
+            Value::String("0.258".to_string())
+                .resolve(&Schema::BigDecimal)
+                .unwrap(),
+            value
+        );
+        assert_eq!(
+            Value::Bytes(vec![4, 1, 2, 6])
+                .resolve(&Schema::BigDecimal)
+                .unwrap(),
+            value
+        );
+        assert!(Value::String("0,258".to_string())
+            .resolve(&Schema::BigDecimal)
+            .is_err());
+        assert!(Value::Decimal(Decimal::from(vec![1, 2]))
+            .resolve(&Schema::BigDecimal)
+            .is_err());
+    }
+
     #[test]
     fn resolve_date() {
         let value = Value::Date(2345);
@@ -1070,6 +1648,45 @@ mod tests {
         assert!(value.resolve(&Schema::TimestampMicros).is_err());
     }
 
+    #[test]
+    fn resolve_local_timestamp() {
+        let value = Value::LocalTimestampMillis(10);
+        assert!(value.clone().resolve(&Schema::LocalTimestampMillis).is_ok());
+        assert!(value.resolve(&Schema::TimestampMillis).is_err());
+        assert_eq!(
+            Value::Long(10)
+                .resolve(&Schema::LocalTimestampMicros)
+                .unwrap(),
+            Value::LocalTimestampMicros(10)
+        );
+
+        let value = Value::LocalTimestampMicros(10);
+        assert!(value.clone().resolve(&Schema::LocalTimestampMicros).is_ok());
+        assert!(value.resolve(&Schema::LocalTimestampMillis).is_err());
+    }
+
+    #[test]
+    fn resolve_timestamp_nanos() {
+        let value = Value::TimestampNanos(10);
+        assert!(value.clone().resolve(&Schema::TimestampNanos).is_ok());
+        assert!(value.resolve(&Schema::LocalTimestampNanos).is_err());
+        assert_eq!(
+            Value::Long(10)
+                .resolve(&Schema::LocalTimestampNanos)
+                .unwrap(),
+            Value::LocalTimestampNanos(10)
+        );
+
+        // The specification does not define promotions between the precisions of timestamps, so
+        // a timestamp of another precision is not read as one of nanoseconds.
+        assert!(Value::TimestampMillis(10)
+            .resolve(&Schema::TimestampNanos)
+            .is_err());
+        assert!(Value::LocalTimestampMicros(10)
+            .resolve(&Schema::LocalTimestampNanos)
+            .is_err());
+    }
+
     #[test]
     fn resolve_duration() {
         let value = Value::Duration(Duration::new(
@@ -1085,8 +1702,34 @@ mod tests {
     #[test]
     fn resolve_uuid() {
         let value = Value::Uuid(Uuid::parse_str("1481531d-ccc9-46d9-a56f-5b67459c0537").unwrap());
-        assert!(value.clone().resolve(&Schema::Uuid).is_ok());
-        assert!(value.resolve(&Schema::TimestampMicros).is_err());
+        let string_uuid = Schema::Uuid {
+            inner: Box::new(Schema::String),
+        };
+        let fixed_uuid = Schema::Uuid {
+            inner: Box::new(Schema::Fixed {
+                name: Name::new("uuid"),
+                doc: None,
+                size: 16,
+                attributes: Default::default(),
+            }),
+        };
+        assert!(value.clone().resolve(&string_uuid).is_ok());
+        assert_eq!(value.clone().resolve(&fixed_uuid).unwrap(), value);
+        assert!(value.clone().resolve(&Schema::TimestampMicros).is_err());
+
+        let bytes = match value {
+            Value::Uuid(uuid) => uuid.as_bytes().to_vec(),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            Value::Fixed(16, bytes.clone())
+                .resolve(&fixed_uuid)
+                .unwrap(),
+            value
+        );
+        assert!(Value::Fixed(8, bytes[..8].to_vec())
+            .resolve(&fixed_uuid)
+            .is_err());
     }
 
     #[test]
@@ -1137,7 +1780,11 @@ mod tests {
             JsonValue::String("test_enum".into())
         );
         assert_eq!(
-            JsonValue::try_from(Value::Union(Box::new(Value::String("test_enum".into())))).unwrap(),
+            JsonValue::try_from(Value::Union(
+                None,
+                Box::new(Value::String("test_enum".into()))
+            ))
+            .unwrap(),
             JsonValue::String("test_enum".into())
         );
         assert_eq!(
@@ -1219,6 +1866,22 @@ mod tests {
             JsonValue::try_from(Value::TimestampMicros(1)).unwrap(),
             JsonValue::Number(1.into())
         );
+        assert_eq!(
+            JsonValue::try_from(Value::LocalTimestampMillis(1)).unwrap(),
+            JsonValue::Number(1.into())
+        );
+        assert_eq!(
+            JsonValue::try_from(Value::LocalTimestampMicros(1)).unwrap(),
+            JsonValue::Number(1.into())
+        );
+        assert_eq!(
+            JsonValue::try_from(Value::TimestampNanos(1)).unwrap(),
+            JsonValue::Number(1.into())
+        );
+        assert_eq!(
+            JsonValue::try_from(Value::LocalTimestampNanos(1)).unwrap(),
+            JsonValue::Number(1.into())
+        );
         assert_eq!(
             JsonValue::try_from(Value::Duration(
                 [1u8, 2u8, 3u8, 4u8, 5u8, 6u8, 7u8, 8u8, 9u8, 10u8, 11u8, 12u8].into()
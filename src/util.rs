@@ -1,6 +1,6 @@
 use crate::{AvroResult, Error};
 use serde_json::{Map, Value};
-use std::{convert::TryFrom, i64, io::Read, sync::Once};
+use std::{convert::TryFrom, fmt, i64, io::Read, sync::Once};
 
 /// Maximum number of bytes that can be allocated when decoding
 /// Avro-encoded values. This is a protection against ill-formed
@@ -120,6 +120,162 @@ pub fn safe_len(len: usize) -> AvroResult<usize> {
     }
 }
 
+/// A segment of the path to a value within a JSON document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum JsonPathSegment {
+    /// The value of a key of an object.
+    Key(String),
+    /// An item of an array.
+    Index(usize),
+}
+
+impl From<&str> for JsonPathSegment {
+    fn from(key: &str) -> Self {
+        JsonPathSegment::Key(key.to_string())
+    }
+}
+
+impl From<usize> for JsonPathSegment {
+    fn from(index: usize) -> Self {
+        JsonPathSegment::Index(index)
+    }
+}
+
+/// Format a path within a JSON document, e.g. `fields[12].type[1].items`. The root of the
+/// document is the empty path.
+pub(crate) fn format_json_path(path: &[JsonPathSegment]) -> String {
+    struct Path<'a>(&'a [JsonPathSegment]);
+
+    impl fmt::Display for Path<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for (position, segment) in self.0.iter().enumerate() {
+                match segment {
+                    JsonPathSegment::Key(key) if position == 0 => write!(f, "{}", key)?,
+                    JsonPathSegment::Key(key) => write!(f, ".{}", key)?,
+                    JsonPathSegment::Index(index) => write!(f, "[{}]", index)?,
+                }
+            }
+            Ok(())
+        }
+    }
+
+    Path(path).to_string()
+}
+
+/// Find the line and column, both starting at 1, of the value at `path` within the JSON document
+/// `input`. Returns `None` if there is no such value, or `input` is not well-formed.
+pub(crate) fn locate_json_path(input: &str, path: &[JsonPathSegment]) -> Option<(usize, usize)> {
+    let bytes = input.as_bytes();
+    let mut position = skip_whitespace(bytes, 0);
+    for segment in path {
+        match segment {
+            JsonPathSegment::Key(key) => {
+                if bytes.get(position) != Some(&b'{') {
+                    return None;
+                }
+                position = skip_whitespace(bytes, position + 1);
+                loop {
+                    let end = skip_string(bytes, position)?;
+                    let current: String = serde_json::from_str(&input[position..end]).ok()?;
+                    position = skip_whitespace(bytes, end);
+                    if bytes.get(position) != Some(&b':') {
+                        return None;
+                    }
+                    position = skip_whitespace(bytes, position + 1);
+                    if &current == key {
+                        break;
+                    }
+                    position = skip_whitespace(bytes, skip_value(bytes, position)?);
+                    if bytes.get(position) != Some(&b',') {
+                        return None;
+                    }
+                    position = skip_whitespace(bytes, position + 1);
+                }
+            }
+            JsonPathSegment::Index(index) => {
+                if bytes.get(position) != Some(&b'[') {
+                    return None;
+                }
+                position = skip_whitespace(bytes, position + 1);
+                for _ in 0..*index {
+                    position = skip_whitespace(bytes, skip_value(bytes, position)?);
+                    if bytes.get(position) != Some(&b',') {
+                        return None;
+                    }
+                    position = skip_whitespace(bytes, position + 1);
+                }
+                if bytes.get(position) == Some(&b']') {
+                    return None;
+                }
+            }
+        }
+    }
+
+    let before = input.get(..position)?;
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map_or(0, |line| line.chars().count())
+        + 1;
+    Some((line, column))
+}
+
+fn skip_whitespace(bytes: &[u8], mut position: usize) -> usize {
+    while matches!(bytes.get(position), Some(b) if b" \t\r\n".contains(b)) {
+        position += 1;
+    }
+    position
+}
+
+/// Return the position right after the JSON string starting at `position`.
+fn skip_string(bytes: &[u8], mut position: usize) -> Option<usize> {
+    if bytes.get(position) != Some(&b'"') {
+        return None;
+    }
+    position += 1;
+    loop {
+        match bytes.get(position)? {
+            b'"' => return Some(position + 1),
+            b'\\' => position += 2,
+            _ => position += 1,
+        }
+    }
+}
+
+/// Return the position right after the JSON value starting at `position`.
+fn skip_value(bytes: &[u8], mut position: usize) -> Option<usize> {
+    match bytes.get(position)? {
+        b'"' => skip_string(bytes, position),
+        b'{' | b'[' => {
+            let mut depth = 0;
+            loop {
+                match bytes.get(position)? {
+                    b'"' => {
+                        position = skip_string(bytes, position)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(position + 1);
+                        }
+                    }
+                    _ => (),
+                }
+                position += 1;
+            }
+        }
+        _ => {
+            while matches!(bytes.get(position), Some(b) if !b",}] \t\r\n".contains(b)) {
+                position += 1;
+            }
+            Some(position)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -193,6 +349,27 @@ mod tests {
         assert!(decode_variable(&mut &causes_left_shift_overflow[..]).is_err());
     }
 
+    #[test]
+    fn test_locate_json_path() {
+        let input = "{\n  \"name\": \"a\\\"b\",\n  \"fields\": [\n    {\"type\": [\"null\", {\"items\": \"é\"}]},\n    {\"type\": \"int\"}\n  ]\n}";
+        let path = vec![
+            "fields".into(),
+            0.into(),
+            "type".into(),
+            1.into(),
+            "items".into(),
+        ];
+        assert_eq!(format_json_path(&path), "fields[0].type[1].items");
+        assert_eq!(locate_json_path(input, &path), Some((4, 33)));
+        assert_eq!(
+            locate_json_path(input, &["fields".into(), 1.into(), "type".into()]),
+            Some((5, 14))
+        );
+        assert_eq!(locate_json_path(input, &[]), Some((1, 1)));
+        assert_eq!(locate_json_path(input, &["fields".into(), 2.into()]), None);
+        assert_eq!(locate_json_path(input, &["doc".into()]), None);
+    }
+
     #[test]
     fn test_safe_len() {
         assert_eq!(42usize, safe_len(42usize).unwrap());
@@ -1,7 +1,7 @@
 //! Logic handling writing in Avro format at user level.
 use crate::{
-    encode::{encode, encode_ref, encode_to_vec},
-    schema::Schema,
+    encode::{encode, encode_internal, encode_to_vec},
+    schema::{Names, Schema},
     ser::Serializer,
     types::Value,
     AvroResult, Codec, Error,
@@ -32,6 +32,9 @@ pub struct Writer<'a, W> {
     marker: Vec<u8>,
     #[builder(default = false, setter(skip))]
     has_header: bool,
+    // The named types of the schema are looked up once for all the values written.
+    #[builder(default = Names::new(schema), setter(skip))]
+    names: Names<'a>,
 }
 
 impl<'a, W: Write> Writer<'a, W> {
@@ -76,7 +79,7 @@ impl<'a, W: Write> Writer<'a, W> {
         };
 
         let avro = value.into();
-        write_value_ref(self.schema, &avro, &mut self.buffer)?;
+        write_value_ref(self.schema, &mut self.names, &avro, &mut self.buffer)?;
 
         self.num_values += 1;
 
@@ -104,7 +107,7 @@ impl<'a, W: Write> Writer<'a, W> {
             0
         };
 
-        write_value_ref(self.schema, value, &mut self.buffer)?;
+        write_value_ref(self.schema, &mut self.names, value, &mut self.buffer)?;
 
         self.num_values += 1;
 
@@ -261,7 +264,7 @@ impl<'a, W: Write> Writer<'a, W> {
 
     /// Append a raw Avro Value to the payload avoiding to encode it again.
     fn append_raw(&mut self, value: &Value, schema: &Schema) -> AvroResult<usize> {
-        self.append_bytes(encode_to_vec(&value, schema).as_ref())
+        self.append_bytes(encode_to_vec(&value, schema)?.as_ref())
     }
 
     /// Append pure bytes to the payload.
@@ -285,7 +288,7 @@ impl<'a, W: Write> Writer<'a, W> {
             &metadata.into(),
             &Schema::Map(Box::new(Schema::Bytes)),
             &mut header,
-        );
+        )?;
         header.extend_from_slice(&self.marker);
 
         Ok(header)
@@ -303,19 +306,19 @@ fn write_avro_datum<T: Into<Value>>(
     buffer: &mut Vec<u8>,
 ) -> Result<(), Error> {
     let avro = value.into();
-    if !avro.validate(schema) {
-        return Err(Error::Validation);
-    }
-    encode(&avro, schema, buffer);
-    Ok(())
+    write_value_ref(schema, &mut Names::new(schema), &avro, buffer)
 }
 
-fn write_value_ref(schema: &Schema, value: &Value, buffer: &mut Vec<u8>) -> AvroResult<()> {
-    if !value.validate(schema) {
+fn write_value_ref<'s>(
+    schema: &'s Schema,
+    names: &mut Names<'s>,
+    value: &Value,
+    buffer: &mut Vec<u8>,
+) -> AvroResult<()> {
+    if !value.validate_with_names(schema, names) {
         return Err(Error::Validation);
     }
-    encode_ref(value, schema, buffer);
-    Ok(())
+    encode_internal(value, schema, names, buffer)
 }
 
 /// Encode a compatible value (implementing the `ToAvro` trait) into Avro format, also
@@ -334,7 +337,7 @@ pub fn to_avro_datum<T: Into<Value>>(schema: &Schema, value: T) -> AvroResult<Ve
 mod tests {
     use super::*;
     use crate::{
-        decimal::Decimal,
+        decimal::{BigDecimal, Decimal},
         duration::{Days, Duration, Millis, Months},
         schema::Name,
         types::Record,
@@ -381,7 +384,7 @@ mod tests {
     #[test]
     fn test_union_not_null() {
         let schema = Schema::parse_str(UNION_SCHEMA).unwrap();
-        let union = Value::Union(Box::new(Value::Long(3)));
+        let union = Value::Union(None, Box::new(Value::Long(3)));
 
         let mut expected = Vec::new();
         zig_i64(1, &mut expected);
@@ -393,7 +396,7 @@ mod tests {
     #[test]
     fn test_union_null() {
         let schema = Schema::parse_str(UNION_SCHEMA).unwrap();
-        let union = Value::Union(Box::new(Value::Null));
+        let union = Value::Union(None, Box::new(Value::Null));
 
         let mut expected = Vec::new();
         zig_i64(0, &mut expected);
@@ -481,12 +484,58 @@ mod tests {
         )
     }
 
+    #[test]
+    fn local_timestamp_millis() -> TestResult<()> {
+        logical_type_test(
+            r#"{"type": "long", "logicalType": "local-timestamp-millis"}"#,
+            &Schema::LocalTimestampMillis,
+            Value::LocalTimestampMillis(1_i64),
+            &Schema::Long,
+            1_i64,
+        )
+    }
+
+    #[test]
+    fn local_timestamp_micros() -> TestResult<()> {
+        logical_type_test(
+            r#"{"type": "long", "logicalType": "local-timestamp-micros"}"#,
+            &Schema::LocalTimestampMicros,
+            Value::LocalTimestampMicros(1_i64),
+            &Schema::Long,
+            1_i64,
+        )
+    }
+
+    #[test]
+    fn timestamp_nanos() -> TestResult<()> {
+        logical_type_test(
+            r#"{"type": "long", "logicalType": "timestamp-nanos"}"#,
+            &Schema::TimestampNanos,
+            Value::TimestampNanos(1_i64),
+            &Schema::Long,
+            1_i64,
+        )
+    }
+
+    #[test]
+    fn local_timestamp_nanos() -> TestResult<()> {
+        logical_type_test(
+            r#"{"type": "long", "logicalType": "local-timestamp-nanos"}"#,
+            &Schema::LocalTimestampNanos,
+            Value::LocalTimestampNanos(1_i64),
+            &Schema::Long,
+            1_i64,
+        )
+    }
+
     #[test]
     fn decimal_fixed() -> TestResult<()> {
         let size = 30;
         let inner = Schema::Fixed {
             name: Name::new("decimal"),
             size,
+            doc: None,
+            attributes: Default::default(),
         };
         let value = vec![0u8; size];
         logical_type_test(
@@ -519,11 +568,79 @@ mod tests {
         )
     }
 
+    #[test]
+    fn big_decimal() -> TestResult<()> {
+        let decimal: BigDecimal = "-123.45".parse()?;
+        logical_type_test(
+            r#"{"type": "bytes", "logicalType": "big-decimal"}"#,
+            &Schema::BigDecimal,
+            Value::BigDecimal(decimal.clone()),
+            &Schema::Bytes,
+            Vec::from(&decimal),
+        )?;
+
+        // Strings, like the ones serialized by serde, are written as the number they represent.
+        let schema = Schema::BigDecimal;
+        assert_eq!(
+            to_avro_datum(&schema, "-123.45")?,
+            to_avro_datum(&schema, decimal)?
+        );
+        assert!(to_avro_datum(&schema, "-123,45").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn uuid_string() -> TestResult<()> {
+        let uuid = "550e8400-e29b-41d4-a716-446655440000";
+        logical_type_test(
+            r#"{"type": "string", "logicalType": "uuid"}"#,
+            &Schema::Uuid {
+                inner: Box::new(Schema::String),
+            },
+            Value::Uuid(uuid.parse()?),
+            &Schema::String,
+            uuid.to_string(),
+        )
+    }
+
+    #[test]
+    fn uuid_fixed() -> TestResult<()> {
+        let inner = Schema::Fixed {
+            name: Name::new("uuid"),
+            size: 16,
+            doc: None,
+            attributes: Default::default(),
+        };
+        let uuid: uuid::Uuid = "550e8400-e29b-41d4-a716-446655440000".parse()?;
+        logical_type_test(
+            r#"{"type": "fixed", "name": "uuid", "size": 16, "logicalType": "uuid"}"#,
+            &Schema::Uuid {
+                inner: Box::new(inner.clone()),
+            },
+            Value::Uuid(uuid),
+            &inner,
+            Value::Fixed(16, uuid.as_bytes().to_vec()),
+        )?;
+
+        // Strings, like the ones serialized by serde, are written as the uuid they represent.
+        let schema = Schema::parse_str(
+            r#"{"type": "fixed", "name": "uuid", "size": 16, "logicalType": "uuid"}"#,
+        )?;
+        assert_eq!(
+            to_avro_datum(&schema, uuid.to_string())?,
+            uuid.as_bytes().to_vec()
+        );
+        assert!(to_avro_datum(&schema, "not a uuid").is_err());
+        Ok(())
+    }
+
     #[test]
     fn duration() -> TestResult<()> {
         let inner = Schema::Fixed {
             name: Name::new("duration"),
             size: 12,
+            doc: None,
+            attributes: Default::default(),
         };
         let value = Value::Duration(Duration::new(
             Months::new(256),
@@ -762,11 +879,11 @@ mod tests {
         let mut record1 = Record::new(&schema).unwrap();
         record1.put(
             "a",
-            Value::Union(Box::new(Value::TimestampMicros(1234_i64))),
+            Value::Union(None, Box::new(Value::TimestampMicros(1234_i64))),
         );
 
         let mut record2 = Record::new(&schema).unwrap();
-        record2.put("a", Value::Union(Box::new(Value::Null)));
+        record2.put("a", Value::Union(None, Box::new(Value::Null)));
 
         let n1 = writer.append(record1).unwrap();
         let n2 = writer.append(record2).unwrap();
@@ -1,5 +1,5 @@
 //! Port of https://github.com/apache/avro/blob/release-1.9.1/lang/py/test/test_schema.py
-use avro_rs::{schema::Name, Error, Schema};
+use avro_rs::{from_avro_datum, schema::Name, to_avro_datum, types::Value, Error, Schema};
 use lazy_static::lazy_static;
 
 const PRIMITIVE_EXAMPLES: &[(&str, bool)] = &[
@@ -38,6 +38,12 @@ const FIXED_EXAMPLES: &[(&str, bool)] = &[
     ),
     (r#"{"type": "fixed", "name": "Missing size"}"#, false),
     (r#"{"type": "fixed", "size": 314}"#, false),
+    (r#"{"type": "fixed", "name": "Test", "size": -1}"#, false),
+    (r#"{"type": "fixed", "name": "1Test", "size": 1}"#, false),
+    (
+        r#"{"type": "fixed", "name": "Test", "namespace": "org.apache-avro", "size": 1}"#,
+        false,
+    ),
 ];
 
 const ENUM_EXAMPLES: &[(&str, bool)] = &[
@@ -76,6 +82,10 @@ const ENUM_EXAMPLES: &[(&str, bool)] = &[
             }"#,
         false,
     ),
+    (
+        r#"{"type": "enum", "name": "Test", "symbols": ["A", "1-bad"]}"#,
+        false,
+    ),
 ];
 
 const ARRAY_EXAMPLES: &[(&str, bool)] = &[
@@ -133,8 +143,6 @@ const RECORD_EXAMPLES: &[(&str, bool)] = &[
         true
     ),
     */
-    /*
-    // TODO: (#92) properly support recursive types and uncomment
     (
         r#"{
             "type": "record",
@@ -144,7 +152,7 @@ const RECORD_EXAMPLES: &[(&str, bool)] = &[
                 {"name": "children", "type": {"type": "array", "items": "Node"}}
             ]
         }"#,
-        true
+        true,
     ),
     (
         r#"{
@@ -167,7 +175,7 @@ const RECORD_EXAMPLES: &[(&str, bool)] = &[
                 }
             ]
         }"#,
-        true
+        true,
     ),
     (
         r#"{
@@ -180,9 +188,9 @@ const RECORD_EXAMPLES: &[(&str, bool)] = &[
                 {"name": "serverHash", "type": "MD5"},
                 {"name": "meta", "type": ["null", {"type": "map", "values": "bytes"}]}
             ]
-        }"#, true
+        }"#,
+        true,
     ),
-    */
     (
         r#"{
                 "type":"record",
@@ -512,6 +520,121 @@ const TIMESTAMPMICROS_LOGICAL_TYPE: &[(&str, bool)] = &[
     ),
 ];
 
+const LOCAL_TIMESTAMP_LOGICAL_TYPES: &[(&str, bool)] = &[
+    (
+        r#"{"type": "long", "logicalType": "local-timestamp-millis"}"#,
+        true,
+    ),
+    (
+        r#"{"type": "long", "logicalType": "local-timestamp-micros"}"#,
+        true,
+    ),
+    (
+        r#"{"type": "int", "logicalType": "local-timestamp-millis"}"#,
+        false,
+    ),
+    (
+        r#"{"type": "string", "logicalType": "local-timestamp-micros"}"#,
+        false,
+    ),
+];
+
+const TIMESTAMP_NANOS_LOGICAL_TYPES: &[(&str, bool)] = &[
+    (
+        r#"{"type": "long", "logicalType": "timestamp-nanos"}"#,
+        true,
+    ),
+    (
+        r#"{"type": "long", "logicalType": "local-timestamp-nanos"}"#,
+        true,
+    ),
+    (
+        r#"{"type": "int", "logicalType": "timestamp-nanos"}"#,
+        false,
+    ),
+    (
+        r#"{"type": "double", "logicalType": "local-timestamp-nanos"}"#,
+        false,
+    ),
+];
+
+const BIG_DECIMAL_LOGICAL_TYPE: &[(&str, bool)] = &[
+    (r#"{"type": "bytes", "logicalType": "big-decimal"}"#, true),
+    (
+        r#"{"type": "fixed", "name": "BigDecimal", "size": 16, "logicalType": "big-decimal"}"#,
+        false,
+    ),
+    (r#"{"type": "string", "logicalType": "big-decimal"}"#, false),
+];
+
+const UUID_LOGICAL_TYPE: &[(&str, bool)] = &[
+    (r#"{"type": "string", "logicalType": "uuid"}"#, true),
+    (
+        r#"{"type": "fixed", "name": "Id", "size": 16, "logicalType": "uuid"}"#,
+        true,
+    ),
+    (
+        r#"{"type": {"type": "fixed", "name": "Id", "size": 16}, "logicalType": "uuid"}"#,
+        true,
+    ),
+    (
+        r#"{"type": "fixed", "name": "Id", "size": 12, "logicalType": "uuid"}"#,
+        false,
+    ),
+    (r#"{"type": "bytes", "logicalType": "uuid"}"#, false),
+];
+
+const DEFAULT_EXAMPLES: &[(&str, bool)] = &[
+    (
+        r#"{"type": "record", "name": "R", "fields": [{"name": "f", "type": "int", "default": 5}]}"#,
+        true,
+    ),
+    (
+        r#"{"type": "record", "name": "R", "fields": [{"name": "f", "type": "int", "default": 1.5}]}"#,
+        false,
+    ),
+    (
+        r#"{"type": "record", "name": "R", "fields": [{"name": "f", "type": "int", "default": 2147483648}]}"#,
+        false,
+    ),
+    (
+        r#"{"type": "record", "name": "R", "fields": [{"name": "f", "type": "string", "default": null}]}"#,
+        false,
+    ),
+    (
+        r#"{"type": "record", "name": "R", "fields": [{"name": "f", "type": "bytes", "default": "\u00ff"}]}"#,
+        true,
+    ),
+    (
+        r#"{"type": "record", "name": "R", "fields": [{"name": "f", "type": "bytes", "default": "\u0100"}]}"#,
+        false,
+    ),
+    (
+        r#"{"type": "record", "name": "R", "fields": [{"name": "f", "type": {"type": "fixed", "name": "F", "size": 2}, "default": "a"}]}"#,
+        false,
+    ),
+    (
+        r#"{"type": "record", "name": "R", "fields": [{"name": "f", "type": ["null", "int"], "default": null}]}"#,
+        true,
+    ),
+    (
+        r#"{"type": "record", "name": "R", "fields": [{"name": "f", "type": ["null", "int"], "default": 5}]}"#,
+        false,
+    ),
+    (
+        r#"{"type": "record", "name": "R", "fields": [{"name": "f", "type": {"type": "enum", "name": "E", "symbols": ["A"]}, "default": "B"}]}"#,
+        false,
+    ),
+    (
+        r#"{"type": "record", "name": "R", "fields": [{"name": "f", "type": {"type": "record", "name": "S", "fields": [{"name": "a", "type": "int"}, {"name": "b", "type": "int", "default": 0}]}, "default": {"a": 1}}]}"#,
+        true,
+    ),
+    (
+        r#"{"type": "record", "name": "R", "fields": [{"name": "f", "type": {"type": "record", "name": "S", "fields": [{"name": "a", "type": "int"}]}, "default": {}}]}"#,
+        false,
+    ),
+];
+
 lazy_static! {
     static ref EXAMPLES: Vec<(&'static str, bool)> = Vec::new()
         .iter()
@@ -532,6 +655,11 @@ lazy_static! {
         .chain(TIMEMICROS_LOGICAL_TYPE.iter().copied())
         .chain(TIMESTAMPMILLIS_LOGICAL_TYPE.iter().copied())
         .chain(TIMESTAMPMICROS_LOGICAL_TYPE.iter().copied())
+        .chain(LOCAL_TIMESTAMP_LOGICAL_TYPES.iter().copied())
+        .chain(TIMESTAMP_NANOS_LOGICAL_TYPES.iter().copied())
+        .chain(BIG_DECIMAL_LOGICAL_TYPE.iter().copied())
+        .chain(UUID_LOGICAL_TYPE.iter().copied())
+        .chain(DEFAULT_EXAMPLES.iter().copied())
         .collect();
     static ref VALID_EXAMPLES: Vec<(&'static str, bool)> =
         EXAMPLES.iter().copied().filter(|s| s.1).collect();
@@ -968,6 +1096,64 @@ fn test_parse_list_shared_dependency() {
     }
 }
 
+#[test]
+fn test_parse_list_nested_dependency() {
+    let schema_str_1 = r#"{
+        "name": "User",
+        "namespace": "com.ex",
+        "type": "record",
+        "fields": [
+            {"name": "address", "type": {
+                "name": "Address",
+                "type": "record",
+                "fields": [
+                    {"name": "country", "type": {"name": "Country", "type": "enum", "symbols": ["FR"]}}
+                ]
+            }}
+        ]
+    }"#;
+    let schema_str_2 = r#"{
+        "name": "Order",
+        "namespace": "com.ex",
+        "type": "record",
+        "fields": [
+            {"name": "billing", "type": "com.ex.Address"},
+            {"name": "shipping", "type": "Address"},
+            {"name": "origin", "type": "Country"}
+        ]
+    }"#;
+    let schema_composite = r#"{
+        "name": "Order",
+        "namespace": "com.ex",
+        "type": "record",
+        "fields": [
+            {"name": "billing", "type": {
+                "name": "Address",
+                "type": "record",
+                "fields": [
+                    {"name": "country", "type": {"name": "Country", "type": "enum", "symbols": ["FR"]}}
+                ]
+            }},
+            {"name": "shipping", "type": "Address"},
+            {"name": "origin", "type": "Country"}
+        ]
+    }"#;
+
+    let parsed = vec![
+        Schema::parse_str(schema_str_1).expect("Test failed"),
+        Schema::parse_str(schema_composite).expect("Test failed"),
+    ];
+    let schema_strs = vec![schema_str_1, schema_str_2];
+    for schema_str_perm in permutations(&schema_strs) {
+        let schema_str_perm: Vec<&str> = schema_str_perm.iter().map(|s| **s).collect();
+        let schemas = Schema::parse_list(&schema_str_perm).expect("Test failed");
+        assert_eq!(schemas.len(), 2);
+        for parsed_schema in &parsed {
+            assert!(schemas.contains(parsed_schema));
+        }
+    }
+}
+
 #[test]
 /// Test that trying to parse two schemas with the same fullname returns an Error
 fn test_name_collision_error() {
@@ -1090,6 +1276,228 @@ fn test_fullname_name_namespace_and_default_namespace_specified() {
     assert_eq!("o.a.a.a", fullname);
 }
 
+#[test]
+fn test_fullname_aliases() {
+    let name: Name =
+        serde_json::from_str(r#"{"name": "a", "namespace": "o.a.h", "aliases": ["b", "o.a.i.c"]}"#)
+            .unwrap();
+    assert_eq!(name.fullname_aliases(), vec!["o.a.h.b", "o.a.i.c"]);
+}
+
+/// Return the fullnames of the named types defined in a schema, in definition order.
+fn defined_fullnames(schema: &Schema) -> Vec<String> {
+    fn collect(schema: &Schema, fullnames: &mut Vec<String>) {
+        match schema {
+            Schema::Record { name, fields, .. } => {
+                fullnames.push(name.fullname(None));
+                for field in fields {
+                    collect(&field.schema, fullnames);
+                }
+            }
+            Schema::Enum { name, .. } | Schema::Fixed { name, .. } => {
+                fullnames.push(name.fullname(None))
+            }
+            Schema::Array(inner) | Schema::Map(inner) => collect(inner, fullnames),
+            Schema::Union(union) => {
+                for variant in union.variants() {
+                    collect(variant, fullnames);
+                }
+            }
+            _ => (),
+        }
+    }
+    let mut fullnames = Vec::new();
+    collect(schema, &mut fullnames);
+    fullnames
+}
+
+#[test]
+fn test_nested_named_types_inherit_namespace() {
+    let schema = Schema::parse_str(
+        r#"{
+            "type": "record",
+            "name": "User",
+            "namespace": "com.acme",
+            "fields": [
+                {"name": "address", "type": {
+                    "type": "record",
+                    "name": "Address",
+                    "fields": [
+                        {"name": "country", "type": {"type": "enum", "name": "Country", "symbols": ["FR", "US"]}}
+                    ]
+                }},
+                {"name": "hash", "type": {"type": "fixed", "name": "MD5", "size": 16}}
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        defined_fullnames(&schema),
+        vec![
+            "com.acme.User",
+            "com.acme.Address",
+            "com.acme.Country",
+            "com.acme.MD5"
+        ]
+    );
+}
+
+#[test]
+fn test_nested_named_types_explicit_namespace() {
+    let schema = Schema::parse_str(
+        r#"{
+            "type": "record",
+            "name": "User",
+            "namespace": "com.acme",
+            "fields": [
+                {"name": "address", "type": {
+                    "type": "record",
+                    "name": "Address",
+                    "namespace": "com.other",
+                    "fields": [
+                        {"name": "country", "type": {"type": "enum", "name": "Country", "symbols": ["FR", "US"]}}
+                    ]
+                }},
+                {"name": "hash", "type": {"type": "fixed", "name": "org.hash.MD5", "namespace": "ignored", "size": 16}},
+                {"name": "status", "type": {"type": "enum", "name": "Status", "namespace": "", "symbols": ["ACTIVE"]}}
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        defined_fullnames(&schema),
+        vec![
+            "com.acme.User",
+            "com.other.Address",
+            "com.other.Country",
+            "org.hash.MD5",
+            "Status"
+        ]
+    );
+}
+
+#[test]
+fn test_nested_named_types_inherit_namespace_of_fullname() {
+    let schema = Schema::parse_str(
+        r#"{
+            "type": "record",
+            "name": "com.acme.User",
+            "fields": [
+                {"name": "address", "type": {"type": "record", "name": "Address", "fields": []}}
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        defined_fullnames(&schema),
+        vec!["com.acme.User", "com.acme.Address"]
+    );
+}
+
+#[test]
+fn test_references_are_resolved_in_enclosing_namespace() {
+    let schema = Schema::parse_str(
+        r#"{
+            "type": "record",
+            "name": "User",
+            "namespace": "com.acme",
+            "fields": [
+                {"name": "home", "type": {"type": "record", "name": "Address", "fields": []}},
+                {"name": "work", "type": "Address"},
+                {"name": "previous", "type": "com.acme.Address"}
+            ]
+        }"#,
+    )
+    .unwrap();
+    if let Schema::Record { fields, .. } = schema {
+        for field in &fields[1..] {
+            match field.schema {
+                Schema::Ref { ref name } => assert_eq!(name.fullname(None), "com.acme.Address"),
+                ref other => panic!("Expected a reference, got {:?}", other),
+            }
+        }
+    } else {
+        panic!("Expected a record schema");
+    }
+}
+
+#[test]
+fn test_references_fall_back_to_null_namespace() {
+    let schema = Schema::parse_str(
+        r#"{
+            "type": "record",
+            "name": "Wrapper",
+            "fields": [
+                {"name": "first", "type": {"type": "fixed", "name": "Hash", "size": 16}},
+                {"name": "nested", "type": {
+                    "type": "record",
+                    "name": "Nested",
+                    "namespace": "com.acme",
+                    "fields": [
+                        {"name": "second", "type": "Hash"}
+                    ]
+                }}
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        schema.canonical_form(),
+        r#"{"name":"Wrapper","type":"record","fields":[{"name":"first","type":{"name":"Hash","type":"fixed","size":16}},{"name":"nested","type":{"name":"com.acme.Nested","type":"record","fields":[{"name":"second","type":"Hash"}]}}]}"#
+    );
+}
+
+#[test]
+fn test_references_to_undefined_names_in_namespace() {
+    let schema = Schema::parse_str(
+        r#"{
+            "type": "record",
+            "name": "User",
+            "namespace": "com.acme",
+            "fields": [
+                {"name": "address", "type": {"type": "record", "name": "Address", "namespace": "com.other", "fields": []}},
+                {"name": "work", "type": "Address"}
+            ]
+        }"#,
+    );
+    assert!(schema.is_err());
+}
+
+#[test]
+fn test_canonical_form_with_inherited_namespace() {
+    let inherited = Schema::parse_str(
+        r#"{
+            "type": "record",
+            "name": "User",
+            "namespace": "com.acme",
+            "fields": [
+                {"name": "home", "type": {"type": "record", "name": "Address", "fields": [
+                    {"name": "country", "type": {"type": "enum", "name": "Country", "symbols": ["FR", "US"]}}
+                ]}},
+                {"name": "work", "type": ["null", "Address"]}
+            ]
+        }"#,
+    )
+    .unwrap();
+    let qualified = Schema::parse_str(
+        r#"{
+            "type": "record",
+            "name": "com.acme.User",
+            "fields": [
+                {"name": "home", "type": {"type": "record", "name": "com.acme.Address", "fields": [
+                    {"name": "country", "type": {"type": "enum", "name": "com.acme.Country", "symbols": ["FR", "US"]}}
+                ]}},
+                {"name": "work", "type": ["null", "com.acme.Address"]}
+            ]
+        }"#,
+    )
+    .unwrap();
+    let expected = r#"{"name":"com.acme.User","type":"record","fields":[{"name":"home","type":{"name":"com.acme.Address","type":"record","fields":[{"name":"country","type":{"name":"com.acme.Country","type":"enum","symbols":["FR","US"]}}]}},{"name":"work","type":["null","com.acme.Address"]}]}"#;
+    assert_eq!(inherited.canonical_form(), expected);
+    assert_eq!(qualified.canonical_form(), expected);
+    assert_eq!(inherited, qualified);
+}
+
 #[test]
 fn test_doc_attributes() {
     fn assert_doc(schema: &Schema) {
@@ -1111,39 +1519,134 @@ fn test_doc_attributes() {
     }
 }
 
-/*
-TODO: (#94) add support for user-defined attributes and uncomment (may need some tweaks to compile)
 #[test]
 fn test_other_attributes() {
-    fn assert_attribute_type(attribute: (String, serde_json::Value)) {
-        match attribute.1.as_ref() {
-            "cp_boolean" => assert!(attribute.2.is_bool()),
-            "cp_int" => assert!(attribute.2.is_i64()),
-            "cp_object" => assert!(attribute.2.is_object()),
-            "cp_float" => assert!(attribute.2.is_f64()),
-            "cp_array" => assert!(attribute.2.is_array()),
+    fn assert_attribute_type(attribute: (&String, &serde_json::Value)) {
+        match attribute.0.as_ref() {
+            "cp_boolean" => assert!(attribute.1.is_boolean()),
+            "cp_int" => assert!(attribute.1.is_i64()),
+            "cp_object" => assert!(attribute.1.is_object()),
+            "cp_float" => assert!(attribute.1.is_f64()),
+            "cp_array" => assert!(attribute.1.is_array()),
+            "cp_string" => assert!(attribute.1.is_string()),
+            "cp_null" => assert!(attribute.1.is_null()),
+            "date" => assert!(attribute.1.is_string()),
+            other => panic!("Unexpected attribute {}", other),
         }
     }
 
     for (raw_schema, _) in OTHER_ATTRIBUTES_EXAMPLES.iter() {
         let schema = Schema::parse_str(raw_schema).unwrap();
+        let attributes = schema.attributes().unwrap();
         // all inputs have at least some user-defined attributes
-        assert!(schema.other_attributes.is_some());
-        for prop in schema.other_attributes.unwrap().iter() {
+        assert!(!attributes.is_empty());
+        for prop in attributes.iter() {
             assert_attribute_type(prop);
         }
         if let Schema::Record { fields, .. } = schema {
-           for f in fields {
-               // all fields in the record have at least some user-defined attributes
-               assert!(f.schema.other_attributes.is_some());
-               for prop in f.schema.other_attributes.unwrap().iter() {
-                   assert_attribute_type(prop);
-               }
-           }
+            for f in fields {
+                // all fields in the record have at least some user-defined attributes
+                assert!(!f.attributes.is_empty());
+                for prop in f.attributes.iter() {
+                    assert_attribute_type(prop);
+                }
+            }
         }
     }
 }
-*/
+
+#[test]
+/// Test that serializing a parsed schema gives back the same JSON, custom attributes included.
+fn test_lossless_round_trip() {
+    let raw_schema = r#"{
+        "type": "record",
+        "name": "User",
+        "namespace": "com.acme",
+        "doc": "A user",
+        "aliases": ["Customer"],
+        "connect.name": "com.acme.User",
+        "fields": [
+            {
+                "name": "id",
+                "type": {"type": "string", "logicalType": "varchar", "maxLength": 36},
+                "doc": "The identifier",
+                "order": "descending",
+                "aliases": ["uid"],
+                "x-pii": false
+            },
+            {
+                "name": "status",
+                "type": {
+                    "type": "enum",
+                    "name": "Status",
+                    "namespace": "com.acme.status",
+                    "doc": "The status",
+                    "aliases": ["State"],
+                    "symbols": ["ACTIVE", "INACTIVE"],
+                    "x-since": 2
+                },
+                "default": "ACTIVE"
+            },
+            {
+                "name": "hash",
+                "type": {
+                    "type": "fixed",
+                    "name": "MD5",
+                    "namespace": "com.acme",
+                    "doc": "An MD5 hash",
+                    "aliases": ["Hash"],
+                    "size": 16,
+                    "x-algorithm": "md5"
+                }
+            },
+            {
+                "name": "tags",
+                "type": {"type": "map", "values": {"type": "array", "items": "string", "x-sorted": true}, "x-max": 10}
+            },
+            {"name": "email", "type": ["null", {"type": "string", "x-pii": true}], "default": null}
+        ]
+    }"#;
+    let schema = Schema::parse_str(raw_schema).unwrap();
+    let expected: serde_json::Value = serde_json::from_str(raw_schema).unwrap();
+    assert_eq!(serde_json::to_value(&schema).unwrap(), expected);
+    assert_eq!(Schema::parse_str(&expected.to_string()).unwrap(), schema);
+}
+
+#[test]
+/// Test that the custom attributes of the schemas which are not the type of a record field are
+/// kept as well.
+fn test_lossless_round_trip_without_record() {
+    for raw_schema in &[
+        r#"{"type": "array", "items": "int", "x-pii": true}"#,
+        r#"{"type": "string", "connect.name": "foo"}"#,
+        r#"{"type": "long", "logicalType": "foo-bar"}"#,
+        r#"["null", {"type": "string", "x-pii": true}, {"type": "array", "items": {"type": "long", "logicalType": "foo-bar"}}]"#,
+        r#"{"type": "array", "items": {"type": "int", "x-unit": "cm"}, "x-max": 10}"#,
+        r#"{"type": "map", "values": ["null", {"type": "bytes", "connect.name": "raw"}]}"#,
+        r#"{"type": "map", "values": {"type": "record", "name": "R", "x-since": 2, "fields": [
+            {"name": "a", "type": {"type": "int", "x-unit": "s"}}
+        ]}}"#,
+    ] {
+        let schema = Schema::parse_str(raw_schema).unwrap();
+        let expected: serde_json::Value = serde_json::from_str(raw_schema).unwrap();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), expected);
+        assert_eq!(Schema::parse_str(&expected.to_string()).unwrap(), schema);
+    }
+
+    // The attributes are kept without changing how the values of the schema are handled.
+    let schema = Schema::parse_str(r#"{"type": "string", "connect.name": "foo"}"#).unwrap();
+    assert_eq!(
+        schema.attribute("connect.name"),
+        Some(&serde_json::Value::from("foo"))
+    );
+    let value = Value::String("bar".to_string());
+    assert!(value.validate(&schema));
+    let encoded = to_avro_datum(&schema, value.clone()).unwrap();
+    assert_eq!(
+        from_avro_datum(&schema, &mut encoded.as_slice(), None).unwrap(),
+        value
+    );
+}
 
 #[test]
 fn test_root_error_is_not_swallowed_on_parse_error() -> Result<(), String> {